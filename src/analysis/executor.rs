@@ -107,7 +107,7 @@ impl Executor {
             loop {
                 self.visited.insert(section_address, state.address);
 
-                let ins = match disassemble(section, state.address.address) {
+                let ins = match disassemble(obj, section, state.address.address) {
                     Some(ins) => ins,
                     None => return Ok(None),
                 };
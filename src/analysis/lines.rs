@@ -0,0 +1,242 @@
+//! Split hints from DWARF 2+ line number programs (`.debug_line`).
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+use anyhow::{anyhow, ensure, Result};
+
+use crate::{
+    obj::{ObjInfo, ObjKind, ObjSplit},
+    util::{
+        dwarf::{read_string, read_uleb128},
+        reader::{Endian, FromReader},
+    },
+};
+
+/// Creates autogenerated splits from the address ranges covered by each `.debug_line`
+/// program. Units are named after the program's first file entry, the compilation unit's
+/// primary source file, so rows for inlined header code stay with their unit. Adjacent
+/// ranges of the same unit are merged. Objects without `.debug_line` are unchanged.
+pub fn splits_from_dwarf(obj: &mut ObjInfo) -> Result<()> {
+    ensure!(obj.kind == ObjKind::Executable, "Line info splits require an executable");
+    let Some(debug_line) = obj.raw_sections.iter().find(|s| s.name == ".debug_line") else {
+        return Ok(());
+    };
+    let mut ranges = read_line_ranges(&debug_line.data, obj.endian)?;
+    ranges.sort_by_key(|&(start, _, _)| start);
+
+    let mut merged = Vec::<(u32, u32, String)>::with_capacity(ranges.len());
+    for (start, end, file) in ranges {
+        match merged.last_mut() {
+            Some(last) if last.2 == file && last.1 >= start => last.1 = last.1.max(end),
+            _ => merged.push((start, end, file)),
+        }
+    }
+    for (start, end, unit) in merged {
+        let Ok((section_index, section)) = obj.sections.at_address(start) else {
+            log::warn!("Line info for {} @ {:#010X} is outside of any section", unit, start);
+            continue;
+        };
+        let end = end.min((section.address + section.size) as u32);
+        obj.add_split(section_index, start, ObjSplit {
+            unit,
+            end,
+            align: None,
+            common: false,
+            autogenerated: true,
+            skip: false,
+            rename: None,
+        })?;
+    }
+    Ok(())
+}
+
+/// Runs each line number program, returning the `(start, end, primary file name)` ranges
+/// between consecutive rows. Line and column information is skipped.
+fn read_line_ranges(data: &[u8], e: Endian) -> Result<Vec<(u32, u32, String)>> {
+    let mut reader = Cursor::new(data);
+    // Rows of (address, file name), with `None` marking the end of a sequence
+    let mut rows = Vec::<(u32, Option<String>)>::new();
+    while reader.position() < data.len() as u64 {
+        let unit_length = u32::from_reader(&mut reader, e)?;
+        ensure!(unit_length != u32::MAX, "64-bit DWARF is not supported");
+        let unit_end = reader.position() + unit_length as u64;
+        let version = u16::from_reader(&mut reader, e)?;
+        ensure!((2..=4).contains(&version), "Unsupported .debug_line version {version}");
+        let header_length = u32::from_reader(&mut reader, e)?;
+        let program_start = reader.position() + header_length as u64;
+        let min_inst_length = u8::from_reader(&mut reader, e)? as u32;
+        if version >= 4 {
+            let _max_ops_per_inst = u8::from_reader(&mut reader, e)?;
+        }
+        let _default_is_stmt = u8::from_reader(&mut reader, e)?;
+        let _line_base = u8::from_reader(&mut reader, e)?;
+        let line_range = u8::from_reader(&mut reader, e)? as u32;
+        ensure!(line_range != 0, "Invalid .debug_line line range");
+        let opcode_base = u8::from_reader(&mut reader, e)?;
+        let mut opcode_lengths = vec![0u8; opcode_base.saturating_sub(1) as usize];
+        reader.read_exact(&mut opcode_lengths)?;
+        // Include directories
+        while !read_string(&mut reader)?.is_empty() {}
+        let mut files = vec![];
+        loop {
+            let name = read_string(&mut reader)?;
+            if name.is_empty() {
+                break;
+            }
+            skip_file_attributes(&mut reader)?;
+            files.push(name);
+        }
+        let primary =
+            files.first().cloned().ok_or_else(|| anyhow!(".debug_line program has no files"))?;
+
+        reader.seek(SeekFrom::Start(program_start))?;
+        let mut address = 0u32;
+        while reader.position() < unit_end {
+            let opcode = u8::from_reader(&mut reader, e)?;
+            if opcode >= opcode_base {
+                let advance = (opcode - opcode_base) as u32 / line_range;
+                address = address.wrapping_add(advance * min_inst_length);
+                rows.push((address, Some(primary.clone())));
+                continue;
+            }
+            match opcode {
+                // Extended opcodes
+                0 => {
+                    let length = read_uleb128(&mut reader)?;
+                    let next = reader.position() + length;
+                    match u8::from_reader(&mut reader, e)? {
+                        // DW_LNE_end_sequence
+                        1 => {
+                            rows.push((address, None));
+                            address = 0;
+                        }
+                        // DW_LNE_set_address
+                        2 => {
+                            ensure!(length == 5, "Unsupported .debug_line address size");
+                            address = u32::from_reader(&mut reader, e)?;
+                        }
+                        _ => {}
+                    }
+                    reader.seek(SeekFrom::Start(next))?;
+                }
+                // DW_LNS_copy
+                1 => rows.push((address, Some(primary.clone()))),
+                // DW_LNS_advance_pc
+                2 => {
+                    let advance = read_uleb128(&mut reader)? as u32;
+                    address = address.wrapping_add(advance * min_inst_length);
+                }
+                // DW_LNS_const_add_pc
+                8 => {
+                    let advance = (255 - opcode_base) as u32 / line_range;
+                    address = address.wrapping_add(advance * min_inst_length);
+                }
+                // DW_LNS_fixed_advance_pc
+                9 => address = address.wrapping_add(u16::from_reader(&mut reader, e)? as u32),
+                // Skip the LEB128 operands of everything else
+                _ => {
+                    for _ in 0..opcode_lengths[opcode as usize - 1] {
+                        read_uleb128(&mut reader)?;
+                    }
+                }
+            }
+        }
+        reader.seek(SeekFrom::Start(unit_end))?;
+    }
+
+    let mut ranges = vec![];
+    for window in rows.windows(2) {
+        if let [(start, Some(file)), (end, _)] = window {
+            if end > start {
+                ranges.push((*start, *end, file.clone()));
+            }
+        }
+    }
+    Ok(ranges)
+}
+
+/// Skips the directory index, modification time and length of a file entry.
+fn skip_file_attributes(reader: &mut Cursor<&[u8]>) -> Result<()> {
+    for _ in 0..3 {
+        read_uleb128(reader)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{testing::test_section, ObjArchitecture, ObjRawSection, ObjSectionKind};
+
+    fn line_program(files: &[&str], program: &[u8]) -> Vec<u8> {
+        let mut header = vec![
+            4,    // minimum_instruction_length
+            1,    // default_is_stmt
+            0xFB, // line_base
+            14,   // line_range
+            13,   // opcode_base
+            0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1, // standard_opcode_lengths
+            0, // include_directories
+        ];
+        for file in files {
+            header.extend_from_slice(file.as_bytes());
+            header.extend_from_slice(&[0, 0, 0, 0]);
+        }
+        header.push(0);
+
+        let mut unit = 2u16.to_be_bytes().to_vec();
+        unit.extend_from_slice(&(header.len() as u32).to_be_bytes());
+        unit.extend(header);
+        unit.extend_from_slice(program);
+        let mut data = (unit.len() as u32).to_be_bytes().to_vec();
+        data.extend(unit);
+        data
+    }
+
+    #[test]
+    fn test_splits_from_dwarf() {
+        let a = [
+            0x00, 0x05, 0x02, 0x80, 0x00, 0x31, 0x00, // DW_LNE_set_address 0x80003100
+            0x01, // DW_LNS_copy
+            0x02, 0x01, // DW_LNS_advance_pc 4
+            0x04, 0x02, // DW_LNS_set_file 2 (inlined from a header)
+            0x03, 0x0A, // DW_LNS_advance_line 10
+            0x01, // DW_LNS_copy
+            0x02, 0x01, // DW_LNS_advance_pc 4
+            0x04, 0x01, // DW_LNS_set_file 1
+            0x01, // DW_LNS_copy
+            0x02, 0x02, // DW_LNS_advance_pc 8
+            0x00, 0x01, 0x01, // DW_LNE_end_sequence
+        ];
+        let b = [
+            0x00, 0x05, 0x02, 0x80, 0x00, 0x31, 0x10, // DW_LNE_set_address 0x80003110
+            0x01, // DW_LNS_copy
+            0x02, 0x04, // DW_LNS_advance_pc 16
+            0x00, 0x01, 0x01, // DW_LNE_end_sequence
+        ];
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "main.elf".to_string(),
+            vec![],
+            vec![test_section(".text", ObjSectionKind::Code, 0x80003100, 0x20)],
+        );
+        obj.raw_sections.push(ObjRawSection {
+            name: ".debug_line".to_string(),
+            sh_type: object::elf::SHT_PROGBITS,
+            sh_flags: 0,
+            align: 1,
+            data: [line_program(&["a.c", "a.h"], &a), line_program(&["b.c"], &b)].concat(),
+        });
+
+        splits_from_dwarf(&mut obj).unwrap();
+        let splits = obj.sections[0]
+            .splits
+            .iter()
+            .map(|(address, split)| (address, split.end, split.unit.as_str(), split.autogenerated))
+            .collect::<Vec<_>>();
+        assert_eq!(splits, vec![
+            (0x80003100, 0x80003110, "a.c", true),
+            (0x80003110, 0x80003120, "b.c", true),
+        ]);
+    }
+}
@@ -1,7 +1,7 @@
 use std::{collections::BTreeSet, num::NonZeroU32};
 
 use anyhow::{anyhow, bail, ensure, Context, Result};
-use ppc750cl::Ins;
+use ppc750cl::{Ins, Opcode};
 
 use crate::{
     analysis::cfa::SectionAddress,
@@ -9,27 +9,30 @@ use crate::{
     obj::{
         ObjInfo, ObjKind, ObjRelocKind, ObjSection, ObjSectionKind, ObjSymbolKind, SectionIndex,
     },
+    util::reader::FromBytes,
 };
 
 pub mod cfa;
 pub mod executor;
+pub mod lines;
 pub mod objects;
 pub mod pass;
+pub mod relocs;
 pub mod signatures;
 pub mod slices;
 pub mod tracker;
 pub mod vm;
 
-pub fn disassemble(section: &ObjSection, address: u32) -> Option<Ins> {
-    read_u32(section, address).map(Ins::new)
+pub fn disassemble(obj: &ObjInfo, section: &ObjSection, address: u32) -> Option<Ins> {
+    read_u32(obj, section, address).map(Ins::new)
 }
 
-pub fn read_u32(section: &ObjSection, address: u32) -> Option<u32> {
+pub fn read_u32(obj: &ObjInfo, section: &ObjSection, address: u32) -> Option<u32> {
     let offset = (address as u64 - section.address) as usize;
     if section.data.len() < offset + 4 {
         return None;
     }
-    Some(u32::from_be_bytes(*array_ref!(section.data, offset, 4)))
+    Some(u32::from_bytes(*array_ref!(section.data, offset, 4), obj.endian))
 }
 
 fn read_unresolved_relocation_address(
@@ -105,7 +108,7 @@ pub fn read_address(obj: &ObjInfo, section: &ObjSection, address: u32) -> Result
         })
     } else {
         let offset = (address as u64 - section.address) as usize;
-        let address = u32::from_be_bytes(*array_ref!(section.data, offset, 4));
+        let address = u32::from_bytes(*array_ref!(section.data, offset, 4), obj.endian);
         let (section_index, _) = obj.sections.at_address(address)?;
         Ok(SectionAddress::new(section_index, address))
     }
@@ -178,7 +181,7 @@ fn get_jump_table_entries(
                     }
                 }
             } else {
-                let entry_addr = u32::from_be_bytes(*array_ref!(data, 0, 4));
+                let entry_addr = u32::from_bytes(*array_ref!(data, 0, 4), obj.endian);
                 if entry_addr > 0 {
                     let (section_index, _) =
                         obj.sections.at_address(entry_addr).with_context(|| {
@@ -206,7 +209,7 @@ fn get_jump_table_entries(
                     RelocationTarget::External => break,
                 }
             } else if obj.kind == ObjKind::Executable {
-                let Some(value) = read_u32(section, cur_addr.address) else {
+                let Some(value) = read_u32(obj, section, cur_addr.address) else {
                     break;
                 };
                 let Ok((section_index, _)) = obj.sections.at_address(value) else {
@@ -270,3 +273,208 @@ pub fn skip_alignment(
         }
     }
 }
+
+/// Determines the size of the function starting at `start` by scanning instructions up to
+/// the first unconditional branch, return or `rfi` that no earlier conditional branch jumps
+/// past. Alignment padding following the function is excluded.
+pub fn refine_function_size(obj: &ObjInfo, section_index: SectionIndex, start: u32) -> Result<u32> {
+    let section = &obj.sections[section_index];
+    let section_end = (section.address + section.size) as u32;
+    ensure!(
+        section.kind == ObjSectionKind::Code && section.contains(start),
+        "Function start {:#010X} not in code section",
+        start
+    );
+    let mut max_target = start;
+    let mut addr = start;
+    while addr < section_end {
+        let ins = disassemble(obj, section, addr)
+            .ok_or_else(|| anyhow!("Failed to disassemble @ {:#010X}", addr))?;
+        let always = ins.field_bo() & 0b10100 == 0b10100;
+        let terminal = match ins.op {
+            Opcode::B => {
+                // A forward jump within the function (e.g. to a loop condition) implies
+                // reachable code after it, unless it's a tail call to a known function
+                if !ins.field_lk() {
+                    if let Some(dest) = ins.branch_dest(addr) {
+                        let is_function = matches!(
+                            obj.symbols.kind_at_section_address(
+                                section_index,
+                                dest,
+                                ObjSymbolKind::Function
+                            ),
+                            Ok(Some(_))
+                        );
+                        if dest > addr && dest < section_end && !is_function {
+                            max_target = max_target.max(dest);
+                        }
+                    }
+                }
+                !ins.field_lk()
+            }
+            Opcode::Bc => {
+                // Only conditional branches imply reachable code after a terminator
+                if !ins.field_lk() && !always {
+                    if let Some(dest) = ins.branch_dest(addr) {
+                        if dest < section_end {
+                            max_target = max_target.max(dest);
+                        }
+                    }
+                }
+                !ins.field_lk() && always
+            }
+            Opcode::Bclr | Opcode::Bcctr => !ins.field_lk() && always,
+            Opcode::Rfi => true,
+            _ => false,
+        };
+        addr += 4;
+        if terminal && max_target < addr {
+            return Ok(addr - start);
+        }
+    }
+    // No terminator found; exclude trailing padding
+    while addr > start {
+        match read_u32(obj, section, addr - 4) {
+            Some(0 | 0x60000000) => addr -= 4,
+            _ => break,
+        }
+    }
+    Ok(addr - start)
+}
+
+/// Layout of a constructor/destructor table.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CtorsForm {
+    /// Function pointers followed by a NULL terminator.
+    NullTerminated,
+    /// An entry count followed by that many function pointers.
+    CountPrefixed,
+}
+
+/// Detects the layout of the `.ctors`/`.dtors` table starting at `start`. The table is
+/// count-prefixed if the first word isn't a function pointer, and is followed by exactly
+/// that many function pointers.
+pub fn detect_ctors_form(obj: &ObjInfo, start: SectionAddress) -> Result<CtorsForm> {
+    let section = &obj.sections[start.section];
+    let is_pointer = |addr: SectionAddress| -> Result<bool> {
+        if relocation_target_for(obj, addr, Some(ObjRelocKind::Absolute))?.is_some() {
+            return Ok(true);
+        }
+        if obj.kind == ObjKind::Relocatable {
+            return Ok(false);
+        }
+        Ok(read_u32(obj, section, addr.address).is_some_and(|value| {
+            matches!(obj.sections.at_address(value), Ok((_, s)) if s.kind == ObjSectionKind::Code)
+        }))
+    };
+
+    let first = read_u32(obj, section, start.address)
+        .ok_or_else(|| anyhow!("Failed to read {} @ {:#010X}", section.name, start))?;
+    if first == 0 || is_pointer(start)? {
+        return Ok(CtorsForm::NullTerminated);
+    }
+    let section_end = (section.address + section.size) as u32;
+    ensure!(
+        (first as u64) < (section_end - start.address) as u64 / 4,
+        "Invalid {} entry @ {:#010X}: {:#010X}",
+        section.name,
+        start,
+        first
+    );
+    for i in 1..=first {
+        ensure!(
+            is_pointer(start + i * 4)?,
+            "Expected {} count-prefixed entry {} @ {:#010X}",
+            section.name,
+            i,
+            start + i * 4
+        );
+    }
+    Ok(CtorsForm::CountPrefixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        testing::{test_section, test_section_words, test_symbol},
+        ObjArchitecture,
+    };
+
+    #[test]
+    fn test_refine_function_size() {
+        let code: [u32; 9] = [
+            0x4182000C, // beq 0xC
+            0x38600000, // li r3, 0
+            0x4E800020, // blr
+            0x38600001, // li r3, 1
+            0x4E800020, // blr
+            0x60000000, // nop (padding)
+            0x00000000, 0x00000000, 0x4E800020, // blr
+        ];
+        let obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![],
+            vec![test_section_words(".text", ObjSectionKind::Code, 0x80003100, &code)],
+        );
+        assert_eq!(refine_function_size(&obj, 0, 0x80003100).unwrap(), 0x14);
+        assert_eq!(refine_function_size(&obj, 0, 0x80003120).unwrap(), 0x4);
+    }
+
+    #[test]
+    fn test_refine_function_size_loop_entry() {
+        let code: [u32; 8] = [
+            0x48000008, // b 0x8
+            0x38630001, // addi r3, r3, 1
+            0x2C030010, // cmpwi r3, 0x10
+            0x4180FFF8, // blt -0x8
+            0x4E800020, // blr
+            0x48000008, // b 0x8
+            0x4E800020, // blr
+            0x4E800020, // blr
+        ];
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![],
+            vec![test_section_words(".text", ObjSectionKind::Code, 0x80003100, &code)],
+        );
+        assert_eq!(refine_function_size(&obj, 0, 0x80003100).unwrap(), 0x14);
+        assert_eq!(refine_function_size(&obj, 0, 0x80003114).unwrap(), 0xC);
+
+        // Jumps to known functions are tail calls
+        obj.add_symbol(test_symbol("callee", 0, 0x8000311C, 4), false).unwrap();
+        assert_eq!(refine_function_size(&obj, 0, 0x80003114).unwrap(), 0x4);
+    }
+
+    fn ctors_obj(ctors: &[u32]) -> ObjInfo {
+        ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0x80003100, 0x10),
+                test_section_words(".ctors", ObjSectionKind::ReadOnlyData, 0x80004000, ctors),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_detect_ctors_form() {
+        let start = SectionAddress::new(1, 0x80004000);
+        let obj = ctors_obj(&[0x80003100, 0x80003108, 0]);
+        assert_eq!(detect_ctors_form(&obj, start).unwrap(), CtorsForm::NullTerminated);
+        let obj = ctors_obj(&[0]);
+        assert_eq!(detect_ctors_form(&obj, start).unwrap(), CtorsForm::NullTerminated);
+
+        let obj = ctors_obj(&[2, 0x80003100, 0x80003108]);
+        assert_eq!(detect_ctors_form(&obj, start).unwrap(), CtorsForm::CountPrefixed);
+        // Count doesn't match the entries that follow
+        let obj = ctors_obj(&[3, 0x80003100, 0x80003108, 0]);
+        assert!(detect_ctors_form(&obj, start).is_err());
+    }
+}
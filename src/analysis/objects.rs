@@ -1,7 +1,7 @@
 use anyhow::Result;
 
 use crate::{
-    obj::{ObjDataKind, ObjInfo, ObjSectionKind, ObjSymbolKind, SymbolIndex},
+    obj::{ObjDataKind, ObjInfo, ObjSectionKind, ObjSymbol, ObjSymbolKind, SymbolIndex},
     util::split::is_linker_generated_label,
 };
 
@@ -63,6 +63,30 @@ pub fn detect_objects(obj: &mut ObjInfo) -> Result<()> {
     Ok(())
 }
 
+/// Infers the kind of symbols with unknown kind from their containing section:
+/// functions in code sections, objects otherwise.
+pub fn infer_symbol_kinds(obj: &mut ObjInfo) -> Result<()> {
+    let mut replace_symbols = vec![];
+    for (section_index, section) in obj.sections.iter() {
+        let kind = match section.kind {
+            ObjSectionKind::Code => ObjSymbolKind::Function,
+            ObjSectionKind::Data | ObjSectionKind::ReadOnlyData | ObjSectionKind::Bss => {
+                ObjSymbolKind::Object
+            }
+        };
+        for (idx, symbol) in obj.symbols.for_section(section_index) {
+            if symbol.kind != ObjSymbolKind::Unknown || is_linker_generated_label(&symbol.name) {
+                continue;
+            }
+            replace_symbols.push((idx, ObjSymbol { kind, ..symbol.clone() }));
+        }
+    }
+    for (idx, symbol) in replace_symbols {
+        obj.symbols.replace(idx, symbol)?;
+    }
+    Ok(())
+}
+
 pub fn detect_strings(obj: &mut ObjInfo) -> Result<()> {
     let mut symbols_set = Vec::<(SymbolIndex, ObjDataKind, usize)>::new();
     for (section_index, section) in obj
@@ -161,3 +185,41 @@ pub fn detect_strings(obj: &mut ObjInfo) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        testing::{test_section, test_symbol},
+        ObjArchitecture, ObjKind,
+    };
+
+    #[test]
+    fn test_infer_symbol_kinds() {
+        let symbol = |name, section, address, kind| ObjSymbol {
+            kind,
+            size_known: false,
+            ..test_symbol(name, section, address, 0)
+        };
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![
+                symbol("fn_80003100", 0, 0x80003100, ObjSymbolKind::Unknown),
+                symbol("lbl_80004000", 1, 0x80004000, ObjSymbolKind::Unknown),
+                symbol("lbl_80004010", 1, 0x80004010, ObjSymbolKind::Function),
+            ],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0x80003100, 0x100),
+                test_section(".data", ObjSectionKind::Data, 0x80004000, 0x100),
+            ],
+        );
+        infer_symbol_kinds(&mut obj).unwrap();
+        let kind = |name| obj.symbols.by_name(name).unwrap().unwrap().1.kind;
+        assert_eq!(kind("fn_80003100"), ObjSymbolKind::Function);
+        assert_eq!(kind("lbl_80004000"), ObjSymbolKind::Object);
+        assert_eq!(kind("lbl_80004010"), ObjSymbolKind::Function);
+        assert!(!obj.symbols.by_name("fn_80003100").unwrap().unwrap().1.size_known);
+    }
+}
@@ -0,0 +1,165 @@
+//! Relocation inference for executables without a relocation table.
+use std::collections::BTreeSet;
+
+use anyhow::{ensure, Result};
+use ppc750cl::{Argument, InsIter, Opcode, GPR};
+
+use crate::{
+    analysis::vm::{is_load_op, is_load_store_op, is_update_op},
+    obj::{
+        best_match_for_reloc, ObjInfo, ObjKind, ObjReloc, ObjRelocKind, ObjSectionKind,
+        SectionIndex, SymbolIndex,
+    },
+};
+
+/// Infers `@ha`/`@h` and `@l` relocations for absolute address pairs in code sections: a
+/// `lis` followed by an `addi`, `ori` or load/store using it as a base, whose combined
+/// address is a known symbol. Instructions that already have a relocation are skipped.
+///
+/// This is a linear scan rather than a full analysis like [`Tracker`]: a `lis` is paired with
+/// later instructions until its register is overwritten or control flow branches.
+/// Relocations are keyed by instruction address.
+///
+/// [`Tracker`]: crate::analysis::tracker::Tracker
+pub fn infer_relocations(obj: &ObjInfo) -> Result<Vec<(SectionIndex, u32, ObjReloc)>> {
+    ensure!(obj.kind == ObjKind::Executable, "Relocation inference requires an executable");
+    let mut relocations = vec![];
+    for (section_index, section) in obj.sections.by_kind(ObjSectionKind::Code) {
+        // Address of the `lis` and the upper half it loads, for each GPR
+        let mut upper: [Option<(u32, u32)>; 32] = [None; 32];
+        let mut hi_relocs = BTreeSet::new();
+        for (ins_addr, ins) in InsIter::new(&section.data, section.address as u32) {
+            let (base, hi_kind, dest) = match ins.op {
+                // lis rD, SIMM
+                Opcode::Addis if ins.field_ra() == 0 => {
+                    upper[ins.field_rd() as usize] =
+                        Some((ins_addr, (ins.field_simm() as u32) << 16));
+                    continue;
+                }
+                Opcode::B | Opcode::Bc | Opcode::Bcctr | Opcode::Bclr => {
+                    upper = [None; 32];
+                    continue;
+                }
+                Opcode::Addi => (ins.field_ra(), ObjRelocKind::PpcAddr16Ha, Some(ins.field_rd())),
+                Opcode::Ori => (ins.field_rs(), ObjRelocKind::PpcAddr16Hi, Some(ins.field_ra())),
+                op if is_load_store_op(op) => {
+                    let dest = is_load_op(op).then(|| ins.field_rd());
+                    (ins.field_ra(), ObjRelocKind::PpcAddr16Ha, dest)
+                }
+                _ => {
+                    for argument in ins.defs() {
+                        if let Argument::GPR(GPR(reg)) = argument {
+                            upper[reg as usize] = None;
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            if let Some((hi_addr, value)) = upper[base as usize].filter(|_| base != 0) {
+                let target = if hi_kind == ObjRelocKind::PpcAddr16Hi {
+                    value | ins.field_uimm() as u32
+                } else {
+                    value.wrapping_add(ins.field_simm() as u32)
+                };
+                if let Some(target_symbol) = symbol_at(obj, target, hi_kind) {
+                    if !section.relocations.contains(hi_addr) && hi_relocs.insert(hi_addr) {
+                        relocations.push((section_index, hi_addr, ObjReloc {
+                            kind: hi_kind,
+                            target_symbol,
+                            addend: 0,
+                            module: None,
+                        }));
+                    }
+                    if !section.relocations.contains(ins_addr) {
+                        relocations.push((section_index, ins_addr, ObjReloc {
+                            kind: ObjRelocKind::PpcAddr16Lo,
+                            target_symbol,
+                            addend: 0,
+                            module: None,
+                        }));
+                    }
+                }
+            }
+
+            if is_update_op(ins.op) {
+                upper[base as usize] = None;
+            }
+            if let Some(dest) = dest {
+                upper[dest as usize] = None;
+            }
+        }
+    }
+    Ok(relocations)
+}
+
+fn symbol_at(obj: &ObjInfo, address: u32, reloc_kind: ObjRelocKind) -> Option<SymbolIndex> {
+    let (section_index, _) = obj.sections.at_address(address).ok()?;
+    let symbols = obj.symbols.at_section_address(section_index, address).collect();
+    best_match_for_reloc(symbols, reloc_kind).map(|(symbol_index, _)| symbol_index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        testing::{test_section_words, test_symbol},
+        ObjArchitecture, ObjSymbol, ObjSymbolKind,
+    };
+
+    fn infer_obj(code: &[u32]) -> ObjInfo {
+        ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "main.dol".to_string(),
+            vec![ObjSymbol {
+                kind: ObjSymbolKind::Object,
+                ..test_symbol("lbl_80408000", 1, 0x80408000, 4)
+            }],
+            vec![
+                test_section_words(".text", ObjSectionKind::Code, 0x80003100, code),
+                test_section_words(".data", ObjSectionKind::Data, 0x80408000, &[0]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_infer_relocations() {
+        let obj = infer_obj(&[
+            0x3C608041, // lis r3, 0x8041
+            0x38638000, // addi r3, r3, -0x8000
+            0x38630004, // addi r3, r3, 4
+            0x4E800020, // blr
+        ]);
+
+        let relocations = infer_relocations(&obj).unwrap();
+        assert_eq!(relocations.len(), 2);
+        let (section_index, address, reloc) = &relocations[0];
+        assert_eq!((*section_index, *address), (0, 0x80003100));
+        assert_eq!(
+            (reloc.kind, reloc.target_symbol, reloc.addend),
+            (ObjRelocKind::PpcAddr16Ha, 0, 0)
+        );
+        let (section_index, address, reloc) = &relocations[1];
+        assert_eq!((*section_index, *address), (0, 0x80003104));
+        assert_eq!(
+            (reloc.kind, reloc.target_symbol, reloc.addend),
+            (ObjRelocKind::PpcAddr16Lo, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_infer_relocations_overwritten() {
+        // The upper half is lost once the register is overwritten
+        let obj = infer_obj(&[
+            0x3C608041, // lis r3, 0x8041
+            0x7C832378, // mr r3, r4
+            0x38638000, // addi r3, r3, -0x8000
+            0x3C608041, // lis r3, 0x8041
+            0x7C632214, // add r3, r3, r4
+            0x38638000, // addi r3, r3, -0x8000
+            0x4E800020, // blr
+        ]);
+        assert!(infer_relocations(&obj).unwrap().is_empty());
+    }
+}
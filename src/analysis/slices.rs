@@ -47,6 +47,7 @@ type InsCheck = dyn Fn(Ins) -> bool;
 
 #[inline(always)]
 fn check_sequence(
+    obj: &ObjInfo,
     section: &ObjSection,
     addr: SectionAddress,
     ins: Option<Ins>,
@@ -54,20 +55,20 @@ fn check_sequence(
 ) -> Result<bool> {
     let mut found = false;
     for &(first, second) in sequence {
-        let Some(ins) = ins.or_else(|| disassemble(section, addr.address)) else {
+        let Some(ins) = ins.or_else(|| disassemble(obj, section, addr.address)) else {
             continue;
         };
         if !first(ins) {
             continue;
         }
-        let Some(next) = disassemble(section, addr.address + 4) else {
+        let Some(next) = disassemble(obj, section, addr.address + 4) else {
             continue;
         };
         if second(next)
             // Also check the following instruction, in case the scheduler
             // put something in between.
             || (!next.is_branch()
-                && matches!(disassemble(section, addr.address + 8), Some(ins) if second(ins)))
+                && matches!(disassemble(obj, section, addr.address + 8), Some(ins) if second(ins)))
         {
             found = true;
             break;
@@ -78,6 +79,7 @@ fn check_sequence(
 }
 
 fn check_prologue_sequence(
+    obj: &ObjInfo,
     section: &ObjSection,
     addr: SectionAddress,
     ins: Option<Ins>,
@@ -97,7 +99,7 @@ fn check_prologue_sequence(
         // stw r0, d(r1)
         ins.op == Opcode::Stw && ins.field_rs() == 0 && ins.field_ra() == 1
     }
-    check_sequence(section, addr, ins, &[(&is_stwu, &is_mflr), (&is_mflr, &is_stw)])
+    check_sequence(obj, section, addr, ins, &[(&is_stwu, &is_mflr), (&is_mflr, &is_stw)])
 }
 
 impl FunctionSlices {
@@ -132,6 +134,7 @@ impl FunctionSlices {
 
     fn check_prologue(
         &mut self,
+        obj: &ObjInfo,
         section: &ObjSection,
         addr: SectionAddress,
         ins: Ins,
@@ -146,7 +149,7 @@ impl FunctionSlices {
             self.has_r1_load = true;
             return Ok(()); // Possibly instead of a prologue
         }
-        if check_prologue_sequence(section, addr, Some(ins))? {
+        if check_prologue_sequence(obj, section, addr, Some(ins))? {
             if let Some(prologue) = self.prologue {
                 if prologue != addr && prologue != addr - 4 {
                     bail!("Found duplicate prologue: {:#010X} and {:#010X}", prologue, addr)
@@ -160,6 +163,7 @@ impl FunctionSlices {
 
     fn check_epilogue(
         &mut self,
+        obj: &ObjInfo,
         section: &ObjSection,
         addr: SectionAddress,
         ins: Ins,
@@ -180,7 +184,10 @@ impl FunctionSlices {
             ins.op == Opcode::Or && ins.field_rd() == 1
         }
 
-        if check_sequence(section, addr, Some(ins), &[(&is_mtlr, &is_addi), (&is_or, &is_mtlr)])? {
+        if check_sequence(obj, section, addr, Some(ins), &[
+            (&is_mtlr, &is_addi),
+            (&is_or, &is_mtlr),
+        ])? {
             if let Some(epilogue) = self.epilogue {
                 if epilogue != addr {
                     bail!("Found duplicate epilogue: {:#010X} and {:#010X}", epilogue, addr)
@@ -222,11 +229,11 @@ impl FunctionSlices {
         // HACK: ProDG sometimes uses LR as a storage register for int-to-float conversions
         // To our heuristic, this looks like a prologue, so first check LR for the magic number.
         if vm.lr != GprValue::Constant(0x43300000) {
-            self.check_prologue(section, ins_addr, ins).with_context(|| {
+            self.check_prologue(obj, section, ins_addr, ins).with_context(|| {
                 format!("While processing {:#010X}: {:#?} {:#?}", function_start, self, vm.gpr)
             })?;
         }
-        self.check_epilogue(section, ins_addr, ins)
+        self.check_epilogue(obj, section, ins_addr, ins)
             .with_context(|| format!("While processing {:#010X}: {:#?}", function_start, self))?;
         if !self.has_conditional_blr && is_conditional_blr(ins) {
             self.has_conditional_blr = true;
@@ -470,7 +477,7 @@ impl FunctionSlices {
                         break 'outer;
                     }
                     // Skip nops
-                    match disassemble(&obj.sections[end.section], end.address) {
+                    match disassemble(obj, &obj.sections[end.section], end.address) {
                         Some(ins) => {
                             if !is_nop(ins) {
                                 break;
@@ -543,7 +550,7 @@ impl FunctionSlices {
                     // FIXME this is real bad
                     if !self.has_conditional_blr {
                         let ins_addr = end - 4;
-                        if let Some(ins) = disassemble(section, ins_addr.address) {
+                        if let Some(ins) = disassemble(obj, section, ins_addr.address) {
                             if ins.op == Opcode::B {
                                 if let Some(RelocationTarget::Address(target)) = ins
                                     .branch_dest(ins_addr.address)
@@ -568,8 +575,8 @@ impl FunctionSlices {
                     // MWCC optimization sometimes leaves an unreachable blr
                     // after generating a conditional blr in the function.
                     if self.has_conditional_blr
-                        && matches!(disassemble(section, end.address - 4), Some(ins) if !ins.is_blr())
-                        && matches!(disassemble(section, end.address), Some(ins) if ins.is_blr())
+                        && matches!(disassemble(obj, section, end.address - 4), Some(ins) if !ins.is_blr())
+                        && matches!(disassemble(obj, section, end.address), Some(ins) if ins.is_blr())
                         && !known_functions.contains_key(&end)
                     {
                         log::trace!("Found trailing blr @ {:#010X}, merging with function", end);
@@ -578,7 +585,7 @@ impl FunctionSlices {
 
                     // Some functions with rfi also include a trailing nop
                     if self.has_rfi
-                        && matches!(disassemble(section, end.address), Some(ins) if is_nop(ins))
+                        && matches!(disassemble(obj, section, end.address), Some(ins) if is_nop(ins))
                         && !known_functions.contains_key(&end)
                     {
                         log::trace!("Found trailing nop @ {:#010X}, merging with function", end);
@@ -647,7 +654,7 @@ impl FunctionSlices {
         if self.prologue.is_none() {
             let mut current_address = function_start;
             while current_address < addr {
-                match check_prologue_sequence(target_section, current_address, None) {
+                match check_prologue_sequence(obj, target_section, current_address, None) {
                     Ok(true) => {
                         log::debug!(
                             "Prologue discovered @ {}; known tail call: {}",
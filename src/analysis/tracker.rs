@@ -554,7 +554,7 @@ impl Tracker {
     ) -> Result<()> {
         let mut addr = SectionAddress::new(section_index, section.address as u32);
         for chunk in section.data.chunks_exact(4) {
-            let value = u32::from_be_bytes(chunk.try_into()?);
+            let value = u32::from_bytes(chunk.try_into()?, obj.endian);
             if let Some(value) = self.is_valid_address(obj, addr, value) {
                 self.relocations
                     .insert(addr, Relocation::Absolute(RelocationTarget::Address(value)));
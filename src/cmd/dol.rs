@@ -422,7 +422,7 @@ pub fn run(args: Args) -> Result<()> {
 }
 
 fn apply_selfile(obj: &mut ObjInfo, buf: &[u8]) -> Result<()> {
-    let rso = process_rso(&mut Cursor::new(buf))?;
+    let rso = process_rso(&mut Cursor::new(buf), None)?;
     for (_, symbol) in rso.symbols.iter() {
         let dol_section_index = match symbol.section {
             Some(section) => section,
@@ -485,6 +485,7 @@ fn apply_selfile(obj: &mut ObjInfo, buf: &[u8]) -> Result<()> {
                 kind: existing_symbol.kind,
                 align: existing_symbol.align,
                 data_kind: existing_symbol.data_kind,
+                st_other: existing_symbol.st_other,
                 name_hash: existing_symbol.name_hash,
                 demangled_name_hash: existing_symbol.demangled_name_hash,
             })?;
@@ -540,7 +541,10 @@ pub fn info(args: InfoArgs) -> Result<()> {
     for (_, section) in obj.sections.iter() {
         println!(
             "\t{: >10} | {:#010X} | {: <#10X} | {: <#10X}",
-            section.name, section.address, section.size, section.file_offset
+            section.name,
+            section.address,
+            section.size,
+            section.file_offset.unwrap_or_default()
         );
     }
     println!("\nDiscovered symbols:");
@@ -766,44 +770,44 @@ fn resolve_external_relocations(
     }
     let mut reloc_to_symbol = HashMap::<RelocRef, SymbolIndex>::new();
 
-    for (_section_index, section) in obj.sections.iter_mut() {
-        for (_reloc_address, reloc) in section.relocations.iter_mut() {
-            if let Some(module_id) = reloc.module {
-                let reloc_ref = RelocRef { module_id, symbol_index: reloc.target_symbol };
-                let symbol_idx = match reloc_to_symbol.entry(reloc_ref) {
-                    hash_map::Entry::Occupied(e) => *e.get(),
-                    hash_map::Entry::Vacant(e) => {
-                        let target_obj = if module_id == obj.module_id {
-                            bail!("Relocation to self in module {}", obj.module_id)
-                        } else if module_id == 0 {
-                            dol_obj.unwrap()
-                        } else {
-                            &modules
-                                .get(&module_id)
-                                .ok_or_else(|| {
-                                    anyhow!("Failed to locate module {}", reloc.module.unwrap())
-                                })?
-                                .obj
-                        };
-
-                        let target_symbol = &target_obj.symbols[reloc.target_symbol];
-                        let symbol_idx = obj.symbols.add_direct(ObjSymbol {
-                            name: target_symbol.name.clone(),
-                            demangled_name: target_symbol.demangled_name.clone(),
-                            ..Default::default()
-                        })?;
-
-                        e.insert(symbol_idx);
-                        symbol_idx
-                    }
-                };
+    for (_section_index, section) in obj.sections.iter() {
+        for (_reloc_address, reloc) in section.relocations.iter() {
+            let Some(module_id) = reloc.module else {
+                continue;
+            };
+            let reloc_ref = RelocRef { module_id, symbol_index: reloc.target_symbol };
+            let hash_map::Entry::Vacant(e) = reloc_to_symbol.entry(reloc_ref) else {
+                continue;
+            };
+            let target_obj = if module_id == obj.module_id {
+                bail!("Relocation to self in module {}", obj.module_id)
+            } else if module_id == 0 {
+                dol_obj.unwrap()
+            } else {
+                &modules
+                    .get(&module_id)
+                    .ok_or_else(|| anyhow!("Failed to locate module {}", module_id))?
+                    .obj
+            };
 
-                reloc.target_symbol = symbol_idx;
-                reloc.module = None;
-            }
+            let target_symbol = &target_obj.symbols[reloc.target_symbol];
+            let symbol_idx = obj.symbols.add_direct(ObjSymbol {
+                name: target_symbol.name.clone(),
+                demangled_name: target_symbol.demangled_name.clone(),
+                ..Default::default()
+            })?;
+            e.insert(symbol_idx);
         }
     }
 
+    // Every cross-module target was added above
+    obj.map_relocations(|_, _, reloc| {
+        if let Some(module_id) = reloc.module.take() {
+            let reloc_ref = RelocRef { module_id, symbol_index: reloc.target_symbol };
+            reloc.target_symbol = reloc_to_symbol[&reloc_ref];
+        }
+    });
+
     Ok(())
 }
 
@@ -1094,7 +1098,7 @@ fn load_analyze_rel(
     if let Some(hash_str) = &module_config.hash {
         verify_hash(data, hash_str)?;
     }
-    let (header, mut module_obj) = process_rel(&mut Cursor::new(data), module_config.name())?;
+    let (header, mut module_obj) = process_rel(&mut Cursor::new(data), module_config.name(), None)?;
 
     if let Some(comment_version) = config.mw_comment_version {
         module_obj.mw_comment = Some(MWComment::new(comment_version)?);
@@ -1942,6 +1946,7 @@ fn apply(args: ApplyArgs) -> Result<()> {
                 kind: linked_sym.kind,
                 align: linked_sym.align,
                 data_kind: linked_sym.data_kind,
+                st_other: linked_sym.st_other,
                 name_hash: linked_sym.name_hash,
                 demangled_name_hash: linked_sym.demangled_name_hash,
             })?;
@@ -22,7 +22,7 @@ use crate::{
         asm::write_asm,
         comment::{CommentSym, MWComment},
         config::{write_splits_file, write_symbols_file},
-        elf::{process_elf, write_elf},
+        elf::{process_elf, process_elf_with_report, write_elf, BoundaryEvent},
         file::{buf_writer, process_rsp},
         path::native_path,
         reader::{Endian, FromReader},
@@ -85,6 +85,9 @@ pub struct SplitArgs {
     #[argp(positional, from_str_fn(native_path))]
     /// output directory
     out_dir: Utf8NativePathBuf,
+    #[argp(switch)]
+    /// log file and section boundary decisions
+    report: bool,
 }
 
 #[derive(FromArgs, PartialEq, Eq, Debug)]
@@ -180,7 +183,21 @@ fn disasm(args: DisasmArgs) -> Result<()> {
 }
 
 fn split(args: SplitArgs) -> Result<()> {
-    let obj = process_elf(&args.in_file)?;
+    let obj = if args.report {
+        let (obj, events) = process_elf_with_report(&args.in_file)?;
+        for event in events {
+            match event {
+                BoundaryEvent::FileStarted(file) => log::info!("File {} started", file),
+                BoundaryEvent::SectionAttributed { section, address, file } => {
+                    log::info!("Section {} @ {:#010X} attributed to {}", section, address, file)
+                }
+                BoundaryEvent::FilesEnded(symbol) => log::info!("Files ended at {}", symbol),
+            }
+        }
+        obj
+    } else {
+        process_elf(&args.in_file)?
+    };
     ensure!(obj.kind == ObjKind::Executable, "Can only split executable objects");
 
     let mut file_map = HashMap::<String, Vec<u8>>::new();
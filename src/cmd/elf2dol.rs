@@ -1,12 +1,14 @@
-use std::io::{Seek, SeekFrom, Write};
+use std::io::{Cursor, Seek, SeekFrom, Write};
 
 use anyhow::{anyhow, bail, ensure, Result};
 use argp::FromArgs;
+use itertools::Itertools;
 use object::{Architecture, Endianness, Object, ObjectKind, ObjectSection, SectionKind};
-use typed_path::Utf8NativePathBuf;
+use typed_path::{Utf8NativePath, Utf8NativePathBuf};
 
 use crate::{
-    util::{file::buf_writer, path::native_path},
+    obj::{ObjInfo, ObjKind, ObjSectionKind},
+    util::{elf::process_elf, file::buf_writer, path::native_path},
     vfs::open_file,
 };
 
@@ -118,8 +120,83 @@ pub fn run(args: Args) -> Result<()> {
         header.bss_size = (address + size) - header.bss_address;
     }
 
-    // Offsets
     out.rewind()?;
+    write_header(&mut out, &header)?;
+
+    // Done!
+    out.flush()?;
+    Ok(())
+}
+
+/// Converts an executable ELF to a DOL.
+pub fn elf_to_dol(elf_path: &Utf8NativePath, dol_path: &Utf8NativePath) -> Result<()> {
+    let obj = process_elf(elf_path)?;
+    let data = write_dol(&obj)?;
+    let mut out = buf_writer(dol_path)?;
+    out.write_all(&data)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Writes an executable as a DOL, assigning code sections to text slots and all other
+/// initialized sections to data slots in address order.
+pub fn write_dol(obj: &ObjInfo) -> Result<Vec<u8>> {
+    ensure!(obj.kind == ObjKind::Executable, "Expected executable object");
+    let mut text_sections = obj.sections.by_kind(ObjSectionKind::Code).collect_vec();
+    let mut data_sections = obj
+        .sections
+        .iter()
+        .filter(|(_, s)| matches!(s.kind, ObjSectionKind::Data | ObjSectionKind::ReadOnlyData))
+        .collect_vec();
+    text_sections.sort_by_key(|(_, s)| s.address);
+    data_sections.sort_by_key(|(_, s)| s.address);
+    for (kind, sections, max) in
+        [("text", &text_sections, MAX_TEXT_SECTIONS), ("data", &data_sections, MAX_DATA_SECTIONS)]
+    {
+        ensure!(
+            sections.len() <= max,
+            "Too many {} sections ({} > {}): {}",
+            kind,
+            sections.len(),
+            max,
+            sections.iter().map(|(_, s)| s.name.as_str()).join(", ")
+        );
+    }
+
+    let mut header = DolHeader { entry_point: obj.entry.unwrap_or(0) as u32, ..Default::default() };
+    let mut out = Cursor::new(Vec::new());
+    let mut offset = 0x100u32;
+    out.seek(SeekFrom::Start(offset as u64))?;
+    for (slots, count, sections) in [
+        (header.text_sections.as_mut_slice(), &mut header.text_section_count, &text_sections),
+        (header.data_sections.as_mut_slice(), &mut header.data_section_count, &data_sections),
+    ] {
+        for (slot, (_, section)) in slots.iter_mut().zip(sections.iter()) {
+            let size = align32(section.size as u32);
+            *slot = DolSection { offset, address: section.address as u32, size };
+            write_aligned(&mut out, &section.data, size)?;
+            offset += size;
+        }
+        *count = sections.len();
+    }
+    if let Some((start, end)) = obj
+        .sections
+        .by_kind(ObjSectionKind::Bss)
+        .map(|(_, s)| (s.address as u32, (s.address + s.size) as u32))
+        .reduce(|(a_start, a_end), (b_start, b_end)| (a_start.min(b_start), a_end.max(b_end)))
+    {
+        header.bss_address = start;
+        header.bss_size = end - start;
+    }
+
+    out.rewind()?;
+    write_header(&mut out, &header)?;
+    Ok(out.into_inner())
+}
+
+fn write_header<W>(out: &mut W, header: &DolHeader) -> std::io::Result<()>
+where W: Write + ?Sized {
+    // Offsets
     for section in &header.text_sections {
         out.write_all(&section.offset.to_be_bytes())?;
     }
@@ -147,9 +224,6 @@ pub fn run(args: Args) -> Result<()> {
     out.write_all(&header.bss_address.to_be_bytes())?;
     out.write_all(&header.bss_size.to_be_bytes())?;
     out.write_all(&header.entry_point.to_be_bytes())?;
-
-    // Done!
-    out.flush()?;
     Ok(())
 }
 
@@ -198,3 +272,52 @@ fn is_alloc(flags: object::SectionFlags) -> bool {
 fn is_name_allowed(s: &object::Section, denied: &[String]) -> bool {
     !denied.contains(&s.name().unwrap_or("[error]").to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        obj::{testing::test_section, ObjArchitecture},
+        util::{
+            dol::{DolFile, DolLike, DolSectionKind},
+            reader::{Endian, FromReader},
+        },
+    };
+
+    #[test]
+    fn test_write_dol() {
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![],
+            vec![
+                test_section(".init", ObjSectionKind::Code, 0x80003100, 0x40),
+                test_section(".text", ObjSectionKind::Code, 0x80003140, 0x100),
+                test_section(".data", ObjSectionKind::Data, 0x80003240, 0x20),
+                test_section(".bss", ObjSectionKind::Bss, 0x80003260, 0x80),
+            ],
+        );
+        obj.entry = Some(0x80003100);
+        let data = write_dol(&obj).unwrap();
+        let dol = DolFile::from_reader(&mut Cursor::new(&data), Endian::Big).unwrap();
+        let sections = dol.sections().iter().map(|s| (s.kind, s.address, s.size)).collect_vec();
+        assert_eq!(sections, vec![
+            (DolSectionKind::Text, 0x80003100, 0x40),
+            (DolSectionKind::Text, 0x80003140, 0x100),
+            (DolSectionKind::Data, 0x80003240, 0x20),
+            (DolSectionKind::Bss, 0x80003260, 0x80),
+        ]);
+        assert_eq!(dol.entry_point(), 0x80003100);
+
+        for i in 0..MAX_TEXT_SECTIONS {
+            obj.sections.push(test_section(
+                &format!(".text{i}"),
+                ObjSectionKind::Code,
+                0x80010000 + i as u64 * 0x100,
+                0x20,
+            ));
+        }
+        assert!(write_dol(&obj).is_err());
+    }
+}
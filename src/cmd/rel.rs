@@ -408,7 +408,7 @@ fn make(args: MakeArgs) -> Result<()> {
 
 fn info(args: InfoArgs) -> Result<()> {
     let mut file = open_file(&args.rel_file, true)?;
-    let (header, mut module_obj) = process_rel(file.as_mut(), "")?;
+    let (header, mut module_obj) = process_rel(file.as_mut(), "", None)?;
 
     let mut state = AnalyzerState::default();
     state.detect_functions(&module_obj)?;
@@ -436,7 +436,11 @@ fn info(args: InfoArgs) -> Result<()> {
         };
         println!(
             "{: >10} | {: <10} | {: <#10X} | {: <#10X} | {: <10}",
-            section.name, kind_str, section.size, section.file_offset, section.elf_index
+            section.name,
+            kind_str,
+            section.size,
+            section.file_offset.unwrap_or_default(),
+            section.elf_index
         );
     }
     println!("\nDiscovered symbols:");
@@ -492,7 +496,7 @@ fn merge(args: MergeArgs) -> Result<()> {
         let (path, mut entry) = result?;
         log::info!("Loading {}", path);
         let name = path.file_stem().unwrap_or_default();
-        let (_, obj) = process_rel(&mut entry, name)?;
+        let (_, obj) = process_rel(&mut entry, name, None)?;
         match module_map.entry(obj.module_id) {
             btree_map::Entry::Vacant(e) => e.insert(obj),
             btree_map::Entry::Occupied(_) => bail!("Duplicate module ID {}", obj.module_id),
@@ -518,7 +522,10 @@ fn merge(args: MergeArgs) -> Result<()> {
                 virtual_address: mod_section.virtual_address,
                 file_offset: mod_section.file_offset,
                 section_known: mod_section.section_known,
+                alloc: mod_section.alloc,
+                load: mod_section.load,
                 splits: mod_section.splits.clone(),
+                segment: None,
             });
             section_map.nested_insert(module.module_id, mod_section.elf_index, offset)?;
             for (_, mod_symbol) in module.symbols.for_section(mod_section_index) {
@@ -533,6 +540,7 @@ fn merge(args: MergeArgs) -> Result<()> {
                     kind: mod_symbol.kind,
                     align: mod_symbol.align,
                     data_kind: mod_symbol.data_kind,
+                    st_other: mod_symbol.st_other,
                     name_hash: mod_symbol.name_hash,
                     demangled_name_hash: mod_symbol.demangled_name_hash,
                 })?;
@@ -626,6 +634,13 @@ fn link_relocations(obj: &mut ObjInfo) -> Result<()> {
                 ObjRelocKind::Absolute => {
                     ins = target_address;
                 }
+                ObjRelocKind::PpcAddr16 => {
+                    ensure!(
+                        target_address <= 0x7fff || target_address >= 0xffff8000,
+                        "R_PPC_ADDR16 relocation out of range"
+                    );
+                    ins = (ins & 0xffff0000) | (target_address & 0xffff);
+                }
                 ObjRelocKind::PpcAddr16Hi => {
                     ins = (ins & 0xffff0000) | ((target_address >> 16) & 0xffff);
                 }
@@ -643,6 +658,9 @@ fn link_relocations(obj: &mut ObjInfo) -> Result<()> {
                     );
                     ins = (ins & !0x3fffffc) | (diff as u32 & 0x3fffffc);
                 }
+                ObjRelocKind::PpcAddr24 => {
+                    ins = (ins & !0x3fffffc) | (target_address & 0x3fffffc);
+                }
                 ObjRelocKind::PpcRel14 => {
                     let diff = target_address as i32 - source_address as i32;
                     ensure!(
@@ -654,6 +672,23 @@ fn link_relocations(obj: &mut ObjInfo) -> Result<()> {
                 ObjRelocKind::PpcEmbSda21 => {
                     // Unused in RELs
                 }
+                ObjRelocKind::PpcTprel16
+                | ObjRelocKind::PpcTprel16Lo
+                | ObjRelocKind::PpcTprel16Hi
+                | ObjRelocKind::PpcTprel16Ha
+                | ObjRelocKind::PpcDtprel16
+                | ObjRelocKind::PpcDtprel16Lo
+                | ObjRelocKind::PpcDtprel16Hi
+                | ObjRelocKind::PpcDtprel16Ha
+                | ObjRelocKind::PpcEmbNaddr32
+                | ObjRelocKind::PpcEmbNaddr16
+                | ObjRelocKind::PpcEmbSdai16
+                | ObjRelocKind::PpcEmbSda2i16 => {
+                    bail!("Unsupported relocation kind {:?}", reloc.kind)
+                }
+                ObjRelocKind::PpcRel32 => {
+                    ins = target_address.wrapping_sub(source_address);
+                }
             };
             *ins_ref = ins.to_be_bytes();
         }
@@ -77,7 +77,7 @@ pub fn run(args: Args) -> Result<()> {
 fn info(args: InfoArgs) -> Result<()> {
     let rso = {
         let mut file = open_file(&args.rso_file, true)?;
-        process_rso(file.as_mut())?
+        process_rso(file.as_mut(), None)?
     };
     println!("Read RSO module {}", rso.name);
     Ok(())
@@ -1,4 +1,4 @@
-use crate::analysis::cfa::SectionAddress;
+use crate::{analysis::cfa::SectionAddress, obj::SectionIndex};
 
 /// A collection of address ranges.
 /// Slow to insert, but fast to check if an address is contained in any of the ranges.
@@ -31,6 +31,26 @@ impl AddressRanges {
         }
     }
 
+    /// Drops ranges in the given section and shifts ranges in later sections down by one.
+    pub fn remove_section(&mut self, section: SectionIndex) {
+        self.inner.retain(|(start, _)| start.section != section);
+        for (start, _) in &mut self.inner {
+            if start.section > section {
+                start.section -= 1;
+            }
+        }
+    }
+
+    /// Moves ranges in the given section by `delta` bytes.
+    pub fn shift_section(&mut self, section: SectionIndex, delta: i64) {
+        for (start, end) in &mut self.inner {
+            if start.section == section {
+                start.address = (start.address as i64 + delta) as u32;
+                *end = (*end as i64 + delta) as u32;
+            }
+        }
+    }
+
     pub fn contains(&self, address: SectionAddress) -> bool {
         let pos = match self.inner.binary_search_by_key(&address, |&(start, _)| start) {
             Ok(_) => return true,
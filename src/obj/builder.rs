@@ -0,0 +1,171 @@
+use anyhow::{anyhow, ensure, Result};
+
+use crate::{
+    obj::{
+        ObjArchitecture, ObjInfo, ObjKind, ObjReloc, ObjSection, ObjSymbol, SectionIndex,
+        SymbolIndex,
+    },
+    util::comment::MWComment,
+};
+
+/// Incrementally constructs an [`ObjInfo`], validating cross references on [`build`].
+///
+/// [`build`]: ObjInfoBuilder::build
+#[derive(Debug, Clone)]
+pub struct ObjInfoBuilder {
+    kind: ObjKind,
+    architecture: ObjArchitecture,
+    name: String,
+    sections: Vec<ObjSection>,
+    symbols: Vec<ObjSymbol>,
+    relocations: Vec<(SectionIndex, u32, ObjReloc)>,
+    entry: Option<u64>,
+    mw_comment: Option<MWComment>,
+    module_id: u32,
+}
+
+impl ObjInfoBuilder {
+    pub fn new(kind: ObjKind, name: String) -> Self {
+        Self {
+            kind,
+            architecture: ObjArchitecture::PowerPc,
+            name,
+            sections: vec![],
+            symbols: vec![],
+            relocations: vec![],
+            entry: None,
+            mw_comment: None,
+            module_id: 0,
+        }
+    }
+
+    /// Adds a section, returning its index.
+    pub fn add_section(&mut self, section: ObjSection) -> SectionIndex {
+        let index = self.sections.len() as SectionIndex;
+        self.sections.push(section);
+        index
+    }
+
+    /// Adds a symbol, returning its index.
+    pub fn add_symbol(&mut self, symbol: ObjSymbol) -> SymbolIndex {
+        let index = self.symbols.len() as SymbolIndex;
+        self.symbols.push(symbol);
+        index
+    }
+
+    /// Adds a relocation at the given address within a section.
+    pub fn add_reloc(&mut self, section: SectionIndex, address: u32, reloc: ObjReloc) -> &mut Self {
+        self.relocations.push((section, address, reloc));
+        self
+    }
+
+    pub fn set_entry(&mut self, entry: u64) -> &mut Self {
+        self.entry = Some(entry);
+        self
+    }
+
+    pub fn set_mw_comment(&mut self, mw_comment: MWComment) -> &mut Self {
+        self.mw_comment = Some(mw_comment);
+        self
+    }
+
+    pub fn set_module_id(&mut self, module_id: u32) -> &mut Self {
+        self.module_id = module_id;
+        self
+    }
+
+    /// Validates section, symbol and relocation references and creates the object.
+    pub fn build(self) -> Result<ObjInfo> {
+        let section_count = self.sections.len();
+        let symbol_count = self.symbols.len();
+        for (index, symbol) in self.symbols.iter().enumerate() {
+            if let Some(section_index) = symbol.section {
+                ensure!(
+                    (section_index as usize) < section_count,
+                    "Symbol {} ({}) references invalid section index {}",
+                    index,
+                    symbol.name,
+                    section_index
+                );
+            }
+        }
+        for (section_index, address, reloc) in &self.relocations {
+            let section = self
+                .sections
+                .get(*section_index as usize)
+                .ok_or_else(|| anyhow!("Relocation in invalid section index {}", section_index))?;
+            ensure!(
+                section.contains(*address),
+                "Relocation @ {:#010X} outside of section {}: {:#010X}-{:#010X}",
+                address,
+                section.name,
+                section.address,
+                section.address + section.size
+            );
+            ensure!(
+                (reloc.target_symbol as usize) < symbol_count,
+                "Relocation @ {} {:#010X} targets invalid symbol index {}",
+                section.name,
+                address,
+                reloc.target_symbol
+            );
+        }
+
+        let mut obj =
+            ObjInfo::new(self.kind, self.architecture, self.name, self.symbols, self.sections);
+        for (section_index, address, reloc) in self.relocations {
+            obj.sections[section_index].relocations.insert(address, reloc)?;
+        }
+        obj.entry = self.entry;
+        obj.mw_comment = self.mw_comment;
+        obj.module_id = self.module_id;
+        Ok(obj)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object::{Object, ObjectSymbol};
+
+    use super::*;
+    use crate::{
+        obj::{
+            testing::{test_section_words, test_symbol},
+            ObjRelocKind, ObjSectionKind,
+        },
+        util::elf::write_elf,
+    };
+
+    #[test]
+    fn test_build_and_write() {
+        let mut builder = ObjInfoBuilder::new(ObjKind::Relocatable, "test.o".to_string());
+        let text = builder.add_section(test_section_words(".text", ObjSectionKind::Code, 0, &[
+            0x48000001, 0x4E800020,
+        ]));
+        let foo = builder.add_symbol(test_symbol("foo", text, 0, 4));
+        let bar = builder.add_symbol(test_symbol("bar", text, 4, 4));
+        builder.add_reloc(text, 0, ObjReloc {
+            kind: ObjRelocKind::PpcRel24,
+            target_symbol: bar,
+            addend: 0,
+            module: None,
+        });
+
+        let mut invalid = builder.clone();
+        invalid.add_reloc(text, 8, ObjReloc {
+            kind: ObjRelocKind::PpcRel24,
+            target_symbol: foo,
+            addend: 0,
+            module: None,
+        });
+        assert!(invalid.build().is_err());
+
+        let obj = builder.build().unwrap();
+        assert_eq!(obj.sections[text].relocations.len(), 1);
+        let data = write_elf(&obj, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let names = file.symbols().filter_map(|s| s.name().ok()).collect::<Vec<_>>();
+        assert!(names.contains(&"foo"));
+        assert!(names.contains(&"bar"));
+    }
+}
@@ -1,31 +1,47 @@
 mod addresses;
+mod builder;
 mod relocations;
 mod sections;
 mod splits;
 mod symbols;
+#[cfg(test)]
+pub mod testing;
 
 use std::{
     cmp::{max, min},
-    collections::{BTreeMap, BTreeSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     hash::Hash,
+    ops::Range,
 };
 
 use anyhow::{anyhow, bail, ensure, Result};
+pub use builder::ObjInfoBuilder;
+use cwdemangle::{demangle, DemangleOptions};
 use objdiff_core::obj::split_meta::SplitMeta;
-pub use relocations::{ObjReloc, ObjRelocKind, ObjRelocations};
+pub use relocations::{
+    is_paired_single_load_store, sda21_field_mask, ObjReloc, ObjRelocKind, ObjRelocations,
+};
 pub use sections::{
-    section_kind_for_section, ObjSection, ObjSectionKind, ObjSections, SectionIndex,
+    overlapping_sections, section_kind_for_section, split_section_suffix, ObjRawSection,
+    ObjSection, ObjSectionKind, ObjSections, SectionIndex, SmallDataArea,
 };
+use sha2::{Digest, Sha256};
 pub use splits::{ObjSplit, ObjSplits};
 pub use symbols::{
-    best_match_for_reloc, ObjDataKind, ObjSymbol, ObjSymbolFlagSet, ObjSymbolFlags, ObjSymbolKind,
-    ObjSymbolScope, ObjSymbols, SymbolIndex,
+    best_match_for_reloc, canonical_symbol_key, ObjDataKind, ObjSymbol, ObjSymbolFlagSet,
+    ObjSymbolFlags, ObjSymbolKind, ObjSymbolScope, ObjSymbols, SymbolIndex,
 };
 
 use crate::{
     analysis::cfa::SectionAddress,
+    array_ref,
     obj::addresses::AddressRanges,
-    util::{comment::MWComment, rel::RelReloc},
+    util::{
+        comment::MWComment,
+        reader::{Endian, FromBytes, ToWriter},
+        rel::RelReloc,
+        split::for_each_split_obj,
+    },
 };
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -53,16 +69,54 @@ pub struct ObjUnit {
     pub order: Option<i32>,
 }
 
+/// Summary of an object's symbols and sections, from [`ObjInfo::stats`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct ObjStats {
+    pub functions: usize,
+    pub objects: usize,
+    pub locals: usize,
+    pub globals: usize,
+    pub weak: usize,
+    pub defined: usize,
+    pub undefined: usize,
+    pub code_size: u64,
+    pub data_size: u64,
+    pub bss_size: u64,
+}
+
+/// Differences between two symbol tables, from [`ObjInfo::diff_symbols`].
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct SymbolDiff {
+    /// Symbols only present in the left object.
+    pub only_in_self: Vec<SymbolIndex>,
+    /// Symbols only present in the right object.
+    pub only_in_other: Vec<SymbolIndex>,
+    /// Matched symbols whose address, size, kind or flags differ, as (left, right).
+    pub changed: Vec<(SymbolIndex, SymbolIndex)>,
+}
+
 #[derive(Debug, Clone)]
 pub struct ObjInfo {
     pub kind: ObjKind,
     pub architecture: ObjArchitecture,
+    /// Byte order used when writing the object.
+    pub endian: Endian,
     pub name: String,
     pub symbols: ObjSymbols,
     pub sections: ObjSections,
     pub entry: Option<u64>,
+    /// Address of the `__start`/`_start` symbol in relocatable objects, which have no
+    /// header entry point.
+    pub start_address: Option<SectionAddress>,
+    /// Runtime startup symbols (e.g. `__start`, `__init_cpp`, `main`) found when reading,
+    /// in startup order, with their addresses.
+    pub entry_chain: Vec<(String, SectionAddress)>,
+    /// ELF header flags, written back as-is. `EF_PPC_EMB` is used when unset.
+    pub e_flags: Option<u32>,
     pub mw_comment: Option<MWComment>,
     pub split_meta: Option<SplitMeta>,
+    /// Unrecognized sections, passed through as-is
+    pub raw_sections: Vec<ObjRawSection>,
 
     // Linker generated
     pub sda2_base: Option<u32>,
@@ -98,12 +152,17 @@ impl ObjInfo {
         Self {
             kind,
             architecture,
+            endian: Endian::Big,
             name,
             symbols: ObjSymbols::new(kind, symbols),
             sections: ObjSections::new(kind, sections),
             entry: None,
+            start_address: None,
+            entry_chain: vec![],
+            e_flags: None,
             mw_comment: Default::default(),
             split_meta: None,
+            raw_sections: vec![],
             sda2_base: None,
             sda_base: None,
             stack_address: None,
@@ -134,6 +193,46 @@ impl ObjInfo {
         self.symbols.add(in_symbol, replace)
     }
 
+    /// Appends a symbol without merging it into existing symbols. Symbol indices are never
+    /// shifted, so relocations stay valid; use [`sorted_symbol_order`] for presentation.
+    ///
+    /// [`sorted_symbol_order`]: ObjInfo::sorted_symbol_order
+    pub fn insert_symbol(&mut self, symbol: ObjSymbol) -> Result<SymbolIndex> {
+        self.symbols.add_direct(symbol)
+    }
+
+    /// Symbol indices ordered by section, address, then name, leaving storage order as-is.
+    pub fn sorted_symbol_order(&self) -> Vec<SymbolIndex> {
+        let mut order = self.symbols.iter().collect::<Vec<_>>();
+        order.sort_by(|(_, a), (_, b)| {
+            (a.section, a.address, &a.name).cmp(&(b.section, b.address, &b.name))
+        });
+        order.into_iter().map(|(index, _)| index).collect()
+    }
+
+    /// Rename a symbol, recomputing its demangled name.
+    /// Relocations reference symbols by index, so they're unaffected.
+    pub fn rename_symbol(&mut self, index: SymbolIndex, new_name: &str) -> Result<()> {
+        ensure!(index < self.symbols.count(), "Invalid symbol index {}", index);
+        if let Some((other_index, other)) = self
+            .symbols
+            .for_name(new_name)
+            .find(|&(i, s)| i != index && s.flags.is_global() && !s.is_undefined())
+        {
+            bail!(
+                "Cannot rename {} to {}: conflicts with existing symbol {} @ {:#010X}",
+                self.symbols[index].name,
+                new_name,
+                other_index,
+                other.address
+            );
+        }
+        let mut symbol = self.symbols[index].clone();
+        symbol.name = new_name.to_string();
+        symbol.demangled_name = demangle(new_name, &DemangleOptions::default());
+        self.symbols.replace(index, symbol)
+    }
+
     pub fn add_split(
         &mut self,
         section_index: SectionIndex,
@@ -303,6 +402,864 @@ impl ObjInfo {
         Ok(())
     }
 
+    /// Ensure the entry point, if set, lies within a code section.
+    pub fn validate_entry(&self) -> Result<()> {
+        let Some(entry) = self.entry else {
+            return Ok(());
+        };
+        let Some((_, section)) =
+            self.sections.iter().find(|(_, s)| (s.address..s.address + s.size).contains(&entry))
+        else {
+            bail!("Entry point {:#010X} is outside of all sections", entry);
+        };
+        ensure!(
+            section.kind == ObjSectionKind::Code,
+            "Entry point {:#010X} is in non-code section {} ({:#010X}-{:#010X})",
+            entry,
+            section.name,
+            section.address,
+            section.address + section.size
+        );
+        Ok(())
+    }
+
+    /// Removes a section, shifting the indices of all later sections down by one.
+    /// Symbols defined in the removed section become undefined if `undefine_symbols`
+    /// is set; otherwise, an error is returned. Relocations and splits within the
+    /// section are dropped along with it, as are unresolved REL relocations from or into
+    /// it. Unresolved relocations refer to sections by ELF index, which doesn't change.
+    /// Entry addresses within the section are dropped, and later ones are remapped.
+    pub fn remove_section(&mut self, index: SectionIndex, undefine_symbols: bool) -> Result<()> {
+        let (section_name, elf_index, section_range) = match self.sections.get(index) {
+            Some(section) => (
+                section.name.clone(),
+                section.elf_index,
+                section.address..section.address + section.size,
+            ),
+            None => bail!("Invalid section index {}", index),
+        };
+        let module_id = self.module_id;
+        let into_section = |reloc: &RelReloc| {
+            reloc.module_id == module_id && reloc.target_section as SectionIndex == elf_index
+        };
+        if let Some(reloc) = self.unresolved_relocations.iter().find(|r| into_section(r)) {
+            ensure!(
+                undefine_symbols,
+                "Unresolved relocation @ {:#010X} targets removed section {}",
+                reloc.address,
+                section_name
+            );
+        }
+        let mut symbols = Vec::with_capacity(self.symbols.count() as usize);
+        for (_, symbol) in self.symbols.iter() {
+            let mut symbol = symbol.clone();
+            match symbol.section {
+                Some(section) if section == index => {
+                    ensure!(
+                        undefine_symbols,
+                        "Symbol {} is defined in removed section {}",
+                        symbol.name,
+                        section_name
+                    );
+                    symbol.section = None;
+                    symbol.address = 0;
+                    symbol.size = 0;
+                    symbol.size_known = false;
+                }
+                Some(section) if section > index => symbol.section = Some(section - 1),
+                _ => {}
+            }
+            symbols.push(symbol);
+        }
+        self.sections.remove(index);
+        self.symbols = ObjSymbols::new(self.kind, symbols);
+        self.unresolved_relocations
+            .retain(|reloc| reloc.section as SectionIndex != elf_index && !into_section(reloc));
+        let remap = |addr: SectionAddress| match addr.section {
+            section if section == index => None,
+            section if section > index => Some(SectionAddress::new(section - 1, addr.address)),
+            _ => Some(addr),
+        };
+        self.start_address = self.start_address.and_then(remap);
+        if self.kind == ObjKind::Executable
+            && self.entry.is_some_and(|entry| section_range.contains(&entry))
+        {
+            self.entry = None;
+        }
+        self.entry_chain = std::mem::take(&mut self.entry_chain)
+            .into_iter()
+            .filter_map(|(name, addr)| Some((name, remap(addr)?)))
+            .collect();
+        self.blocked_relocation_sources.remove_section(index);
+        self.blocked_relocation_targets.remove_section(index);
+        self.known_functions = std::mem::take(&mut self.known_functions)
+            .into_iter()
+            .filter(|(addr, _)| addr.section != index)
+            .map(|(addr, size)| {
+                let section = if addr.section > index { addr.section - 1 } else { addr.section };
+                (SectionAddress::new(section, addr.address), size)
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Patches section data with the final value of each relocation and clears the
+    /// relocation lists, producing a flat image. Only valid for executables.
+    pub fn apply_relocations(&mut self) -> Result<()> {
+        self.resolve_relocations()?;
+        for (_, section) in self.sections.iter_mut() {
+            section.relocations = Default::default();
+        }
+        Ok(())
+    }
+
+    /// Patches section data with the final value of each relocation, keeping the
+    /// relocation lists. Only valid for executables. Relocations against other modules are
+    /// left for the module loader.
+    pub fn resolve_relocations(&mut self) -> Result<()> {
+        ensure!(self.kind == ObjKind::Executable, "Can only apply relocations in executables");
+        let mut patches = vec![];
+        for (section_index, section) in self.sections.iter() {
+            for (addr, reloc) in section.relocations.iter() {
+                if reloc.module.is_some_and(|module| module != self.module_id) {
+                    continue;
+                }
+                let target = &self.symbols[reloc.target_symbol];
+                ensure!(
+                    !target.is_undefined(),
+                    "Relocation @ {:#010X} against undefined symbol {}",
+                    addr,
+                    target.name
+                );
+                let value = (target.address as i64 + reloc.addend) as u32;
+                let offset = (addr as u64 - section.address) as usize;
+                ensure!(
+                    offset + 4 <= section.data.len(),
+                    "Relocation @ {:#010X} outside of section {} data",
+                    addr,
+                    section.name
+                );
+                let ins = u32::from_bytes(*array_ref!(section.data, offset, 4), self.endian);
+                let ins = match reloc.kind {
+                    ObjRelocKind::Absolute => value,
+                    ObjRelocKind::PpcRel32 => value.wrapping_sub(addr),
+                    ObjRelocKind::PpcEmbNaddr32 => value.wrapping_neg(),
+                    ObjRelocKind::PpcEmbNaddr16 => {
+                        (ins & !0xFFFF) | (value.wrapping_neg() & 0xFFFF)
+                    }
+                    ObjRelocKind::PpcEmbSdai16 | ObjRelocKind::PpcEmbSda2i16 => {
+                        bail!("Unsupported relocation {:?} @ {:#010X}", reloc.kind, addr)
+                    }
+                    ObjRelocKind::PpcTprel16
+                    | ObjRelocKind::PpcTprel16Lo
+                    | ObjRelocKind::PpcTprel16Hi
+                    | ObjRelocKind::PpcTprel16Ha
+                    | ObjRelocKind::PpcDtprel16
+                    | ObjRelocKind::PpcDtprel16Lo
+                    | ObjRelocKind::PpcDtprel16Hi
+                    | ObjRelocKind::PpcDtprel16Ha => {
+                        bail!("Unsupported TLS relocation {:?} @ {:#010X}", reloc.kind, addr)
+                    }
+                    ObjRelocKind::PpcAddr16 | ObjRelocKind::PpcAddr16Lo => {
+                        (ins & !0xFFFF) | (value & 0xFFFF)
+                    }
+                    ObjRelocKind::PpcAddr16Hi => (ins & !0xFFFF) | (value >> 16),
+                    ObjRelocKind::PpcAddr16Ha => {
+                        (ins & !0xFFFF) | (value.wrapping_add(0x8000) >> 16)
+                    }
+                    ObjRelocKind::PpcRel24 => {
+                        let diff = value.wrapping_sub(addr) as i32;
+                        ensure!(
+                            (-0x2000000..0x2000000).contains(&diff),
+                            "R_PPC_REL24 relocation @ {:#010X} out of range",
+                            addr
+                        );
+                        (ins & !0x3FFFFFC) | (diff as u32 & 0x3FFFFFC)
+                    }
+                    ObjRelocKind::PpcAddr24 => {
+                        ensure!(
+                            (-0x2000000..0x2000000).contains(&(value as i32)),
+                            "R_PPC_ADDR24 relocation @ {:#010X} out of range",
+                            addr
+                        );
+                        (ins & !0x3FFFFFC) | (value & 0x3FFFFFC)
+                    }
+                    ObjRelocKind::PpcRel14 => {
+                        let diff = value.wrapping_sub(addr) as i32;
+                        ensure!(
+                            (-0x8000..0x8000).contains(&diff),
+                            "R_PPC_REL14 relocation @ {:#010X} out of range",
+                            addr
+                        );
+                        (ins & !0xFFFC) | (diff as u32 & 0xFFFC)
+                    }
+                    ObjRelocKind::PpcEmbSda21 => {
+                        let target_section = target.section.and_then(|idx| self.sections.get(idx));
+                        let (reg, base) = match target_section.and_then(|s| s.small_data_area()) {
+                            Some(SmallDataArea::Sda) => (13, self.sda_base),
+                            Some(SmallDataArea::Sda2) => (2, self.sda2_base),
+                            None => (0, Some(0)),
+                        };
+                        let base = base.ok_or_else(|| {
+                            anyhow!("Relocation @ {:#010X} requires SDA base", addr)
+                        })?;
+                        let diff = value.wrapping_sub(base) as i32;
+                        // Paired-single loads and stores have a 12-bit displacement
+                        let (range, disp_mask) = if is_paired_single_load_store(ins) {
+                            (-0x800..0x800, 0xFFF)
+                        } else {
+                            (-0x8000..0x8000, 0xFFFF)
+                        };
+                        ensure!(
+                            range.contains(&diff),
+                            "R_PPC_EMB_SDA21 relocation @ {:#010X} out of range",
+                            addr
+                        );
+                        (ins & !sda21_field_mask(ins)) | (reg << 16) | (diff as u32 & disp_mask)
+                    }
+                };
+                patches.push((section_index, offset, ins));
+            }
+        }
+        for (section_index, offset, ins) in patches {
+            ins.to_writer_at(&mut self.sections[section_index].data, offset, self.endian)?;
+        }
+        Ok(())
+    }
+
+    /// Moves `_SDA_BASE_` to `new_base`. In executables, the displacements encoded by
+    /// `R_PPC_EMB_SDA21` relocations into `.sdata`/`.sbss` are recomputed relative to the
+    /// new base. Relocatable objects only record the new base, since the linker
+    /// resolves SDA21 relocations.
+    pub fn rebase_sda(&mut self, new_base: u32) -> Result<()> {
+        if self.kind == ObjKind::Executable {
+            let mut patches = vec![];
+            for (section_index, section) in self.sections.iter() {
+                for (addr, reloc) in section.relocations.iter() {
+                    if reloc.kind != ObjRelocKind::PpcEmbSda21 {
+                        continue;
+                    }
+                    let target = &self.symbols[reloc.target_symbol];
+                    let target_section = target.section.and_then(|idx| self.sections.get(idx));
+                    if target_section.and_then(|s| s.small_data_area()) != Some(SmallDataArea::Sda)
+                    {
+                        continue;
+                    }
+                    let offset = (addr as u64 - section.address) as usize;
+                    ensure!(
+                        offset + 4 <= section.data.len(),
+                        "Relocation @ {:#010X} outside of section {} data",
+                        addr,
+                        section.name
+                    );
+                    let ins = u32::from_bytes(*array_ref!(section.data, offset, 4), self.endian);
+                    let (range, disp_mask) = if is_paired_single_load_store(ins) {
+                        (-0x800..0x800, 0xFFF)
+                    } else {
+                        (-0x8000..0x8000, 0xFFFF)
+                    };
+                    let value = (target.address as i64 + reloc.addend) as u32;
+                    let diff = value.wrapping_sub(new_base) as i32;
+                    ensure!(
+                        range.contains(&diff),
+                        "R_PPC_EMB_SDA21 relocation @ {:#010X} out of range of SDA base {:#010X}",
+                        addr,
+                        new_base
+                    );
+                    patches.push((
+                        section_index,
+                        offset,
+                        (ins & !disp_mask) | (diff as u32 & disp_mask),
+                    ));
+                }
+            }
+            for (section_index, offset, ins) in patches {
+                ins.to_writer_at(&mut self.sections[section_index].data, offset, self.endian)?;
+            }
+        }
+        if let Some((index, symbol)) = self.symbols.by_name("_SDA_BASE_")? {
+            let symbol = ObjSymbol { address: new_base as u64, ..symbol.clone() };
+            self.symbols.replace(index, symbol)?;
+        }
+        self.sda_base = Some(new_base);
+        Ok(())
+    }
+
+    /// Addresses reachable from `_SDA_BASE_` with a signed 16-bit offset.
+    pub fn sda_window(&self) -> Option<Range<u32>> { self.sda_base.map(sda_window) }
+
+    /// Addresses reachable from `_SDA2_BASE_` with a signed 16-bit offset.
+    pub fn sda2_window(&self) -> Option<Range<u32>> { self.sda2_base.map(sda_window) }
+
+    /// Checks that an `R_PPC_EMB_SDA21` target in a small data section lies within the
+    /// window of its base. Other targets are addressed relative to r0 and aren't checked.
+    pub fn check_sda21_target(&self, target: &ObjSymbol) -> Result<()> {
+        let section = target.section.and_then(|idx| self.sections.get(idx));
+        let (window, base_name) = match section.and_then(|s| s.small_data_area()) {
+            Some(SmallDataArea::Sda) => (self.sda_window(), "_SDA_BASE_"),
+            Some(SmallDataArea::Sda2) => (self.sda2_window(), "_SDA2_BASE_"),
+            None => return Ok(()),
+        };
+        let window = window.ok_or_else(|| anyhow!("{} requires {}", target.name, base_name))?;
+        ensure!(
+            window.contains(&(target.address as u32)),
+            "SDA21 target {} @ {:#010X} outside of {} window {:#010X}-{:#010X}",
+            target.name,
+            target.address,
+            base_name,
+            window.start,
+            window.end
+        );
+        Ok(())
+    }
+
+    /// Validates internal consistency: symbol section indices, relocation targets,
+    /// section data sizes and split units.
+    pub fn check(&self) -> Result<()> {
+        for (symbol_index, symbol) in self.symbols.iter() {
+            if let Some(section_index) = symbol.section {
+                ensure!(
+                    section_index < self.sections.len(),
+                    "Symbol {} ({}) references invalid section index {}",
+                    symbol_index,
+                    symbol.name,
+                    section_index
+                );
+            }
+        }
+        for (_, section) in self.sections.iter() {
+            if section.kind != ObjSectionKind::Bss {
+                ensure!(
+                    section.data.len() as u64 == section.size,
+                    "Section {} data length {:#X} doesn't match size {:#X}",
+                    section.name,
+                    section.data.len(),
+                    section.size
+                );
+            }
+            for (addr, reloc) in section.relocations.iter() {
+                ensure!(
+                    reloc.target_symbol < self.symbols.count(),
+                    "Relocation @ {}:{:#010X} targets invalid symbol index {}",
+                    section.name,
+                    addr,
+                    reloc.target_symbol
+                );
+                let target = &self.symbols[reloc.target_symbol];
+                ensure!(
+                    !target.flags.is_stripped(),
+                    "Relocation @ {}:{:#010X} targets stripped symbol {}",
+                    section.name,
+                    addr,
+                    target.name
+                );
+            }
+            for (addr, split) in section.splits.iter() {
+                ensure!(
+                    self.link_order.iter().any(|unit| unit.name == split.unit),
+                    "Split @ {}:{:#010X} references unknown unit {}",
+                    section.name,
+                    addr,
+                    split.unit
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// For executables, checks that every relocation targets a defined symbol: one with a
+    /// section, or an absolute address. Relocations against other modules are skipped.
+    /// Relocatable objects are exempt, since their references are resolved at link time.
+    pub fn verify_relocations_resolve(&self) -> Result<()> {
+        if self.kind != ObjKind::Executable {
+            return Ok(());
+        }
+        for (_, section) in self.sections.iter() {
+            for (addr, reloc) in section.relocations.iter() {
+                if reloc.module.is_some_and(|module| module != self.module_id) {
+                    continue;
+                }
+                let target = &self.symbols[reloc.target_symbol];
+                ensure!(
+                    !target.is_undefined(),
+                    "Relocation @ {}:{:#010X} targets unresolved symbol {}",
+                    section.name,
+                    addr,
+                    target.name
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Pairs of section indices whose address ranges intersect.
+    /// Empty sections are ignored.
+    pub fn overlapping_sections(&self) -> Vec<(SectionIndex, SectionIndex)> {
+        overlapping_sections(self.sections.iter().map(|(_, section)| section))
+    }
+
+    /// Relocations against external modules, grouped by target module ID.
+    /// Each entry is (section index, address, relocation), in section and address order.
+    pub fn relocations_by_module(&self) -> HashMap<u32, Vec<(SectionIndex, u32, &ObjReloc)>> {
+        let mut result = HashMap::<u32, Vec<_>>::new();
+        for (section_index, section) in self.sections.iter() {
+            for (address, reloc) in section.relocations.iter() {
+                if let Some(module) = reloc.module {
+                    result.entry(module).or_default().push((section_index, address, reloc));
+                }
+            }
+        }
+        result
+    }
+
+    /// Moves a section to `new_address`, shifting its symbols, relocations and splits by the
+    /// same amount. Relocations are symbol-relative, so relative relocations within the
+    /// section stay intact and absolute relocations resolve to the new addresses on emit.
+    /// Executables hold resolved values, so their relocations are re-applied to every
+    /// section, and the entry point moves along with the section. On error, the object is
+    /// left unchanged.
+    pub fn set_section_address(&mut self, index: SectionIndex, new_address: u32) -> Result<()> {
+        // Re-applying relocations can fail, so move the section in a copy
+        let mut staged = self.clone();
+        staged.move_section(index, new_address)?;
+        *self = staged;
+        Ok(())
+    }
+
+    fn move_section(&mut self, index: SectionIndex, new_address: u32) -> Result<()> {
+        let section = self
+            .sections
+            .get_mut(index)
+            .ok_or_else(|| anyhow!("Invalid section index {}", index))?;
+        let delta = new_address as i64 - section.address as i64;
+        if delta == 0 {
+            return Ok(());
+        }
+        let shift = |address: u32| (address as i64 + delta) as u32;
+        if let Some(entry) = self.entry.filter(|&entry| section.contains(entry as u32)) {
+            self.entry = Some(shift(entry as u32) as u64);
+        }
+        section.address = new_address as u64;
+        section.virtual_address =
+            section.virtual_address.map(|address| (address as i64 + delta) as u64);
+        section.relocations = ObjRelocations::new(
+            section
+                .relocations
+                .iter()
+                .map(|(address, reloc)| (shift(address), reloc.clone()))
+                .collect(),
+        )?;
+        let mut splits = ObjSplits::default();
+        for (address, split) in section.splits.iter() {
+            // An end of 0 means the split runs to the end of the section
+            let end = if split.end == 0 { 0 } else { shift(split.end) };
+            splits.push(shift(address), ObjSplit { end, ..split.clone() });
+        }
+        section.splits = splits;
+
+        let mut symbols = Vec::with_capacity(self.symbols.count() as usize);
+        for (_, symbol) in self.symbols.iter() {
+            let mut symbol = symbol.clone();
+            if symbol.section == Some(index) {
+                symbol.address = shift(symbol.address as u32) as u64;
+            }
+            symbols.push(symbol);
+        }
+        self.symbols = ObjSymbols::new(self.kind, symbols);
+        self.blocked_relocation_sources.shift_section(index, delta);
+        self.blocked_relocation_targets.shift_section(index, delta);
+        self.known_functions = std::mem::take(&mut self.known_functions)
+            .into_iter()
+            .map(|(addr, size)| {
+                if addr.section == index {
+                    (SectionAddress::new(index, shift(addr.address)), size)
+                } else {
+                    (addr, size)
+                }
+            })
+            .collect();
+        if let Some(start) = self.start_address.filter(|start| start.section == index) {
+            self.start_address = Some(SectionAddress::new(index, shift(start.address)));
+        }
+        for (_, address) in self.entry_chain.iter_mut().filter(|(_, a)| a.section == index) {
+            address.address = shift(address.address);
+        }
+        if self.kind == ObjKind::Executable {
+            self.resolve_relocations()?;
+        }
+        Ok(())
+    }
+
+    /// Rounds a section's size up to its alignment, zero-filling the data of non-BSS
+    /// sections. The section address must already be aligned.
+    pub fn align_section(&mut self, index: SectionIndex) -> Result<()> {
+        let section = self
+            .sections
+            .get_mut(index)
+            .ok_or_else(|| anyhow!("Invalid section index {}", index))?;
+        let align = section.align.max(1);
+        ensure!(
+            align.is_power_of_two(),
+            "Invalid alignment {:#X} for section {}",
+            align,
+            section.name
+        );
+        ensure!(
+            section.address % align == 0,
+            "Section {} address {:#010X} is not aligned to {:#X}",
+            section.name,
+            section.address,
+            align
+        );
+        let size = (section.size + align - 1) & !(align - 1);
+        if section.kind != ObjSectionKind::Bss {
+            section.data.resize(size as usize, 0);
+        }
+        section.size = size;
+        Ok(())
+    }
+
+    /// Resolves names defined both weakly and strongly in favor of the strong definition.
+    /// Weak definitions are removed, and relocations against them retarget the strong
+    /// symbol. Multiple strong definitions of a name are an error.
+    pub fn resolve_weak(&mut self) -> Result<()> {
+        let mut definitions = BTreeMap::<&str, (Option<SymbolIndex>, Vec<SymbolIndex>)>::new();
+        for (index, symbol) in self.symbols.iter() {
+            if symbol.name.is_empty() || symbol.flags.is_local() || symbol.section.is_none() {
+                continue;
+            }
+            let (strong, weak) = definitions.entry(symbol.name.as_str()).or_default();
+            if symbol.flags.is_weak() {
+                weak.push(index);
+            } else if let Some(existing) = *strong {
+                bail!(
+                    "Multiple strong definitions of {}: symbols {} and {}",
+                    symbol.name,
+                    existing,
+                    index
+                );
+            } else {
+                *strong = Some(index);
+            }
+        }
+        let mut replacements = HashMap::<SymbolIndex, SymbolIndex>::new();
+        for (strong, weak) in definitions.into_values() {
+            if let Some(strong) = strong {
+                replacements.extend(weak.into_iter().map(|index| (index, strong)));
+            }
+        }
+        if !replacements.is_empty() {
+            self.retain_symbols(|index, _| !replacements.contains_key(&index), &replacements);
+        }
+        Ok(())
+    }
+
+    /// Rebuilds the symbol table from the symbols `keep` accepts, updating relocation targets
+    /// to the new indices. Relocations against a removed symbol are retargeted to its entry
+    /// in `replacements`; all other relocations must already target kept symbols.
+    fn retain_symbols<F>(&mut self, mut keep: F, replacements: &HashMap<SymbolIndex, SymbolIndex>)
+    where F: FnMut(SymbolIndex, &ObjSymbol) -> bool {
+        let mut new_indexes = Vec::with_capacity(self.symbols.count() as usize);
+        let mut symbols = Vec::with_capacity(self.symbols.count() as usize);
+        for (index, symbol) in self.symbols.iter() {
+            // Removed symbols are never looked up below
+            new_indexes.push(symbols.len() as SymbolIndex);
+            if keep(index, symbol) {
+                symbols.push(symbol.clone());
+            }
+        }
+        let module_id = self.module_id;
+        self.map_relocations(|_, _, reloc| {
+            // Relocations against other modules index their symbol tables
+            if reloc.module.is_some_and(|module| module != module_id) {
+                return;
+            }
+            let target = replacements.get(&reloc.target_symbol).unwrap_or(&reloc.target_symbol);
+            reloc.target_symbol = new_indexes[*target as usize];
+        });
+        self.symbols = ObjSymbols::new(self.kind, symbols);
+    }
+
+    /// Removes everything not reachable from the `roots` symbols by following relocations
+    /// transitively. Unreachable symbols are removed, along with relocations outside of
+    /// reachable symbols and sections left with no reachable symbols. The data of kept
+    /// sections is left intact. A reachable section symbol keeps its whole section, and a
+    /// reachable symbol of unknown or zero size extends to the next symbol or the section end.
+    /// Entry addresses within removed sections are dropped, as with [`Self::remove_section`].
+    /// Relocations against other modules are kept with their live ranges but not followed.
+    pub fn gc_sections(&mut self, roots: &[SymbolIndex]) -> Result<()> {
+        let count = self.symbols.count();
+        for &root in roots {
+            ensure!(root < count, "Invalid root symbol index {}", root);
+        }
+        let mut reachable = vec![false; count as usize];
+        let mut live_ranges = BTreeMap::<SectionIndex, Vec<Range<u32>>>::new();
+        let mut queue = roots.to_vec();
+        while let Some(index) = queue.pop() {
+            if std::mem::replace(&mut reachable[index as usize], true) {
+                continue;
+            }
+            let symbol = &self.symbols[index];
+            let Some(section_index) = symbol.section else {
+                continue;
+            };
+            let section = &self.sections[section_index];
+            let section_end = (section.address + section.size) as u32;
+            let range = if symbol.kind == ObjSymbolKind::Section {
+                section.address as u32..section_end
+            } else if !symbol.size_known || symbol.size == 0 {
+                let start = symbol.address as u32;
+                let end = self
+                    .symbols
+                    .for_section_range(section_index, start + 1..)
+                    .next()
+                    .map_or(section_end, |(_, next)| next.address as u32);
+                start..end
+            } else {
+                symbol.address as u32..(symbol.address + symbol.size) as u32
+            };
+            queue.extend(
+                section
+                    .relocations
+                    .range(range.clone())
+                    .filter(|(_, r)| !r.module.is_some_and(|module| module != self.module_id))
+                    .map(|(_, r)| r.target_symbol),
+            );
+            live_ranges.entry(section_index).or_default().push(range);
+        }
+
+        // Relocations within live ranges only target reachable symbols
+        for (section_index, section) in self.sections.iter_mut() {
+            let ranges = live_ranges.get(&section_index).map(Vec::as_slice).unwrap_or_default();
+            let relocations = section
+                .relocations
+                .iter()
+                .filter(|(address, _)| ranges.iter().any(|range| range.contains(address)))
+                .map(|(address, reloc)| (address, reloc.clone()))
+                .collect();
+            section.relocations = ObjRelocations::new(relocations)?;
+        }
+        self.retain_symbols(|index, _| reachable[index as usize], &HashMap::new());
+
+        let dead_sections = self
+            .sections
+            .iter()
+            .map(|(index, _)| index)
+            .filter(|index| !live_ranges.contains_key(index))
+            .collect::<Vec<_>>();
+        for index in dead_sections.into_iter().rev() {
+            self.remove_section(index, false)?;
+        }
+        Ok(())
+    }
+
+    /// Calls `f` with the section index and address of every relocation, allowing it to be
+    /// modified in place.
+    pub fn map_relocations<F>(&mut self, mut f: F)
+    where F: FnMut(SectionIndex, u32, &mut ObjReloc) {
+        for (section_index, section) in self.sections.iter_mut() {
+            for (address, reloc) in section.relocations.iter_mut() {
+                f(section_index, address, reloc);
+            }
+        }
+    }
+
+    /// Splits the object into one relocatable object per unit, passing each to `f` in link
+    /// order and dropping it before building the next. Units with no splits other than
+    /// skipped ones are omitted.
+    pub fn for_each_split<F>(&self, mut f: F) -> Result<()>
+    where F: FnMut(&str, ObjInfo) -> Result<()> {
+        let emitted_units = self
+            .sections
+            .all_splits()
+            .filter(|(_, _, _, split)| !split.skip)
+            .map(|(_, _, _, split)| split.unit.as_str())
+            .collect::<HashSet<_>>();
+        for_each_split_obj(self, None, |unit, split_obj| {
+            if emitted_units.contains(unit.name.as_str()) {
+                f(&unit.name, split_obj)
+            } else {
+                Ok(())
+            }
+        })
+    }
+
+    /// Symbols the object depends on externally: no section, not common and not absolute.
+    /// These are written as `SHN_UNDEF`.
+    pub fn undefined_symbols(&self) -> impl Iterator<Item = (SymbolIndex, &ObjSymbol)> {
+        self.symbols.iter().filter(|(_, symbol)| symbol.is_undefined())
+    }
+
+    /// Symbols matching `query` by name or demangled name. Name matches come first.
+    pub fn find_symbols<'a>(
+        &'a self,
+        query: &'a str,
+    ) -> impl Iterator<Item = (SymbolIndex, &'a ObjSymbol)> + 'a {
+        self.symbols.for_name(query).chain(self.symbols.iter().filter(move |(_, symbol)| {
+            symbol.name != query && symbol.demangled_name.as_deref() == Some(query)
+        }))
+    }
+
+    /// The first symbol matching `query` by name or demangled name.
+    /// Use [`find_symbols`](Self::find_symbols) to handle ambiguous matches.
+    pub fn find_symbol(&self, query: &str) -> Option<(SymbolIndex, &ObjSymbol)> {
+        self.find_symbols(query).next()
+    }
+
+    /// The canonical symbol at a section address, for resolving references to it, ranked by
+    /// [`canonical_symbol_key`]. Stripped symbols are never chosen.
+    pub fn primary_symbol_at(
+        &self,
+        section: SectionIndex,
+        address: u32,
+    ) -> Option<(SymbolIndex, &ObjSymbol)> {
+        self.symbols
+            .at_section_address(section, address)
+            .min_by_key(|&(index, symbol)| canonical_symbol_key(index, symbol))
+    }
+
+    /// Compares the symbol tables of two objects. Symbols are matched by name, then by
+    /// demangled name, so that e.g. a symbol named by its demangled form in `other` still
+    /// matches. Section symbols and unnamed symbols are ignored.
+    pub fn diff_symbols(&self, other: &ObjInfo) -> SymbolDiff {
+        fn is_comparable(symbol: &ObjSymbol) -> bool {
+            symbol.kind != ObjSymbolKind::Section && !symbol.name.is_empty()
+        }
+        fn demangled_key(symbol: &ObjSymbol) -> String {
+            symbol
+                .demangled_name
+                .clone()
+                .or_else(|| demangle(&symbol.name, &DemangleOptions::default()))
+                .unwrap_or_else(|| symbol.name.clone())
+        }
+
+        let mut other_by_demangled = HashMap::<String, SymbolIndex>::new();
+        for (index, symbol) in other.symbols.iter().filter(|(_, s)| is_comparable(s)) {
+            other_by_demangled.entry(demangled_key(symbol)).or_insert(index);
+        }
+        let mut matched = HashSet::<SymbolIndex>::new();
+        let mut diff = SymbolDiff::default();
+        for (index, symbol) in self.symbols.iter().filter(|(_, s)| is_comparable(s)) {
+            let other_index = other
+                .symbols
+                .for_name(&symbol.name)
+                .map(|(i, _)| i)
+                .find(|i| !matched.contains(i))
+                .or_else(|| {
+                    other_by_demangled
+                        .get(&demangled_key(symbol))
+                        .copied()
+                        .filter(|i| !matched.contains(i))
+                });
+            let Some(other_index) = other_index else {
+                diff.only_in_self.push(index);
+                continue;
+            };
+            matched.insert(other_index);
+            let other_symbol = &other.symbols[other_index];
+            if symbol.address != other_symbol.address
+                || symbol.size != other_symbol.size
+                || symbol.kind != other_symbol.kind
+                || symbol.flags != other_symbol.flags
+            {
+                diff.changed.push((index, other_index));
+            }
+        }
+        diff.only_in_other = other
+            .symbols
+            .iter()
+            .filter(|&(index, symbol)| is_comparable(symbol) && !matched.contains(&index))
+            .map(|(index, _)| index)
+            .collect();
+        diff
+    }
+
+    /// Adds a relocation whose target is known as an offset into `target_section` rather
+    /// than a symbol. The relocation targets the section symbol (created if missing), with
+    /// the offset as the addend.
+    pub fn add_section_relative_reloc(
+        &mut self,
+        section: SectionIndex,
+        address: u32,
+        kind: ObjRelocKind,
+        target_section: SectionIndex,
+        offset: i64,
+    ) -> Result<()> {
+        let target = self
+            .sections
+            .get(target_section)
+            .ok_or_else(|| anyhow!("Invalid target section index {}", target_section))?;
+        let existing = self
+            .symbols
+            .by_kind(ObjSymbolKind::Section)
+            .find(|(_, symbol)| symbol.section == Some(target_section))
+            .map(|(index, _)| index);
+        let target_symbol = match existing {
+            Some(index) => index,
+            None => self.symbols.add_direct(ObjSymbol {
+                name: target.name.clone(),
+                address: target.address,
+                section: Some(target_section),
+                flags: ObjSymbolFlagSet(ObjSymbolFlags::Local.into()),
+                kind: ObjSymbolKind::Section,
+                ..Default::default()
+            })?,
+        };
+        let section = self
+            .sections
+            .get_mut(section)
+            .ok_or_else(|| anyhow!("Invalid section index {}", section))?;
+        ensure!(
+            section.contains(address),
+            "Relocation @ {:#010X} outside of section {}",
+            address,
+            section.name
+        );
+        section.relocations.insert(address, ObjReloc {
+            kind,
+            target_symbol,
+            addend: offset,
+            module: None,
+        })
+    }
+
+    /// Indices of common symbols, in their original layout (address) order.
+    /// Includes symbols flagged common and symbols following the common BSS split.
+    pub fn common_bss_layout(&self) -> Vec<SymbolIndex> {
+        let common_start = self.sections.common_bss_start();
+        let mut symbols = self
+            .symbols
+            .iter()
+            .filter(|(_, symbol)| {
+                symbol.flags.is_common()
+                    || matches!(
+                        (common_start, symbol.section),
+                        (Some(start), Some(section))
+                            if section == start.section && symbol.address as u32 >= start.address
+                    )
+            })
+            .map(|(index, symbol)| (symbol.address, index))
+            .collect::<Vec<_>>();
+        symbols.sort();
+        symbols.into_iter().map(|(_, index)| index).collect()
+    }
+
+    /// SHA-256 of each section's contents, for content-addressed caching of split output.
+    /// BSS sections have no data, so only their size is hashed.
+    pub fn section_hashes(&self) -> Vec<(String, [u8; 32])> {
+        self.sections
+            .iter()
+            .map(|(_, section)| {
+                let mut hasher = Sha256::new();
+                hasher.update(section.size.to_be_bytes());
+                if section.kind != ObjSectionKind::Bss {
+                    hasher.update(&section.data);
+                }
+                (section.name.clone(), hasher.finalize().into())
+            })
+            .collect()
+    }
+
     pub fn is_unit_autogenerated(&self, unit: &str) -> bool {
         self.sections
             .all_splits()
@@ -310,6 +1267,42 @@ impl ObjInfo {
             .all(|(_, _, _, split)| split.autogenerated)
     }
 
+    /// Counts symbols by kind, scope and definition, and totals section sizes by kind.
+    /// Section symbols are not counted.
+    pub fn stats(&self) -> ObjStats {
+        let mut stats = ObjStats::default();
+        for (_, symbol) in self.symbols.iter() {
+            match symbol.kind {
+                ObjSymbolKind::Function => stats.functions += 1,
+                ObjSymbolKind::Object => stats.objects += 1,
+                ObjSymbolKind::Section | ObjSymbolKind::File => continue,
+                ObjSymbolKind::Unknown => {}
+            }
+            if symbol.flags.is_weak() {
+                stats.weak += 1;
+            } else if symbol.flags.is_local() {
+                stats.locals += 1;
+            } else {
+                stats.globals += 1;
+            }
+            if symbol.is_undefined() {
+                stats.undefined += 1;
+            } else {
+                stats.defined += 1;
+            }
+        }
+        for (_, section) in self.sections.iter() {
+            match section.kind {
+                ObjSectionKind::Code => stats.code_size += section.size,
+                ObjSectionKind::Data | ObjSectionKind::ReadOnlyData => {
+                    stats.data_size += section.size
+                }
+                ObjSectionKind::Bss => stats.bss_size += section.size,
+            }
+        }
+        stats
+    }
+
     /// Calculate the total size of all code sections.
     pub fn code_size(&self) -> u32 {
         self.sections
@@ -334,4 +1327,968 @@ impl ObjInfo {
             )
             .sum()
     }
+
+    /// Flattens the loaded code and data sections into a single image at their addresses,
+    /// returning the base address and data. Gaps and BSS within the range are zero-filled.
+    pub fn to_flat_binary(&self) -> Result<(u32, Vec<u8>)> {
+        ensure!(self.kind == ObjKind::Executable, "Flat binary requires an executable");
+        let mut sections = self
+            .sections
+            .iter()
+            .filter(|(_, s)| s.kind != ObjSectionKind::Bss && s.load && s.size != 0)
+            .map(|(_, s)| s)
+            .collect::<Vec<_>>();
+        sections.sort_by_key(|s| s.address);
+        let (Some(first), Some(last)) = (sections.first(), sections.last()) else {
+            bail!("No loadable sections");
+        };
+        let base = first.address;
+        let mut data = vec![0u8; (last.address + last.size - base) as usize];
+        let mut end = base;
+        for section in sections {
+            ensure!(
+                section.address >= end,
+                "Section {} @ {:#010X} overlaps previous section ending @ {:#010X}",
+                section.name,
+                section.address,
+                end
+            );
+            ensure!(
+                section.data.len() as u64 == section.size,
+                "Section {} data size mismatch",
+                section.name
+            );
+            let offset = (section.address - base) as usize;
+            data[offset..offset + section.data.len()].copy_from_slice(&section.data);
+            end = section.address + section.size;
+        }
+        Ok((base as u32, data))
+    }
+}
+
+fn sda_window(base: u32) -> Range<u32> { base.saturating_sub(0x8000)..base.saturating_add(0x8000) }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::testing::{test_section, test_section_words, test_symbol};
+
+    #[test]
+    fn test_validate_entry() {
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "main.dol".to_string(),
+            vec![],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0x80003100, 0x100),
+                test_section(".data", ObjSectionKind::Data, 0x80003200, 0x100),
+            ],
+        );
+        obj.entry = Some(0x80003100);
+        assert!(obj.validate_entry().is_ok());
+        obj.entry = Some(0x80003200);
+        assert!(obj.validate_entry().is_err());
+        obj.entry = Some(0x80004000);
+        assert!(obj.validate_entry().is_err());
+    }
+
+    #[test]
+    fn test_overlapping_sections() {
+        let obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![],
+            vec![
+                test_section(".data", ObjSectionKind::Data, 0x80004000, 0x100),
+                test_section(".sdata", ObjSectionKind::Data, 0x80004080, 0x100),
+                // Adjacent to .sdata
+                test_section(".bss", ObjSectionKind::Bss, 0x80004180, 0x100),
+                // Empty, within .data
+                test_section(".sbss", ObjSectionKind::Bss, 0x80004010, 0),
+                test_section(".data.a", ObjSectionKind::Data, 0x80004000, 0x10),
+            ],
+        );
+        assert_eq!(obj.overlapping_sections(), vec![(0, 1), (0, 4)]);
+    }
+
+    #[test]
+    fn test_check_sda21_target() {
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("near", 0, 0x80400000, 4) },
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("far", 0, 0x80410000, 4) },
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("near2", 1, 0x80420000, 4) },
+            ],
+            vec![
+                test_section(".sdata", ObjSectionKind::Data, 0x80400000, 0x10004),
+                test_section(".sdata2", ObjSectionKind::ReadOnlyData, 0x80420000, 4),
+            ],
+        );
+        assert_eq!(obj.sections[0].small_data_area(), Some(SmallDataArea::Sda));
+        assert_eq!(obj.sections[1].small_data_area(), Some(SmallDataArea::Sda2));
+        assert!(obj.check_sda21_target(&obj.symbols[0]).is_err());
+
+        obj.sda_base = Some(0x80408000);
+        obj.sda2_base = Some(0x80428000);
+        assert_eq!(obj.sda_window(), Some(0x80400000..0x80410000));
+        assert!(obj.check_sda21_target(&obj.symbols[0]).is_ok());
+        assert!(obj.check_sda21_target(&obj.symbols[1]).is_err());
+        assert!(obj.check_sda21_target(&obj.symbols[2]).is_ok());
+    }
+
+    #[test]
+    fn test_rebase_sda() {
+        let mut text = test_section(".text", ObjSectionKind::Code, 0x80003100, 4);
+        // lwz r3, -0x7FF0(r13)
+        text.data.copy_from_slice(&0x806D8010u32.to_be_bytes());
+        text.relocations
+            .insert(0x80003100, ObjReloc {
+                kind: ObjRelocKind::PpcEmbSda21,
+                target_symbol: 0,
+                addend: 0,
+                module: None,
+            })
+            .unwrap();
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("var", 1, 0x80400010, 4) }],
+            vec![text, test_section(".sdata", ObjSectionKind::Data, 0x80400000, 0x20)],
+        );
+        obj.sda_base = Some(0x80408000);
+
+        obj.rebase_sda(0x80404000).unwrap();
+        assert_eq!(obj.sda_base, Some(0x80404000));
+        let ins = u32::from_be_bytes(*array_ref!(obj.sections[0].data, 0, 4));
+        assert_eq!(ins, 0x806DC010);
+        let resolved = 0x80404000u32.wrapping_add(ins as i16 as i32 as u32);
+        assert_eq!(resolved, 0x80400010);
+        // Out of range of the new base
+        assert!(obj.rebase_sda(0x80410010).is_err());
+    }
+
+    #[test]
+    fn test_align_section() {
+        let mut data = test_section(".data", ObjSectionKind::Data, 0x80004000, 0x1E);
+        data.data.fill(0xFF);
+        data.align = 0x20;
+        let mut misaligned = test_section(".bss", ObjSectionKind::Bss, 0x80005010, 0x10);
+        misaligned.align = 0x20;
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![],
+            vec![data, misaligned],
+        );
+        obj.align_section(0).unwrap();
+        let section = &obj.sections[0];
+        assert_eq!((section.size, section.data.len()), (0x20, 0x20));
+        assert_eq!(&section.data[0x1C..], &[0xFF, 0xFF, 0, 0]);
+        assert!(obj.align_section(1).is_err());
+    }
+
+    #[test]
+    fn test_resolve_weak() {
+        let mut obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![
+                test_symbol("caller", 0, 0, 4),
+                ObjSymbol {
+                    flags: ObjSymbolFlagSet(ObjSymbolFlags::Weak.into()),
+                    ..test_symbol("foo", 0, 4, 4)
+                },
+                test_symbol("foo", 0, 8, 4),
+            ],
+            vec![test_section(".text", ObjSectionKind::Code, 0, 0xC)],
+        );
+        obj.sections[0]
+            .relocations
+            .insert(0, ObjReloc {
+                kind: ObjRelocKind::PpcRel24,
+                target_symbol: 1,
+                addend: 0,
+                module: None,
+            })
+            .unwrap();
+
+        let mut duplicate = obj.clone();
+        duplicate.symbols.flags(1).set_scope(ObjSymbolScope::Global);
+        assert!(duplicate.resolve_weak().is_err());
+
+        obj.resolve_weak().unwrap();
+        assert_eq!(obj.symbols.count(), 2);
+        let (foo, symbol) = obj.symbols.by_name("foo").unwrap().unwrap();
+        assert_eq!((symbol.address, symbol.flags.is_weak()), (8, false));
+        assert_eq!(obj.sections[0].relocations.at(0).unwrap().target_symbol, foo);
+    }
+
+    #[test]
+    fn test_insert_symbol() {
+        let mut obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![test_symbol("foo", 0, 4, 4), test_symbol("bar", 0, 8, 4)],
+            vec![test_section(".text", ObjSectionKind::Code, 0, 0xC)],
+        );
+        obj.sections[0]
+            .relocations
+            .insert(4, ObjReloc {
+                kind: ObjRelocKind::PpcRel24,
+                target_symbol: 1,
+                addend: 0,
+                module: None,
+            })
+            .unwrap();
+
+        let index = obj.insert_symbol(test_symbol("baz", 0, 0, 4)).unwrap();
+        assert_eq!(index, 2);
+        let reloc = obj.sections[0].relocations.at(4).unwrap();
+        assert_eq!(obj.symbols[reloc.target_symbol].name, "bar");
+        assert_eq!(obj.sorted_symbol_order(), vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn test_to_flat_binary() {
+        let mut text = test_section(".text", ObjSectionKind::Code, 0x80003100, 8);
+        text.data = vec![0x38, 0x60, 0x00, 0x00, 0x4E, 0x80, 0x00, 0x20];
+        let mut data = test_section(".data", ObjSectionKind::Data, 0x80003110, 4);
+        data.data = vec![1, 2, 3, 4];
+        let bss = test_section(".bss", ObjSectionKind::Bss, 0x80003120, 0x20);
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "main.dol".to_string(),
+            vec![],
+            vec![text, data, bss],
+        );
+
+        let (base, image) = obj.to_flat_binary().unwrap();
+        assert_eq!(base, 0x80003100);
+        assert_eq!(image.len(), 0x14);
+        assert_eq!(&image[..8], &[0x38, 0x60, 0x00, 0x00, 0x4E, 0x80, 0x00, 0x20]);
+        assert!(image[8..0x10].iter().all(|&b| b == 0));
+        assert_eq!(&image[0x10..], &[1, 2, 3, 4]);
+
+        obj.sections[1].address = 0x80003104;
+        assert!(obj.to_flat_binary().is_err());
+    }
+
+    #[test]
+    fn test_map_relocations() {
+        let mut obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![test_symbol("foo", 0, 0, 8)],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0, 8),
+                test_section(".data", ObjSectionKind::Data, 0, 8),
+            ],
+        );
+        let reloc = |kind, addend| ObjReloc { kind, target_symbol: 0, addend, module: None };
+        obj.sections[0].relocations.insert(0, reloc(ObjRelocKind::PpcRel24, 0)).unwrap();
+        obj.sections[1].relocations.insert(0, reloc(ObjRelocKind::Absolute, 0)).unwrap();
+        obj.sections[1].relocations.insert(4, reloc(ObjRelocKind::Absolute, 8)).unwrap();
+
+        let mut visited = vec![];
+        obj.map_relocations(|section_index, address, reloc| {
+            visited.push((section_index, address));
+            reloc.addend += 4;
+        });
+        assert_eq!(visited, vec![(0, 0), (1, 0), (1, 4)]);
+        let addends = obj
+            .sections
+            .iter()
+            .flat_map(|(_, s)| s.relocations.iter().map(|(_, r)| r.addend))
+            .collect::<Vec<_>>();
+        assert_eq!(addends, vec![4, 4, 12]);
+    }
+
+    #[test]
+    fn test_gc_sections() {
+        let mut obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![
+                test_symbol("main", 0, 0, 8),
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("used", 1, 0, 4) },
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("unused", 1, 4, 4) },
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("orphan", 2, 0, 4) },
+            ],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0, 8),
+                test_section(".data", ObjSectionKind::Data, 0, 8),
+                test_section(".rodata", ObjSectionKind::ReadOnlyData, 0, 4),
+            ],
+        );
+        for (address, kind) in [(2, ObjRelocKind::PpcAddr16Ha), (6, ObjRelocKind::PpcAddr16Lo)] {
+            obj.sections[0]
+                .relocations
+                .insert(address, ObjReloc { kind, target_symbol: 1, addend: 0, module: None })
+                .unwrap();
+        }
+        obj.sections[1]
+            .relocations
+            .insert(4, ObjReloc {
+                kind: ObjRelocKind::Absolute,
+                target_symbol: 3,
+                addend: 0,
+                module: None,
+            })
+            .unwrap();
+
+        obj.start_address = Some(SectionAddress::new(0, 0));
+        obj.entry_chain = vec![
+            ("main".to_string(), SectionAddress::new(0, 0)),
+            ("orphan".to_string(), SectionAddress::new(2, 0)),
+        ];
+
+        assert!(obj.clone().gc_sections(&[4]).is_err());
+        obj.gc_sections(&[0]).unwrap();
+        let names = obj.symbols.iter().map(|(_, s)| s.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["main", "used"]);
+        let sections = obj.sections.iter().map(|(_, s)| s.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(sections, vec![".text", ".data"]);
+        assert_eq!(obj.sections[0].relocations.at(4).unwrap().target_symbol, 1);
+        assert!(obj.sections[1].relocations.is_empty());
+        assert_eq!(obj.start_address, Some(SectionAddress::new(0, 0)));
+        assert_eq!(obj.entry_chain, vec![("main".to_string(), SectionAddress::new(0, 0))]);
+    }
+
+    #[test]
+    fn test_gc_sections_unsized() {
+        let mut obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![
+                test_symbol("main", 0, 0, 4),
+                ObjSymbol { size_known: false, ..test_symbol("label", 0, 4, 0) },
+                test_symbol("callee", 0, 0xC, 4),
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("table", 1, 0, 0) },
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("unused", 1, 4, 4) },
+            ],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0, 0x10),
+                test_section(".data", ObjSectionKind::Data, 0, 0x10),
+            ],
+        );
+        let reloc = |kind, target_symbol| ObjReloc { kind, target_symbol, addend: 0, module: None };
+        // main -> label, label -> table, table -> callee
+        obj.sections[0].relocations.insert(0, reloc(ObjRelocKind::PpcRel24, 1)).unwrap();
+        obj.sections[0].relocations.insert(8, reloc(ObjRelocKind::PpcAddr16Lo, 3)).unwrap();
+        obj.sections[1].relocations.insert(0, reloc(ObjRelocKind::Absolute, 2)).unwrap();
+        obj.sections[1].relocations.insert(8, reloc(ObjRelocKind::Absolute, 0)).unwrap();
+        // callee -> another module's symbol 4, which isn't ours
+        obj.sections[0]
+            .relocations
+            .insert(0xC, ObjReloc { module: Some(1), ..reloc(ObjRelocKind::PpcRel24, 4) })
+            .unwrap();
+
+        obj.gc_sections(&[0]).unwrap();
+        let names = obj.symbols.iter().map(|(_, s)| s.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["main", "label", "callee", "table"]);
+        // label extends to callee, table to unused
+        assert_eq!(obj.sections[0].relocations.at(8).unwrap().target_symbol, 3);
+        assert_eq!(obj.sections[1].relocations.at(0).unwrap().target_symbol, 2);
+        assert!(obj.sections[1].relocations.at(8).is_none());
+        assert_eq!(obj.sections[0].relocations.at(0xC).unwrap().target_symbol, 4);
+    }
+
+    #[test]
+    fn test_set_section_address() {
+        let mut text = test_section(".text", ObjSectionKind::Code, 0x80003100, 8);
+        text.data = vec![0x48, 0x00, 0x00, 0x05, 0x4E, 0x80, 0x00, 0x20]; // bl bar; blr
+        text.relocations
+            .insert(0x80003100, ObjReloc {
+                kind: ObjRelocKind::PpcRel24,
+                target_symbol: 1,
+                addend: 0,
+                module: None,
+            })
+            .unwrap();
+        text.splits.push(0x80003100, ObjSplit {
+            unit: "a.c".to_string(),
+            end: 0x80003108,
+            align: None,
+            common: false,
+            autogenerated: false,
+            skip: false,
+            rename: None,
+        });
+        // .4byte foo
+        let mut data = test_section_words(".data", ObjSectionKind::Data, 0x80004000, &[0x80003100]);
+        data.relocations
+            .insert(0x80004000, ObjReloc {
+                kind: ObjRelocKind::Absolute,
+                target_symbol: 0,
+                addend: 0,
+                module: None,
+            })
+            .unwrap();
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![test_symbol("foo", 0, 0x80003100, 4), test_symbol("bar", 0, 0x80003104, 4)],
+            vec![text, data],
+        );
+        obj.entry = Some(0x80003100);
+        obj.entry_chain.push(("__start".to_string(), SectionAddress::new(0, 0x80003100)));
+        obj.set_section_address(0, 0x80005000).unwrap();
+
+        let section = &obj.sections[0];
+        assert_eq!(section.address, 0x80005000);
+        assert_eq!(section.splits.for_address(0x80005004).unwrap().1.end, 0x80005008);
+        let reloc = section.relocations.at(0x80005000).unwrap();
+        let (bar_index, bar) = obj
+            .symbols
+            .kind_at_section_address(0, 0x80005004, ObjSymbolKind::Function)
+            .unwrap()
+            .unwrap();
+        assert_eq!(bar.name, "bar");
+        assert_eq!(reloc.target_symbol, bar_index);
+        let ins = crate::analysis::disassemble(&obj, section, 0x80005000).unwrap();
+        assert_eq!(ins.branch_dest(0x80005000), Some(bar.address as u32));
+        assert_eq!(obj.entry, Some(0x80005000));
+        assert_eq!(obj.entry_chain[0].1, SectionAddress::new(0, 0x80005000));
+        // Absolute references from other sections are updated
+        assert_eq!(obj.sections[1].data, 0x80005000u32.to_be_bytes());
+        assert!(obj.set_section_address(2, 0).is_err());
+
+        // A relocation that fails to resolve leaves the object unchanged
+        obj.sections[1].relocations.at_mut(0x80004000).unwrap().kind = ObjRelocKind::PpcEmbSdai16;
+        assert!(obj.set_section_address(0, 0x80006000).is_err());
+        assert_eq!(obj.sections[0].address, 0x80005000);
+        assert_eq!(obj.symbols[0].address, 0x80005000);
+        assert!(obj.sections[0].relocations.at(0x80005000).is_some());
+        assert_eq!(obj.entry, Some(0x80005000));
+    }
+
+    #[test]
+    fn test_remove_section() {
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![
+                test_symbol("fn_80003100", 0, 0x80003100, 0x10),
+                test_symbol("lbl_80004000", 1, 0x80004000, 0x10),
+                test_symbol("lbl_80005000", 2, 0x80005000, 0x10),
+            ],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0x80003100, 0x100),
+                test_section(".data", ObjSectionKind::Data, 0x80004000, 0x100),
+                test_section(".bss", ObjSectionKind::Bss, 0x80005000, 0x100),
+            ],
+        );
+        for (index, section) in obj.sections.iter_mut() {
+            section.elf_index = index + 1;
+        }
+        let rel_reloc = |section: u8, target_section: u8| RelReloc {
+            kind: ObjRelocKind::Absolute,
+            section,
+            address: 0,
+            module_id: 0,
+            target_section,
+            addend: 0,
+            original_section: section,
+            original_target_section: target_section,
+        };
+        // .text -> .data, .data -> .bss and .text -> .bss
+        obj.unresolved_relocations = vec![rel_reloc(1, 2), rel_reloc(2, 3), rel_reloc(1, 3)];
+        obj.entry_chain = vec![
+            ("__start".to_string(), SectionAddress::new(0, 0x80003100)),
+            ("data".to_string(), SectionAddress::new(1, 0x80004000)),
+            ("bss".to_string(), SectionAddress::new(2, 0x80005000)),
+        ];
+        assert!(obj.clone().remove_section(1, false).is_err());
+        obj.remove_section(1, true).unwrap();
+        assert_eq!(obj.sections.len(), 2);
+        assert_eq!(obj.sections[1].name, ".bss");
+        let (_, symbol) = obj.symbols.by_name("lbl_80005000").unwrap().unwrap();
+        assert_eq!(symbol.section, Some(1));
+        assert_eq!(obj.symbols.for_section(1).count(), 1);
+        let (_, symbol) = obj.symbols.by_name("lbl_80004000").unwrap().unwrap();
+        assert_eq!(symbol.section, None);
+        // Relocations from or into the removed section are dropped
+        let remaining = obj
+            .unresolved_relocations
+            .iter()
+            .map(|reloc| (reloc.section, reloc.target_section))
+            .collect::<Vec<_>>();
+        assert_eq!(remaining, vec![(1, 3)]);
+        let (_, bss) = obj.sections.get_elf_index(3).unwrap();
+        assert_eq!(bss.name, ".bss");
+        assert_eq!(obj.entry_chain, vec![
+            ("__start".to_string(), SectionAddress::new(0, 0x80003100)),
+            ("bss".to_string(), SectionAddress::new(1, 0x80005000)),
+        ]);
+    }
+
+    #[test]
+    fn test_apply_relocations() {
+        let mut text = test_section(".text", ObjSectionKind::Code, 0x80003100, 0x10);
+        // bl 0x0; blr
+        text.data[..8].copy_from_slice(&[0x48, 0x00, 0x00, 0x01, 0x4E, 0x80, 0x00, 0x20]);
+        text.relocations
+            .insert(0x80003100, ObjReloc {
+                kind: ObjRelocKind::PpcRel24,
+                target_symbol: 1,
+                addend: 0,
+                module: None,
+            })
+            .unwrap();
+        // Relocations against other modules index their symbol tables, not ours
+        text.relocations
+            .insert(0x80003108, ObjReloc {
+                kind: ObjRelocKind::Absolute,
+                target_symbol: 5,
+                addend: 0,
+                module: Some(1),
+            })
+            .unwrap();
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![
+                test_symbol("fn_80003100", 0, 0x80003100, 0x8),
+                test_symbol("fn_80003108", 0, 0x80003108, 0x8),
+            ],
+            vec![text],
+        );
+        let mut little = obj.clone();
+        obj.apply_relocations().unwrap();
+        assert_eq!(&obj.sections[0].data[..4], &[0x48, 0x00, 0x00, 0x09]);
+        assert_eq!(&obj.sections[0].data[8..12], &[0; 4]);
+        assert!(obj.sections[0].relocations.is_empty());
+
+        // Instruction words are read and written in the object's byte order
+        little.endian = Endian::Little;
+        little.sections[0].data[..4].copy_from_slice(&[0x01, 0x00, 0x00, 0x48]);
+        little.apply_relocations().unwrap();
+        assert_eq!(&little.sections[0].data[..4], &[0x09, 0x00, 0x00, 0x48]);
+    }
+
+    #[test]
+    fn test_relocations_by_module() {
+        let mut text = test_section(".text", ObjSectionKind::Code, 0, 0x10);
+        for (address, module) in [(0, Some(1)), (4, Some(2)), (8, None), (12, Some(1))] {
+            text.relocations
+                .insert(address, ObjReloc {
+                    kind: ObjRelocKind::PpcRel24,
+                    target_symbol: 0,
+                    addend: 0,
+                    module,
+                })
+                .unwrap();
+        }
+        let obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![test_symbol("foo", 0, 0, 0x10)],
+            vec![text],
+        );
+        let by_module = obj.relocations_by_module();
+        assert_eq!(by_module.len(), 2);
+        let addresses =
+            |module: u32| by_module[&module].iter().map(|&(s, a, _)| (s, a)).collect::<Vec<_>>();
+        assert_eq!(addresses(1), vec![(0, 0), (0, 12)]);
+        assert_eq!(addresses(2), vec![(0, 4)]);
+    }
+
+    #[test]
+    fn test_undefined_symbols() {
+        let obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![
+                test_symbol("foo", 0, 0, 4),
+                ObjSymbol { name: "ext".to_string(), section: None, ..test_symbol("", 0, 0, 0) },
+                test_symbol("bar", 0, 4, 4),
+            ],
+            vec![test_section(".text", ObjSectionKind::Code, 0, 8)],
+        );
+        let undefined =
+            obj.undefined_symbols().map(|(i, s)| (i, s.name.as_str())).collect::<Vec<_>>();
+        assert_eq!(undefined, vec![(1, "ext")]);
+    }
+
+    #[test]
+    fn test_stats() {
+        let obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![
+                test_symbol("foo", 0, 0, 4),
+                ObjSymbol {
+                    flags: ObjSymbolFlagSet(ObjSymbolFlags::Local.into()),
+                    ..test_symbol("bar", 0, 4, 4)
+                },
+                ObjSymbol {
+                    kind: ObjSymbolKind::Object,
+                    flags: ObjSymbolFlagSet(ObjSymbolFlags::Weak.into()),
+                    ..test_symbol("baz", 1, 0, 8)
+                },
+                ObjSymbol { name: "ext".to_string(), section: None, ..test_symbol("", 0, 0, 0) },
+                ObjSymbol { kind: ObjSymbolKind::Section, ..test_symbol(".text", 0, 0, 0) },
+            ],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0, 8),
+                test_section(".data", ObjSectionKind::Data, 0, 8),
+                test_section(".rodata", ObjSectionKind::ReadOnlyData, 0, 4),
+                test_section(".bss", ObjSectionKind::Bss, 0, 0x20),
+            ],
+        );
+        assert_eq!(obj.stats(), ObjStats {
+            functions: 3,
+            objects: 1,
+            locals: 1,
+            globals: 2,
+            weak: 1,
+            defined: 3,
+            undefined: 1,
+            code_size: 8,
+            data_size: 12,
+            bss_size: 0x20,
+        });
+    }
+
+    #[test]
+    fn test_find_symbol() {
+        let obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![
+                ObjSymbol {
+                    demangled_name: Some("Foo::bar()".to_string()),
+                    ..test_symbol("bar__3FooFv", 0, 0, 4)
+                },
+                ObjSymbol {
+                    demangled_name: Some("Foo::bar() const".to_string()),
+                    ..test_symbol("bar__3FooCFv", 0, 4, 4)
+                },
+                ObjSymbol {
+                    demangled_name: Some("Foo::bar()".to_string()),
+                    ..test_symbol("bar__3FooFv", 0, 8, 4)
+                },
+            ],
+            vec![test_section(".text", ObjSectionKind::Code, 0, 0xC)],
+        );
+        let (index, symbol) = obj.find_symbol("Foo::bar() const").unwrap();
+        assert_eq!((index, symbol.name.as_str()), (1, "bar__3FooCFv"));
+        assert_eq!(obj.find_symbol("bar__3FooCFv").map(|(i, _)| i), Some(1));
+        assert_eq!(obj.find_symbols("Foo::bar()").map(|(i, _)| i).collect::<Vec<_>>(), vec![0, 2]);
+        assert!(obj.find_symbol("Foo::baz()").is_none());
+    }
+
+    #[test]
+    fn test_primary_symbol_at() {
+        let label = |name: &str, address: u64, scope: ObjSymbolFlags| ObjSymbol {
+            size: 0,
+            size_known: false,
+            flags: ObjSymbolFlagSet(scope.into()),
+            kind: ObjSymbolKind::Unknown,
+            ..test_symbol(name, 0, address, 0)
+        };
+        let mut obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![
+                label("lbl_0", 0, ObjSymbolFlags::Local),
+                label("entry", 0, ObjSymbolFlags::Global),
+                test_symbol("foo", 0, 0, 8),
+                label("lbl_4", 4, ObjSymbolFlags::Local),
+                label("inner", 4, ObjSymbolFlags::Global),
+                test_symbol("caller", 0, 8, 4),
+            ],
+            vec![test_section(".text", ObjSectionKind::Code, 0, 0xC)],
+        );
+        obj.sections[0]
+            .relocations
+            .insert(8, ObjReloc {
+                kind: ObjRelocKind::PpcRel24,
+                target_symbol: 0,
+                addend: 0,
+                module: None,
+            })
+            .unwrap();
+
+        // The reference resolves to the sized function rather than either label
+        let target = &obj.symbols[obj.sections[0].relocations.at(8).unwrap().target_symbol];
+        let (index, symbol) = obj.primary_symbol_at(0, target.address as u32).unwrap();
+        assert_eq!((index, symbol.name.as_str()), (2, "foo"));
+        // Interior labels prefer global over local
+        assert_eq!(obj.primary_symbol_at(0, 4).map(|(i, _)| i), Some(4));
+        assert!(obj.primary_symbol_at(0, 0xC).is_none());
+    }
+
+    #[test]
+    fn test_diff_symbols() {
+        let new_obj = |symbols| {
+            ObjInfo::new(
+                ObjKind::Relocatable,
+                ObjArchitecture::PowerPc,
+                "test".to_string(),
+                symbols,
+                vec![test_section(".text", ObjSectionKind::Code, 0, 0x20)],
+            )
+        };
+        let left = new_obj(vec![
+            test_symbol("foo", 0, 0, 4),
+            test_symbol("bar", 0, 4, 8),
+            test_symbol("removed", 0, 0xC, 4),
+            ObjSymbol {
+                demangled_name: Some("Foo::bar()".to_string()),
+                ..test_symbol("bar__3FooFv", 0, 0x10, 4)
+            },
+        ]);
+        let right = new_obj(vec![
+            test_symbol("Foo::bar()", 0, 0x10, 4),
+            test_symbol("added", 0, 0x14, 4),
+            test_symbol("bar", 0, 4, 0xC),
+            test_symbol("foo", 0, 0, 4),
+        ]);
+
+        let diff = left.diff_symbols(&right);
+        assert_eq!(diff.only_in_self, vec![2]);
+        assert_eq!(diff.only_in_other, vec![1]);
+        assert_eq!(diff.changed, vec![(1, 2)]);
+        assert_eq!(left.diff_symbols(&left), SymbolDiff::default());
+    }
+
+    #[test]
+    fn test_section_relative_reloc() {
+        use object::{Object, ObjectSection, ObjectSymbol, RelocationTarget};
+
+        let mut obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![],
+            vec![
+                test_section(".text", ObjSectionKind::Code, 0, 0x10),
+                test_section(".data", ObjSectionKind::Data, 0, 0x10),
+            ],
+        );
+        obj.add_section_relative_reloc(0, 0, ObjRelocKind::Absolute, 1, 8).unwrap();
+        obj.add_section_relative_reloc(0, 4, ObjRelocKind::Absolute, 1, 12).unwrap();
+        assert_eq!(obj.symbols.by_kind(ObjSymbolKind::Section).count(), 1);
+        assert!(obj.add_section_relative_reloc(0, 0x10, ObjRelocKind::Absolute, 1, 0).is_err());
+
+        let data = crate::util::elf::write_elf(&obj, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let text = file.section_by_name(".text").unwrap();
+        let data_index = file.section_by_name(".data").unwrap().index();
+        let relocations = text
+            .relocations()
+            .map(|(offset, reloc)| {
+                let RelocationTarget::Symbol(index) = reloc.target() else { unreachable!() };
+                let symbol = file.symbol_by_index(index).unwrap();
+                assert_eq!(symbol.kind(), object::SymbolKind::Section);
+                assert_eq!(symbol.section_index(), Some(data_index));
+                (offset, reloc.addend())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(relocations, vec![(0, 8), (4, 12)]);
+    }
+
+    #[test]
+    fn test_common_bss_layout() {
+        let mut bss = test_section(".bss", ObjSectionKind::Bss, 0x80005000, 0x40);
+        bss.splits.push(0x80005000, ObjSplit {
+            unit: "a.c".to_string(),
+            end: 0x80005010,
+            align: None,
+            common: false,
+            autogenerated: false,
+            skip: false,
+            rename: None,
+        });
+        bss.splits.push(0x80005010, ObjSplit {
+            unit: "a.c".to_string(),
+            end: 0x80005040,
+            align: None,
+            common: true,
+            autogenerated: false,
+            skip: false,
+            rename: None,
+        });
+        let obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![
+                test_symbol("local", 0, 0x80005000, 0x10),
+                test_symbol("c", 0, 0x80005030, 0x10),
+                test_symbol("a", 0, 0x80005010, 0x10),
+                test_symbol("b", 0, 0x80005020, 0x10),
+            ],
+            vec![bss],
+        );
+        let names = obj
+            .common_bss_layout()
+            .into_iter()
+            .map(|index| obj.symbols[index].name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_verify_relocations_resolve() {
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![test_symbol("foo", 0, 0x80003100, 8), ObjSymbol {
+                name: "missing".to_string(),
+                kind: ObjSymbolKind::Function,
+                ..Default::default()
+            }],
+            vec![test_section(".text", ObjSectionKind::Code, 0x80003100, 0x10)],
+        );
+        let reloc = |target_symbol| ObjReloc {
+            kind: ObjRelocKind::PpcRel24,
+            target_symbol,
+            addend: 0,
+            module: None,
+        };
+        obj.sections[0].relocations.insert(0x80003104, reloc(0)).unwrap();
+        obj.verify_relocations_resolve().unwrap();
+
+        obj.sections[0].relocations.insert(0x80003108, reloc(1)).unwrap();
+        let err = obj.verify_relocations_resolve().unwrap_err().to_string();
+        assert!(err.contains("0x80003108") && err.contains("missing"), "{err}");
+
+        obj.kind = ObjKind::Relocatable;
+        obj.verify_relocations_resolve().unwrap();
+    }
+
+    #[test]
+    fn test_section_hashes() {
+        let new_obj = || {
+            ObjInfo::new(
+                ObjKind::Executable,
+                ObjArchitecture::PowerPc,
+                "test".to_string(),
+                vec![],
+                vec![
+                    test_section(".text", ObjSectionKind::Code, 0x80003100, 0x100),
+                    test_section(".data", ObjSectionKind::Data, 0x80003200, 0x100),
+                    test_section(".bss", ObjSectionKind::Bss, 0x80003300, 0x100),
+                ],
+            )
+        };
+        let hashes = new_obj().section_hashes();
+        assert_eq!(hashes, new_obj().section_hashes());
+        assert_eq!(hashes.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), vec![
+            ".text", ".data", ".bss"
+        ]);
+
+        let mut obj = new_obj();
+        obj.sections[1].data[0x10] = 1;
+        obj.sections[2].size = 0x200;
+        let changed = obj.section_hashes();
+        assert_eq!(changed[0], hashes[0]);
+        assert_ne!(changed[1].1, hashes[1].1);
+        assert_ne!(changed[2].1, hashes[2].1);
+    }
+
+    #[test]
+    fn test_check() {
+        let obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![test_symbol("fn_80003100", 0, 0x80003100, 0x8)],
+            vec![test_section(".text", ObjSectionKind::Code, 0x80003100, 0x10)],
+        );
+        obj.check().unwrap();
+        let reloc = |target_symbol| ObjReloc {
+            kind: ObjRelocKind::Absolute,
+            target_symbol,
+            addend: 0,
+            module: None,
+        };
+        let check_err = |f: &dyn Fn(&mut ObjInfo)| {
+            let mut obj = obj.clone();
+            f(&mut obj);
+            obj.check().unwrap_err().to_string()
+        };
+
+        let err = check_err(&|obj: &mut ObjInfo| {
+            obj.sections[0].relocations.replace(0x80003100, reloc(1))
+        });
+        assert!(err.contains("invalid symbol index"), "{err}");
+        let err = check_err(&|obj: &mut ObjInfo| {
+            obj.symbols.flags(0).0 |= ObjSymbolFlags::Stripped;
+            obj.sections[0].relocations.replace(0x80003100, reloc(0));
+        });
+        assert!(err.contains("stripped symbol"), "{err}");
+        let err = check_err(&|obj: &mut ObjInfo| {
+            let mut symbol = obj.symbols[0].clone();
+            symbol.section = Some(1);
+            obj.symbols.add_direct(symbol).unwrap();
+        });
+        assert!(err.contains("invalid section index"), "{err}");
+        let err = check_err(&|obj: &mut ObjInfo| obj.sections[0].data.truncate(4));
+        assert!(err.contains("doesn't match size"), "{err}");
+        let err = check_err(&|obj: &mut ObjInfo| {
+            obj.sections[0].splits.push(0x80003100, ObjSplit {
+                unit: "main.c".to_string(),
+                end: 0x80003110,
+                align: None,
+                common: false,
+                autogenerated: false,
+                skip: false,
+                rename: None,
+            })
+        });
+        assert!(err.contains("unknown unit"), "{err}");
+    }
+
+    #[test]
+    fn test_rename_symbol() {
+        let mut obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test.o".to_string(),
+            vec![test_symbol("foo", 0, 0, 4), test_symbol("bar", 0, 4, 4)],
+            vec![test_section(".text", ObjSectionKind::Code, 0, 8)],
+        );
+        obj.sections[0]
+            .relocations
+            .insert(4, ObjReloc {
+                kind: ObjRelocKind::PpcRel24,
+                target_symbol: 0,
+                addend: 0,
+                module: None,
+            })
+            .unwrap();
+
+        assert!(obj.rename_symbol(0, "bar").is_err());
+        obj.rename_symbol(0, "__ct__3FooFv").unwrap();
+        assert_eq!(obj.symbols[0].demangled_name.as_deref(), Some("Foo::Foo()"));
+        assert!(obj.symbols.by_name("foo").unwrap().is_none());
+        let (index, _) = obj.symbols.by_name("__ct__3FooFv").unwrap().unwrap();
+        let reloc = obj.sections[0].relocations.at(4).unwrap();
+        assert_eq!(reloc.target_symbol, index);
+    }
 }
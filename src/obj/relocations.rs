@@ -14,12 +14,34 @@ use crate::obj::SymbolIndex;
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum ObjRelocKind {
     Absolute,
+    PpcAddr16,
     PpcAddr16Hi,
     PpcAddr16Ha,
     PpcAddr16Lo,
     PpcRel24,
+    /// 24-bit absolute branch target, as used by `ba`/`bla`
+    PpcAddr24,
     PpcRel14,
     PpcEmbSda21,
+    /// 32-bit PC-relative word, as used by `.eh_frame`
+    PpcRel32,
+    /// Thread-local storage offsets from the thread pointer (`@tprel`)
+    PpcTprel16,
+    PpcTprel16Lo,
+    PpcTprel16Hi,
+    PpcTprel16Ha,
+    /// Thread-local storage offsets within the module's TLS block (`@dtprel`)
+    PpcDtprel16,
+    PpcDtprel16Lo,
+    PpcDtprel16Hi,
+    PpcDtprel16Ha,
+    /// Negated 32-bit address (`R_PPC_EMB_NADDR32`)
+    PpcEmbNaddr32,
+    /// Negated 16-bit address (`R_PPC_EMB_NADDR16`)
+    PpcEmbNaddr16,
+    /// 16-bit offset of a linker-created pointer in `.sdata` or `.sdata2`
+    PpcEmbSdai16,
+    PpcEmbSda2i16,
 }
 
 impl Serialize for ObjRelocKind {
@@ -27,12 +49,27 @@ impl Serialize for ObjRelocKind {
     where S: serde::Serializer {
         serializer.serialize_str(match self {
             ObjRelocKind::Absolute => "abs",
+            ObjRelocKind::PpcAddr16 => "addr16",
             ObjRelocKind::PpcAddr16Hi => "hi",
             ObjRelocKind::PpcAddr16Ha => "ha",
             ObjRelocKind::PpcAddr16Lo => "l",
             ObjRelocKind::PpcRel24 => "rel24",
+            ObjRelocKind::PpcAddr24 => "addr24",
             ObjRelocKind::PpcRel14 => "rel14",
             ObjRelocKind::PpcEmbSda21 => "sda21",
+            ObjRelocKind::PpcRel32 => "rel32",
+            ObjRelocKind::PpcTprel16 => "tprel16",
+            ObjRelocKind::PpcTprel16Lo => "tprel16_lo",
+            ObjRelocKind::PpcTprel16Hi => "tprel16_hi",
+            ObjRelocKind::PpcTprel16Ha => "tprel16_ha",
+            ObjRelocKind::PpcDtprel16 => "dtprel16",
+            ObjRelocKind::PpcDtprel16Lo => "dtprel16_lo",
+            ObjRelocKind::PpcDtprel16Hi => "dtprel16_hi",
+            ObjRelocKind::PpcDtprel16Ha => "dtprel16_ha",
+            ObjRelocKind::PpcEmbNaddr32 => "naddr32",
+            ObjRelocKind::PpcEmbNaddr16 => "naddr16",
+            ObjRelocKind::PpcEmbSdai16 => "sdai16",
+            ObjRelocKind::PpcEmbSda2i16 => "sda2i16",
         })
     }
 }
@@ -42,20 +79,56 @@ impl<'de> Deserialize<'de> for ObjRelocKind {
     where D: serde::Deserializer<'de> {
         match String::deserialize(deserializer)?.as_str() {
             "Absolute" | "abs" => Ok(ObjRelocKind::Absolute),
+            "PpcAddr16" | "addr16" => Ok(ObjRelocKind::PpcAddr16),
             "PpcAddr16Hi" | "hi" => Ok(ObjRelocKind::PpcAddr16Hi),
             "PpcAddr16Ha" | "ha" => Ok(ObjRelocKind::PpcAddr16Ha),
             "PpcAddr16Lo" | "l" => Ok(ObjRelocKind::PpcAddr16Lo),
             "PpcRel24" | "rel24" => Ok(ObjRelocKind::PpcRel24),
+            "PpcAddr24" | "addr24" => Ok(ObjRelocKind::PpcAddr24),
             "PpcRel14" | "rel14" => Ok(ObjRelocKind::PpcRel14),
             "PpcEmbSda21" | "sda21" => Ok(ObjRelocKind::PpcEmbSda21),
+            "PpcRel32" | "rel32" => Ok(ObjRelocKind::PpcRel32),
+            "PpcTprel16" | "tprel16" => Ok(ObjRelocKind::PpcTprel16),
+            "PpcTprel16Lo" | "tprel16_lo" => Ok(ObjRelocKind::PpcTprel16Lo),
+            "PpcTprel16Hi" | "tprel16_hi" => Ok(ObjRelocKind::PpcTprel16Hi),
+            "PpcTprel16Ha" | "tprel16_ha" => Ok(ObjRelocKind::PpcTprel16Ha),
+            "PpcDtprel16" | "dtprel16" => Ok(ObjRelocKind::PpcDtprel16),
+            "PpcDtprel16Lo" | "dtprel16_lo" => Ok(ObjRelocKind::PpcDtprel16Lo),
+            "PpcDtprel16Hi" | "dtprel16_hi" => Ok(ObjRelocKind::PpcDtprel16Hi),
+            "PpcDtprel16Ha" | "dtprel16_ha" => Ok(ObjRelocKind::PpcDtprel16Ha),
+            "PpcEmbNaddr32" | "naddr32" => Ok(ObjRelocKind::PpcEmbNaddr32),
+            "PpcEmbNaddr16" | "naddr16" => Ok(ObjRelocKind::PpcEmbNaddr16),
+            "PpcEmbSdai16" | "sdai16" => Ok(ObjRelocKind::PpcEmbSdai16),
+            "PpcEmbSda2i16" | "sda2i16" => Ok(ObjRelocKind::PpcEmbSda2i16),
             s => Err(serde::de::Error::unknown_variant(s, &[
-                "abs", "hi", "ha", "l", "rel24", "rel14", "sda21",
+                "abs",
+                "addr16",
+                "hi",
+                "ha",
+                "l",
+                "rel24",
+                "addr24",
+                "rel14",
+                "sda21",
+                "rel32",
+                "tprel16",
+                "tprel16_lo",
+                "tprel16_hi",
+                "tprel16_ha",
+                "dtprel16",
+                "dtprel16_lo",
+                "dtprel16_hi",
+                "dtprel16_ha",
+                "naddr32",
+                "naddr16",
+                "sdai16",
+                "sda2i16",
             ])),
         }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Eq, PartialEq)]
 pub struct ObjReloc {
     pub kind: ObjRelocKind,
     // pub address: u64,
@@ -77,6 +150,11 @@ impl ObjReloc {
                     elf::R_PPC_UADDR32
                 }
             }
+            // Assumes the 16-bit field is an instruction immediate, like @h/@ha/@l
+            ObjRelocKind::PpcAddr16 => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_ADDR16
+            }
             ObjRelocKind::PpcAddr16Hi => {
                 r_offset = (r_offset & !3) + 2;
                 elf::R_PPC_ADDR16_HI
@@ -93,6 +171,10 @@ impl ObjReloc {
                 r_offset &= !3;
                 elf::R_PPC_REL24
             }
+            ObjRelocKind::PpcAddr24 => {
+                r_offset &= !3;
+                elf::R_PPC_ADDR24
+            }
             ObjRelocKind::PpcRel14 => {
                 r_offset &= !3;
                 elf::R_PPC_REL14
@@ -101,11 +183,73 @@ impl ObjReloc {
                 r_offset &= !3;
                 elf::R_PPC_EMB_SDA21
             }
+            ObjRelocKind::PpcRel32 => elf::R_PPC_REL32,
+            ObjRelocKind::PpcTprel16 => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_TPREL16
+            }
+            ObjRelocKind::PpcTprel16Lo => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_TPREL16_LO
+            }
+            ObjRelocKind::PpcTprel16Hi => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_TPREL16_HI
+            }
+            ObjRelocKind::PpcTprel16Ha => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_TPREL16_HA
+            }
+            ObjRelocKind::PpcDtprel16 => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_DTPREL16
+            }
+            ObjRelocKind::PpcDtprel16Lo => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_DTPREL16_LO
+            }
+            ObjRelocKind::PpcDtprel16Hi => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_DTPREL16_HI
+            }
+            ObjRelocKind::PpcDtprel16Ha => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_DTPREL16_HA
+            }
+            ObjRelocKind::PpcEmbNaddr32 => elf::R_PPC_EMB_NADDR32,
+            ObjRelocKind::PpcEmbNaddr16 => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_EMB_NADDR16
+            }
+            ObjRelocKind::PpcEmbSdai16 => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_EMB_SDAI16
+            }
+            ObjRelocKind::PpcEmbSda2i16 => {
+                r_offset = (r_offset & !3) + 2;
+                elf::R_PPC_EMB_SDA2I16
+            }
         };
         (r_offset, r_type)
     }
 }
 
+/// Whether `ins` is a paired-single load or store (`psq_l`, `psq_lu`, `psq_st`, `psq_stu`).
+/// These encode the W and I fields above a 12-bit displacement.
+#[inline]
+pub fn is_paired_single_load_store(ins: u32) -> bool { matches!(ins >> 26, 56 | 57 | 60 | 61) }
+
+/// Bits of `ins` patched by an `R_PPC_EMB_SDA21` relocation: the base register and the
+/// displacement.
+#[inline]
+pub fn sda21_field_mask(ins: u32) -> u32 {
+    if is_paired_single_load_store(ins) {
+        0x1F0FFF
+    } else {
+        0x1FFFFF
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct ObjRelocations {
     relocations: BTreeMap<u32, ObjReloc>,
@@ -127,25 +271,24 @@ impl Error for ExistingRelocationError {}
 
 impl ObjRelocations {
     pub fn new(relocations: Vec<(u32, ObjReloc)>) -> Result<Self, ExistingRelocationError> {
-        let mut map = BTreeMap::new();
+        let mut result = Self::default();
         for (address, reloc) in relocations {
-            let address = address & !3;
-            match map.entry(address) {
-                btree_map::Entry::Vacant(e) => e.insert(reloc),
-                btree_map::Entry::Occupied(e) => {
-                    return Err(ExistingRelocationError { address, value: e.get().clone() })
-                }
-            };
+            result.insert(address, reloc)?;
         }
-        Ok(Self { relocations: map })
+        Ok(result)
     }
 
     pub fn len(&self) -> usize { self.relocations.len() }
 
+    /// Inserts a relocation at the word containing `address`. Inserting a relocation
+    /// identical to an existing one is a no-op; any other collision is an error.
     pub fn insert(&mut self, address: u32, reloc: ObjReloc) -> Result<(), ExistingRelocationError> {
         let address = address & !3;
         match self.relocations.entry(address) {
-            btree_map::Entry::Vacant(e) => e.insert(reloc),
+            btree_map::Entry::Vacant(e) => {
+                e.insert(reloc);
+            }
+            btree_map::Entry::Occupied(e) if *e.get() == reloc => {}
             btree_map::Entry::Occupied(e) => {
                 return Err(ExistingRelocationError { address, value: e.get().clone() })
             }
@@ -182,3 +325,43 @@ impl ObjRelocations {
 
     pub fn contains(&self, address: u32) -> bool { self.relocations.contains_key(&address) }
 }
+
+#[cfg(test)]
+mod tests {
+    use object::RelocationFlags;
+
+    use super::*;
+    use crate::util::elf::to_obj_reloc_kind;
+
+    #[test]
+    fn test_addr16_round_trip() {
+        let reloc =
+            ObjReloc { kind: ObjRelocKind::PpcAddr16, target_symbol: 0, addend: 0, module: None };
+        let (r_offset, r_type) = reloc.to_elf(0x10);
+        assert_eq!(r_offset, 0x12);
+        assert_eq!(r_type, elf::R_PPC_ADDR16);
+        let kind = to_obj_reloc_kind(RelocationFlags::Elf { r_type }).unwrap();
+        assert_eq!(kind, ObjRelocKind::PpcAddr16);
+
+        let mut relocations = ObjRelocations::default();
+        relocations.insert(r_offset as u32, ObjReloc { kind, ..reloc }).unwrap();
+        assert!(relocations.contains(0x10));
+    }
+
+    #[test]
+    fn test_insert_duplicate() {
+        let reloc = |kind, addend| ObjReloc { kind, target_symbol: 1, addend, module: None };
+        let mut relocations = ObjRelocations::default();
+        relocations.insert(0x12, reloc(ObjRelocKind::PpcAddr16Ha, 0)).unwrap();
+        relocations.insert(0x16, reloc(ObjRelocKind::PpcAddr16Lo, 0)).unwrap();
+        // Identical relocations are deduplicated
+        relocations.insert(0x12, reloc(ObjRelocKind::PpcAddr16Ha, 0)).unwrap();
+        assert_eq!(relocations.len(), 2);
+
+        let err = relocations.insert(0x10, reloc(ObjRelocKind::PpcAddr16Ha, 4)).unwrap_err();
+        assert_eq!(err.address, 0x10);
+        assert_eq!(err.value, reloc(ObjRelocKind::PpcAddr16Ha, 0));
+        assert!(relocations.insert(0x14, reloc(ObjRelocKind::Absolute, 0)).is_err());
+        assert_eq!(relocations.at(0x10), Some(&reloc(ObjRelocKind::PpcAddr16Ha, 0)));
+    }
+}
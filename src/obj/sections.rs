@@ -1,4 +1,5 @@
 use std::{
+    borrow::Cow,
     cmp::min,
     collections::Bound,
     ops::{Index, IndexMut, Range, RangeBounds},
@@ -9,7 +10,8 @@ use itertools::Itertools;
 
 use crate::{
     analysis::cfa::SectionAddress,
-    obj::{ObjKind, ObjRelocations, ObjSplit, ObjSplits, ObjSymbol},
+    obj::{ObjKind, ObjReloc, ObjRelocKind, ObjRelocations, ObjSplit, ObjSplits, ObjSymbol},
+    util::reader::{Endian, FromBytes},
 };
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -32,9 +34,42 @@ pub struct ObjSection {
     pub elf_index: SectionIndex,
     pub relocations: ObjRelocations,
     pub virtual_address: Option<u64>,
-    pub file_offset: u64,
+    /// Offset of the section data in the source file, if it has any
+    pub file_offset: Option<u64>,
     pub section_known: bool,
+    /// Whether the section occupies memory at runtime (`SHF_ALLOC`)
+    pub alloc: bool,
+    /// Whether the section is loaded from a `PT_LOAD` segment. Linker script `NOLOAD`
+    /// sections are allocated but not loaded.
+    pub load: bool,
     pub splits: ObjSplits,
+    /// Index of the loadable segment (`PT_LOAD`) containing this section,
+    /// when read from an executable ELF.
+    pub segment: Option<usize>,
+}
+
+/// Small data area, addressed relative to a base register by `R_PPC_EMB_SDA21`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum SmallDataArea {
+    /// `.sdata*`/`.sbss*`, relative to `_SDA_BASE_` (r13)
+    Sda,
+    /// `.sdata2*`/`.sbss2*`, relative to `_SDA2_BASE_` (r2)
+    Sda2,
+}
+
+/// A section that isn't otherwise understood (e.g. `.line`), preserved verbatim
+/// so that it can be re-emitted when writing the object back out.
+///
+/// Raw sections are data-only: their relocation sections (e.g. `.rela.line` or
+/// `.rela.debug_info`) are dropped on read, so any references they hold are written back
+/// as the resolved bytes of the input, without relocations.
+#[derive(Debug, Clone)]
+pub struct ObjRawSection {
+    pub name: String,
+    pub sh_type: u32,
+    pub sh_flags: u64,
+    pub align: u64,
+    pub data: Vec<u8>,
 }
 
 #[derive(Debug, Clone)]
@@ -125,6 +160,11 @@ impl ObjSections {
             .map_err(|_| anyhow!("Multiple sections with name {}", name))
     }
 
+    /// Removes a section, shifting the indices of all later sections down by one.
+    pub fn remove(&mut self, index: SectionIndex) -> ObjSection {
+        self.sections.remove(index as usize)
+    }
+
     pub fn push(&mut self, section: ObjSection) -> SectionIndex {
         let index = self.sections.len();
         self.sections.push(section);
@@ -195,6 +235,32 @@ impl ObjSection {
         })
     }
 
+    /// Read `len` bytes at `offset` from the start of the section.
+    /// BSS sections (and any data past the end of `data`) read as zeros.
+    pub fn data_or_zero(&self, offset: u64, len: u64) -> Result<Cow<[u8]>> {
+        ensure!(
+            offset.checked_add(len).is_some_and(|end| end <= self.size),
+            "Range {:#X}-{:#X} outside of section {} (size {:#X})",
+            offset,
+            offset.saturating_add(len),
+            self.name,
+            self.size
+        );
+        let start = offset as usize;
+        let end = (offset + len) as usize;
+        if self.kind != ObjSectionKind::Bss && end <= self.data.len() {
+            return Ok(Cow::Borrowed(&self.data[start..end]));
+        }
+        let mut out = vec![0u8; len as usize];
+        if start < self.data.len() {
+            let available = &self.data[start..min(end, self.data.len())];
+            if self.kind != ObjSectionKind::Bss {
+                out[..available.len()].copy_from_slice(available);
+            }
+        }
+        Ok(Cow::Owned(out))
+    }
+
     #[inline]
     pub fn symbol_data(&self, symbol: &ObjSymbol) -> Result<&[u8]> {
         if symbol.size == 0 {
@@ -203,6 +269,43 @@ impl ObjSection {
         self.data_range(symbol.address as u32, symbol.address as u32 + symbol.size as u32)
     }
 
+    /// Iterates the address and instruction word of each instruction in the section,
+    /// read in the object's byte order `e`. Trailing bytes that don't form a complete
+    /// instruction are ignored.
+    pub fn iter_instructions(&self, e: Endian) -> impl Iterator<Item = (u32, u32)> + '_ {
+        self.data.chunks_exact(4).enumerate().map(move |(i, chunk)| {
+            let ins = u32::from_bytes(chunk.try_into().unwrap(), e);
+            (self.address as u32 + i as u32 * 4, ins)
+        })
+    }
+
+    /// The relocation applied to the instruction or data word at `address`. Relocations are
+    /// stored by word, so a halfword `address + 2` only finds relocations of a 16-bit
+    /// immediate.
+    pub fn relocation_at(&self, address: u32) -> Option<&ObjReloc> {
+        self.relocations.at(address & !3).filter(|reloc| {
+            address & 3 != 2
+                || matches!(
+                    reloc.kind,
+                    ObjRelocKind::PpcAddr16
+                        | ObjRelocKind::PpcAddr16Hi
+                        | ObjRelocKind::PpcAddr16Ha
+                        | ObjRelocKind::PpcAddr16Lo
+                        | ObjRelocKind::PpcTprel16
+                        | ObjRelocKind::PpcTprel16Lo
+                        | ObjRelocKind::PpcTprel16Hi
+                        | ObjRelocKind::PpcTprel16Ha
+                        | ObjRelocKind::PpcDtprel16
+                        | ObjRelocKind::PpcDtprel16Lo
+                        | ObjRelocKind::PpcDtprel16Hi
+                        | ObjRelocKind::PpcDtprel16Ha
+                        | ObjRelocKind::PpcEmbNaddr16
+                        | ObjRelocKind::PpcEmbSdai16
+                        | ObjRelocKind::PpcEmbSda2i16
+                )
+        })
+    }
+
     #[inline]
     pub fn contains(&self, addr: u32) -> bool {
         (self.address..self.address + self.size).contains(&(addr as u64))
@@ -226,6 +329,24 @@ impl ObjSection {
         start_in_range && end_in_range
     }
 
+    /// The small data area this section belongs to, determined by its name.
+    pub fn small_data_area(&self) -> Option<SmallDataArea> {
+        let name = self.name.as_str();
+        if name.starts_with(".sdata2") || name.starts_with(".sbss2") {
+            Some(SmallDataArea::Sda2)
+        } else if name.starts_with(".sdata") || name.starts_with(".sbss") {
+            Some(SmallDataArea::Sda)
+        } else {
+            None
+        }
+    }
+
+    /// The section name without any overlay suffix, e.g. `.text` for `.text.a`.
+    pub fn base_name(&self) -> &str { split_section_suffix(&self.name).0 }
+
+    /// The overlay suffix of the section name, e.g. `a` for `.text.a`.
+    pub fn suffix(&self) -> Option<&str> { split_section_suffix(&self.name).1 }
+
     pub fn rename(&mut self, name: String) -> Result<()> {
         self.kind = section_kind_for_section(&name)?;
         self.name = name;
@@ -234,14 +355,124 @@ impl ObjSection {
     }
 }
 
+/// Pairs of indices into `sections` whose address ranges intersect, as can happen with
+/// overlays. Empty sections are ignored.
+pub fn overlapping_sections<'a, I>(sections: I) -> Vec<(SectionIndex, SectionIndex)>
+where I: IntoIterator<Item = &'a ObjSection> {
+    let sections = sections.into_iter().collect::<Vec<_>>();
+    let mut result = vec![];
+    for (a_index, a) in sections.iter().enumerate() {
+        for (b_index, b) in sections.iter().enumerate().skip(a_index + 1) {
+            if a.size > 0
+                && b.size > 0
+                && a.address < b.address + b.size
+                && b.address < a.address + a.size
+            {
+                result.push((a_index as SectionIndex, b_index as SectionIndex));
+            }
+        }
+    }
+    result
+}
+
+/// Splits an overlay suffix from a section name, e.g. `.text.a` into `.text` and `a`.
+/// Only single lowercase letter suffixes on a known base section are split, so that GCC
+/// `-ffunction-sections` names such as `.text.foo` aren't taken for overlays.
+pub fn split_section_suffix(name: &str) -> (&str, Option<&str>) {
+    let start = name.starts_with('.') as usize;
+    if let Some(pos) = name[start..].find('.') {
+        let (base, suffix) = (&name[..start + pos], &name[start + pos + 1..]);
+        let is_overlay = matches!(suffix.as_bytes(), [c] if c.is_ascii_lowercase());
+        if is_overlay && section_kind_for_section(base).is_ok() {
+            return (base, Some(suffix));
+        }
+    }
+    (name, None)
+}
+
 pub fn section_kind_for_section(section_name: &str) -> Result<ObjSectionKind> {
-    Ok(match section_name {
+    Ok(match split_section_suffix(section_name).0 {
         ".init" | ".text" | ".dbgtext" | ".vmtext" => ObjSectionKind::Code,
         ".ctors" | ".dtors" | ".rodata" | ".sdata2" | "extab" | "extabindex" | ".BINARY" => {
             ObjSectionKind::ReadOnlyData
         }
         ".bss" | ".sbss" | ".sbss2" => ObjSectionKind::Bss,
         ".data" | ".sdata" => ObjSectionKind::Data,
-        name => bail!("Unknown section {name}"),
+        _ => bail!("Unknown section {section_name}"),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        analysis::read_u32,
+        obj::{
+            testing::{test_section, test_section_data},
+            ObjArchitecture, ObjInfo,
+        },
+    };
+
+    #[test]
+    fn test_section_endian() {
+        let section =
+            test_section_data(".test", ObjSectionKind::Data, 0x80000000, vec![1, 2, 3, 4]);
+        let mut obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "test".to_string(),
+            vec![],
+            vec![section.clone()],
+        );
+        assert_eq!(read_u32(&obj, &section, 0x80000000), Some(0x01020304));
+        obj.endian = Endian::Little;
+        assert_eq!(read_u32(&obj, &section, 0x80000000), Some(0x04030201));
+    }
+
+    #[test]
+    fn test_iter_instructions() {
+        let data = vec![0x60, 0x00, 0x00, 0x00, 0x4E, 0x80, 0x00, 0x20, 0xFF];
+        let section = test_section_data(".test", ObjSectionKind::Code, 0x80000000, data);
+        assert_eq!(section.iter_instructions(Endian::Big).collect::<Vec<_>>(), vec![
+            (0x80000000, 0x60000000),
+            (0x80000004, 0x4E800020)
+        ]);
+        assert_eq!(
+            section.iter_instructions(Endian::Little).map(|(_, ins)| ins).collect::<Vec<_>>(),
+            vec![0x00000060, 0x2000804E]
+        );
+    }
+
+    #[test]
+    fn test_relocation_at() {
+        let mut section = test_section(".test", ObjSectionKind::Code, 0x80000000, 12);
+        let reloc = |kind| ObjReloc { kind, target_symbol: 0, addend: 0, module: None };
+        // addi r3, r3, sym@l
+        section.relocations.insert(0x80000006, reloc(ObjRelocKind::PpcAddr16Lo)).unwrap();
+        section.relocations.insert(0x80000008, reloc(ObjRelocKind::PpcRel24)).unwrap();
+
+        assert_eq!(section.relocation_at(0x80000004).unwrap().kind, ObjRelocKind::PpcAddr16Lo);
+        assert_eq!(section.relocation_at(0x80000006).unwrap().kind, ObjRelocKind::PpcAddr16Lo);
+        assert_eq!(section.relocation_at(0x80000008).unwrap().kind, ObjRelocKind::PpcRel24);
+        assert!(section.relocation_at(0x80000000).is_none());
+        // Only 16-bit relocations are found at the halfword
+        assert!(section.relocation_at(0x8000000A).is_none());
+        section.relocations.insert(0x80000002, reloc(ObjRelocKind::Absolute)).unwrap();
+        assert_eq!(section.relocation_at(0x80000000).unwrap().kind, ObjRelocKind::Absolute);
+        assert!(section.relocation_at(0x80000002).is_none());
+    }
+
+    #[test]
+    fn test_data_or_zero() {
+        let data = test_section_data(".test", ObjSectionKind::Data, 0x80000000, vec![
+            1, 2, 3, 4, 5, 6, 7, 8,
+        ]);
+        assert_eq!(data.data_or_zero(2, 4).unwrap().as_ref(), &[3, 4, 5, 6]);
+        assert!(matches!(data.data_or_zero(0, 8).unwrap(), Cow::Borrowed(_)));
+        assert!(data.data_or_zero(6, 4).is_err());
+
+        let bss = test_section(".test", ObjSectionKind::Bss, 0x80000000, 0x10);
+        assert_eq!(bss.data_or_zero(4, 8).unwrap().as_ref(), &[0u8; 8]);
+        assert!(bss.data_or_zero(0x10, 1).is_err());
+    }
+}
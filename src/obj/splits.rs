@@ -1,6 +1,10 @@
-use std::{cmp::max, collections::BTreeMap, ops::RangeBounds};
+use std::{
+    cmp::max,
+    collections::BTreeMap,
+    ops::{Range, RangeBounds},
+};
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, ensure, Result};
 use itertools::Itertools;
 
 use crate::{
@@ -21,10 +25,17 @@ pub struct ObjSplit {
     /// Skip when emitting the split object.
     pub skip: bool,
     /// Override the section name in the split object. (e.g. `.ctors$10`)
+    /// Used as-is, so renaming a `.text.a` split to `.text` drops its overlay suffix.
     pub rename: Option<String>,
 }
 
 impl ObjSplit {
+    /// The address range covered by this split, given its start address.
+    /// An `end` of 0 means the split extends to the end of the section.
+    pub fn range(&self, start: u32) -> Range<u32> {
+        start..if self.end == 0 { u32::MAX } else { self.end }
+    }
+
     pub fn alignment(
         &self,
         obj: &ObjInfo,
@@ -37,7 +48,7 @@ impl ObjSplit {
             max(
                 // Maximum alignment of any symbol in this split
                 obj.symbols
-                    .for_section_range(section_index, split_addr..self.end)
+                    .for_section_range(section_index, self.range(split_addr))
                     .filter(|&(_, s)| s.size_known && s.size > 0)
                     .filter_map(|(_, s)| s.align)
                     .max()
@@ -68,14 +79,17 @@ impl ObjSplits {
     /// Locate an existing split for the given address.
     pub fn for_address(&self, address: u32) -> Option<(u32, &ObjSplit)> {
         match self.for_range(..=address).next_back() {
-            Some((addr, split)) if split.end == 0 || split.end > address => Some((addr, split)),
+            Some((addr, split)) if split.range(addr).contains(&address) => Some((addr, split)),
             _ => None,
         }
     }
 
+    /// Whether any split covers the given address.
+    pub fn contains(&self, address: u32) -> bool { self.for_address(address).is_some() }
+
     pub fn at_mut(&mut self, address: u32) -> Option<&mut ObjSplit> {
         match self.for_range_mut(..=address).next_back() {
-            Some((_, split)) if split.end == 0 || split.end > address => Some(split),
+            Some((addr, split)) if split.range(addr).contains(&address) => Some(split),
             _ => None,
         }
     }
@@ -106,9 +120,125 @@ impl ObjSplits {
             .map_err(|_| anyhow!("Multiple splits for unit {}", unit))
     }
 
+    /// Locate all splits for the given unit, in address order.
+    pub fn all_for_unit<'a>(
+        &'a self,
+        unit: &'a str,
+    ) -> impl DoubleEndedIterator<Item = (u32, &'a ObjSplit)> + 'a {
+        self.iter().filter(move |(_, split)| split.unit == unit)
+    }
+
+    /// Bisects the split covering `address`, assigning the range from `address` to the
+    /// original end to `new_unit`. Alignment and common flags are carried over.
+    pub fn split_at(&mut self, address: u32, new_unit: String) -> Result<()> {
+        ensure!(!self.has_split_at(address), "Split already exists at {:#010X}", address);
+        let split = self
+            .at_mut(address)
+            .ok_or_else(|| anyhow!("No split covers address {:#010X}", address))?;
+        let new_split = ObjSplit {
+            unit: new_unit,
+            end: split.end,
+            align: split.align,
+            common: split.common,
+            autogenerated: false,
+            skip: false,
+            rename: None,
+        };
+        split.end = address;
+        self.push(address, new_split);
+        Ok(())
+    }
+
     pub fn push(&mut self, address: u32, split: ObjSplit) {
         self.splits.nested_push(address, split);
     }
 
+    /// Keeps only the splits for which `f` returns true, removing addresses left without
+    /// any splits.
+    pub fn retain<F>(&mut self, mut f: F)
+    where F: FnMut(u32, &ObjSplit) -> bool {
+        self.splits.retain(|&addr, splits| {
+            splits.retain(|split| f(addr, split));
+            !splits.is_empty()
+        });
+    }
+
     pub fn remove(&mut self, address: u32) -> Option<Vec<ObjSplit>> { self.splits.remove(&address) }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_split(unit: &str, end: u32) -> ObjSplit {
+        ObjSplit {
+            unit: unit.to_string(),
+            end,
+            align: None,
+            common: false,
+            autogenerated: false,
+            skip: false,
+            rename: None,
+        }
+    }
+
+    #[test]
+    fn test_all_for_unit() {
+        let mut splits = ObjSplits::default();
+        splits.push(0x80003100, test_split("a.c", 0x80003200));
+        splits.push(0x80003200, test_split("b.c", 0x80003300));
+        splits.push(0x80003300, test_split("a.c", 0x80003400));
+        assert!(splits.for_unit("a.c").is_err());
+        let addrs = splits.all_for_unit("a.c").map(|(addr, _)| addr).collect::<Vec<_>>();
+        assert_eq!(addrs, vec![0x80003100, 0x80003300]);
+        assert_eq!(splits.all_for_unit("b.c").count(), 1);
+    }
+
+    #[test]
+    fn test_split_range() {
+        let mut splits = ObjSplits::default();
+        splits.push(0x80003100, test_split("a.c", 0x80003200));
+        splits.push(0x80003200, test_split("b.c", 0));
+        let (addr, split) = splits.for_address(0x80003100).unwrap();
+        assert_eq!(split.range(addr), 0x80003100..0x80003200);
+        let (addr, split) = splits.for_address(0x80003200).unwrap();
+        assert_eq!(split.range(addr), 0x80003200..u32::MAX);
+        assert!(!splits.contains(0x800030FC));
+        assert!(splits.contains(0x800031FC));
+        assert!(splits.contains(0x80003200));
+        assert!(splits.contains(0x80009000));
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut splits = ObjSplits::default();
+        splits.push(0x80003100, test_split("a.c", 0x80003200));
+        let mut auto = test_split("auto_80003200.c", 0x80003300);
+        auto.autogenerated = true;
+        splits.push(0x80003200, auto.clone());
+        splits.push(0x80003300, auto);
+        splits.push(0x80003300, test_split("b.c", 0x80003400));
+        splits.retain(|_, split| !split.autogenerated);
+        let units = splits.iter().map(|(addr, s)| (addr, s.unit.as_str())).collect::<Vec<_>>();
+        assert_eq!(units, vec![(0x80003100, "a.c"), (0x80003300, "b.c")]);
+        assert!(!splits.has_split_at(0x80003200));
+    }
+
+    #[test]
+    fn test_split_at() {
+        let mut splits = ObjSplits::default();
+        let mut split = test_split("a.c", 0x80003200);
+        split.align = Some(32);
+        splits.push(0x80003100, split);
+        splits.split_at(0x80003180, "b.c".to_string()).unwrap();
+        let (addr, split) = splits.for_address(0x80003100).unwrap();
+        assert_eq!((addr, split.unit.as_str(), split.end), (0x80003100, "a.c", 0x80003180));
+        let (addr, split) = splits.for_address(0x80003180).unwrap();
+        assert_eq!((addr, split.unit.as_str(), split.end), (0x80003180, "b.c", 0x80003200));
+        assert_eq!(split.align, Some(32));
+        // Existing boundary
+        assert!(splits.split_at(0x80003180, "c.c".to_string()).is_err());
+        // Not covered by any split
+        assert!(splits.split_at(0x80003200, "c.c".to_string()).is_err());
+    }
+}
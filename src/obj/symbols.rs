@@ -5,6 +5,7 @@ use std::{
 };
 
 use anyhow::{anyhow, bail, ensure, Result};
+use cwdemangle::demangle;
 use flagset::{flags, FlagSet};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
@@ -51,6 +52,8 @@ flags! {
         NoExport,
         /// Symbol does not contain any relocations
         NoReloc,
+        /// Thread-local storage symbol (`STT_TLS`)
+        Tls,
     }
 }
 
@@ -104,6 +107,9 @@ impl ObjSymbolFlagSet {
     #[inline]
     pub fn is_no_reloc(&self) -> bool { self.0.contains(ObjSymbolFlags::NoReloc) }
 
+    #[inline]
+    pub fn is_tls(&self) -> bool { self.0.contains(ObjSymbolFlags::Tls) }
+
     #[inline]
     pub fn set_scope(&mut self, scope: ObjSymbolScope) {
         match scope {
@@ -143,7 +149,8 @@ impl ObjSymbolFlagSet {
                 | ObjSymbolFlags::RelocationIgnore
                 | ObjSymbolFlags::Stripped
                 | ObjSymbolFlags::NoExport
-                | ObjSymbolFlags::NoReloc)
+                | ObjSymbolFlags::NoReloc
+                | ObjSymbolFlags::Tls)
     }
 }
 
@@ -162,6 +169,8 @@ pub enum ObjSymbolKind {
     Function,
     Object,
     Section,
+    /// Source file name (`STT_FILE`), only kept by raw ELF reads.
+    File,
 }
 
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
@@ -194,6 +203,8 @@ pub struct ObjSymbol {
     pub kind: ObjSymbolKind,
     pub align: Option<u32>,
     pub data_kind: ObjDataKind,
+    /// ELF `st_other` bits other than the visibility, which is kept in `flags`
+    pub st_other: u8,
     /// ALF hashes
     pub name_hash: Option<u32>,
     pub demangled_name_hash: Option<u32>,
@@ -305,6 +316,11 @@ impl ObjSymbols {
                     ObjDataKind::Unknown => existing.data_kind,
                     kind => kind,
                 },
+                st_other: if in_symbol.st_other == 0 {
+                    existing.st_other
+                } else {
+                    in_symbol.st_other
+                },
                 name_hash: in_symbol.name_hash.or(existing.name_hash),
                 demangled_name_hash: in_symbol.demangled_name_hash.or(existing.demangled_name_hash),
             };
@@ -326,6 +342,7 @@ impl ObjSymbols {
                 kind: in_symbol.kind,
                 align: in_symbol.align,
                 data_kind: in_symbol.data_kind,
+                st_other: in_symbol.st_other,
                 name_hash: in_symbol.name_hash,
                 demangled_name_hash: in_symbol.demangled_name_hash,
             })?;
@@ -379,6 +396,16 @@ impl ObjSymbols {
             .filter(|(_, sym)| !sym.flags.is_stripped())
     }
 
+    /// All symbol indices defined at the given section address, including stripped
+    /// symbols. Assembly may define several global labels at the same address.
+    pub fn aliases_at(&self, section_idx: SectionIndex, addr: u32) -> &[SymbolIndex] {
+        self.symbols_by_section
+            .get(section_idx as usize)
+            .and_then(|v| v.get(&addr))
+            .map(|v| v.as_slice())
+            .unwrap_or_default()
+    }
+
     pub fn kind_at_section_address(
         &self,
         section_idx: SectionIndex,
@@ -586,6 +613,28 @@ impl Index<SymbolIndex> for ObjSymbols {
 }
 
 impl ObjSymbol {
+    /// Sets the display name, leaving the mangled `name` untouched. Warns if it doesn't match
+    /// the demangled `name`, as the symbol table would no longer agree with it.
+    pub fn set_demangled_name(&mut self, demangled: &str) {
+        match demangle(&self.name, &Default::default()) {
+            Some(expected) if expected != demangled => log::warn!(
+                "Demangled name {} for {} doesn't match {}",
+                demangled,
+                self.name,
+                expected
+            ),
+            None => log::warn!("Setting demangled name {} for unmangled {}", demangled, self.name),
+            _ => {}
+        }
+        self.demangled_name = Some(demangled.to_string());
+    }
+
+    /// Whether this symbol is defined outside of the object: it has no section, isn't
+    /// common and isn't absolute.
+    pub fn is_undefined(&self) -> bool {
+        self.section.is_none() && !self.flags.is_common() && self.address == 0
+    }
+
     /// Whether this symbol can be referenced by the given relocation kind.
     pub fn referenced_by(&self, reloc_kind: ObjRelocKind) -> bool {
         if self.flags.is_relocation_ignore() || self.flags.is_stripped() {
@@ -596,7 +645,10 @@ impl ObjSymbol {
             // Linker generated labels will only be referenced by @ha/@h/@l relocations
             return matches!(
                 reloc_kind,
-                ObjRelocKind::PpcAddr16Ha | ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Lo
+                ObjRelocKind::PpcAddr16
+                    | ObjRelocKind::PpcAddr16Ha
+                    | ObjRelocKind::PpcAddr16Hi
+                    | ObjRelocKind::PpcAddr16Lo
             );
         }
 
@@ -607,6 +659,7 @@ impl ObjSymbol {
                 // !matches!(reloc_kind, ObjRelocKind::PpcRel14 | ObjRelocKind::PpcRel24)
                 true // SADX has bugged relocations that jump from .text to .bss, how awful
             }
+            ObjSymbolKind::File => false,
             ObjSymbolKind::Section => {
                 matches!(
                     reloc_kind,
@@ -619,6 +672,27 @@ impl ObjSymbol {
     }
 }
 
+/// Canonical ranking of symbols at the same address, lowest first: sized over unsized,
+/// function or object over label over section symbol, global over local, then the lowest
+/// symbol index. Used by [`ObjInfo::primary_symbol_at`](super::ObjInfo::primary_symbol_at),
+/// and by [`best_match_for_reloc`] to break ties.
+pub fn canonical_symbol_key(
+    index: SymbolIndex,
+    symbol: &ObjSymbol,
+) -> (bool, u8, bool, SymbolIndex) {
+    let kind_rank = match symbol.kind {
+        ObjSymbolKind::Function | ObjSymbolKind::Object => 0,
+        ObjSymbolKind::Unknown => 1,
+        ObjSymbolKind::Section | ObjSymbolKind::File => 2,
+    };
+    (symbol.size == 0, kind_rank, symbol.flags.is_local(), index)
+}
+
+/// Chooses the symbol a relocation of `reloc_kind` should target. This departs from
+/// [`canonical_symbol_key`] where compilers do: `@ha`/`@l` pairs typically address labels
+/// (e.g. jump tables inside a function) rather than the enclosing symbol, and the
+/// register save/restore routines are only reached through their interior labels.
+/// Remaining ties use the canonical order.
 pub fn best_match_for_reloc(
     mut symbols: Vec<(SymbolIndex, &ObjSymbol)>,
     reloc_kind: ObjRelocKind,
@@ -626,43 +700,118 @@ pub fn best_match_for_reloc(
     if symbols.len() == 1 {
         return symbols.into_iter().next();
     }
-    symbols.sort_by_key(|&(_, symbol)| {
-        let mut rank = match symbol.kind {
-            ObjSymbolKind::Function | ObjSymbolKind::Object => {
-                // HACK: These are generally not referenced directly, so reduce their rank
-                if matches!(
-                    symbol.name.as_str(),
-                    "__save_gpr" | "__restore_gpr" | "__save_fpr" | "__restore_fpr"
-                ) {
-                    return 0;
-                }
-                match reloc_kind {
-                    ObjRelocKind::PpcAddr16Hi
-                    | ObjRelocKind::PpcAddr16Ha
-                    | ObjRelocKind::PpcAddr16Lo => 1,
-                    ObjRelocKind::Absolute
-                    | ObjRelocKind::PpcRel24
-                    | ObjRelocKind::PpcRel14
-                    | ObjRelocKind::PpcEmbSda21 => 2,
-                }
+    symbols.sort_by_key(|&(index, symbol)| {
+        (-reloc_symbol_rank(symbol, reloc_kind), canonical_symbol_key(index, symbol))
+    });
+    symbols.into_iter().next()
+}
+
+fn reloc_symbol_rank(symbol: &ObjSymbol, reloc_kind: ObjRelocKind) -> i32 {
+    let mut rank = match symbol.kind {
+        ObjSymbolKind::Function | ObjSymbolKind::Object => {
+            // HACK: These are generally not referenced directly, so reduce their rank
+            if matches!(
+                symbol.name.as_str(),
+                "__save_gpr" | "__restore_gpr" | "__save_fpr" | "__restore_fpr"
+            ) {
+                return 0;
             }
-            // Label
-            ObjSymbolKind::Unknown => match reloc_kind {
-                ObjRelocKind::PpcAddr16Hi
+            match reloc_kind {
+                ObjRelocKind::PpcAddr16
+                | ObjRelocKind::PpcAddr16Hi
                 | ObjRelocKind::PpcAddr16Ha
                 | ObjRelocKind::PpcAddr16Lo
-                    if !symbol.name.starts_with("..") =>
-                {
-                    3
-                }
-                _ => 1,
-            },
-            ObjSymbolKind::Section => -1,
-        };
-        if symbol.size > 0 {
-            rank += 1;
+                | ObjRelocKind::PpcTprel16
+                | ObjRelocKind::PpcTprel16Lo
+                | ObjRelocKind::PpcTprel16Hi
+                | ObjRelocKind::PpcTprel16Ha
+                | ObjRelocKind::PpcDtprel16
+                | ObjRelocKind::PpcDtprel16Lo
+                | ObjRelocKind::PpcDtprel16Hi
+                | ObjRelocKind::PpcDtprel16Ha
+                | ObjRelocKind::PpcEmbNaddr16
+                | ObjRelocKind::PpcEmbSdai16
+                | ObjRelocKind::PpcEmbSda2i16 => 1,
+                ObjRelocKind::Absolute
+                | ObjRelocKind::PpcRel24
+                | ObjRelocKind::PpcAddr24
+                | ObjRelocKind::PpcRel14
+                | ObjRelocKind::PpcEmbSda21
+                | ObjRelocKind::PpcRel32
+                | ObjRelocKind::PpcEmbNaddr32 => 2,
+            }
         }
-        -rank
-    });
-    symbols.into_iter().next()
+        // Label
+        ObjSymbolKind::Unknown => match reloc_kind {
+            ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Ha | ObjRelocKind::PpcAddr16Lo
+                if !symbol.name.starts_with("..") =>
+            {
+                3
+            }
+            _ => 1,
+        },
+        ObjSymbolKind::Section | ObjSymbolKind::File => -1,
+    };
+    if symbol.size > 0 {
+        rank += 1;
+    }
+    rank
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_demangled_name() {
+        let mut symbol = ObjSymbol { name: "foo__Fi".to_string(), ..Default::default() };
+        symbol.set_demangled_name("foo(int)");
+        assert_eq!(symbol.name, "foo__Fi");
+        assert_eq!(symbol.demangled_name.as_deref(), Some("foo(int)"));
+
+        // Mismatched names are stored anyway
+        symbol.set_demangled_name("bar(int)");
+        assert_eq!(symbol.name, "foo__Fi");
+        assert_eq!(symbol.demangled_name.as_deref(), Some("bar(int)"));
+    }
+
+    #[test]
+    fn test_is_undefined() {
+        let undefined = ObjSymbol { name: "foo".to_string(), ..Default::default() };
+        assert!(undefined.is_undefined());
+        assert!(!ObjSymbol { section: Some(0), ..undefined.clone() }.is_undefined());
+        assert!(!ObjSymbol { address: 0x80003100, ..undefined.clone() }.is_undefined());
+        let common = ObjSymbol {
+            flags: ObjSymbolFlagSet(ObjSymbolFlags::Common.into()),
+            ..undefined.clone()
+        };
+        assert!(!common.is_undefined());
+    }
+
+    #[test]
+    fn test_best_match_for_reloc() {
+        let symbol = |name: &str, kind, size, scope: ObjSymbolFlags| ObjSymbol {
+            name: name.to_string(),
+            section: Some(0),
+            size,
+            kind,
+            flags: ObjSymbolFlagSet(scope.into()),
+            ..Default::default()
+        };
+        let symbols = [
+            symbol("foo", ObjSymbolKind::Function, 8, ObjSymbolFlags::Global),
+            symbol("lbl_local", ObjSymbolKind::Unknown, 0, ObjSymbolFlags::Local),
+            symbol("lbl_global", ObjSymbolKind::Unknown, 0, ObjSymbolFlags::Global),
+        ];
+        let candidates =
+            || symbols.iter().enumerate().map(|(i, s)| (i as SymbolIndex, s)).collect::<Vec<_>>();
+        let best = |kind| best_match_for_reloc(candidates(), kind).map(|(i, _)| i);
+
+        // Branches go to the function, as in the canonical order
+        assert_eq!(best(ObjRelocKind::PpcRel24), Some(0));
+        let canonical = candidates().into_iter().min_by_key(|&(i, s)| canonical_symbol_key(i, s));
+        assert_eq!(canonical.map(|(i, _)| i), Some(0));
+        // @ha/@l pairs prefer labels, with ties broken by the canonical order
+        assert_eq!(best(ObjRelocKind::PpcAddr16Ha), Some(2));
+    }
 }
@@ -0,0 +1,61 @@
+//! Section and symbol constructors shared by unit tests.
+
+use crate::obj::{
+    ObjSection, ObjSectionKind, ObjSymbol, ObjSymbolFlagSet, ObjSymbolFlags, ObjSymbolKind,
+    SectionIndex,
+};
+
+/// A loaded section of `size` zero bytes. BSS sections have no data.
+pub fn test_section(name: &str, kind: ObjSectionKind, address: u64, size: u64) -> ObjSection {
+    ObjSection {
+        name: name.to_string(),
+        kind,
+        address,
+        size,
+        data: if kind == ObjSectionKind::Bss { vec![] } else { vec![0; size as usize] },
+        align: 4,
+        elf_index: 0,
+        relocations: Default::default(),
+        virtual_address: None,
+        file_offset: None,
+        section_known: true,
+        alloc: true,
+        load: true,
+        splits: Default::default(),
+        segment: None,
+    }
+}
+
+/// A loaded section containing `data`.
+pub fn test_section_data(
+    name: &str,
+    kind: ObjSectionKind,
+    address: u64,
+    data: Vec<u8>,
+) -> ObjSection {
+    ObjSection { size: data.len() as u64, data, ..test_section(name, kind, address, 0) }
+}
+
+/// A loaded section containing big-endian `words`.
+pub fn test_section_words(
+    name: &str,
+    kind: ObjSectionKind,
+    address: u64,
+    words: &[u32],
+) -> ObjSection {
+    test_section_data(name, kind, address, words.iter().flat_map(|w| w.to_be_bytes()).collect())
+}
+
+/// A global function symbol of known size.
+pub fn test_symbol(name: &str, section: SectionIndex, address: u64, size: u64) -> ObjSymbol {
+    ObjSymbol {
+        name: name.to_string(),
+        address,
+        section: Some(section),
+        size,
+        size_known: true,
+        flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
+        kind: ObjSymbolKind::Function,
+        ..Default::default()
+    }
+}
@@ -237,6 +237,7 @@ impl AlfSymbol {
             kind,
             align: None,
             data_kind: Default::default(),
+            st_other: 0,
             name_hash,
             demangled_name_hash,
         })
@@ -0,0 +1,135 @@
+use std::{
+    collections::{btree_map, BTreeMap},
+    io::Read,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use crate::{
+    obj::ObjInfo,
+    util::elf::{process_elf_data, write_elf},
+};
+
+pub const AR_MAGIC: &[u8; 8] = b"!<arch>\n";
+
+/// Reads each object member of an `ar` archive. The GNU symbol table (`/`) and long
+/// name table (`//`) members are consumed while reading and not returned.
+pub fn read_archive(data: &[u8]) -> Result<Vec<(String, ObjInfo)>> {
+    let mut archive = ar::Archive::new(data);
+    let mut objects = vec![];
+    while let Some(result) = archive.next_entry() {
+        let mut entry = result.map_err(|e| anyhow!("Failed to read archive entry: {e}"))?;
+        let name = String::from_utf8_lossy(entry.header().identifier()).into_owned();
+        let mut member = Vec::with_capacity(entry.header().size() as usize);
+        entry.read_to_end(&mut member)?;
+        let obj = process_elf_data(&member, |_| {})
+            .with_context(|| format!("Failed to process archive member '{name}'"))?;
+        objects.push((name, obj));
+    }
+    Ok(objects)
+}
+
+/// Writes each object with [`write_elf`] and bundles them into a GNU `ar` archive,
+/// including a symbol table of defined global symbols.
+pub fn write_archive(objects: &[(String, ObjInfo)]) -> Result<Vec<u8>> {
+    let mut identifiers = Vec::with_capacity(objects.len());
+    let mut symbol_table = BTreeMap::new();
+    let mut members = Vec::with_capacity(objects.len());
+    for (name, obj) in objects {
+        let identifier = name.as_bytes().to_vec();
+        let entries = match symbol_table.entry(identifier.clone()) {
+            btree_map::Entry::Vacant(e) => e.insert(Vec::new()),
+            btree_map::Entry::Occupied(_) => bail!("Duplicate object name '{name}'"),
+        };
+        for (_, symbol) in obj.symbols.iter() {
+            if !symbol.flags.is_local()
+                && (symbol.section.is_some() || symbol.flags.is_common())
+                && !symbol.name.is_empty()
+            {
+                entries.push(symbol.name.as_bytes().to_vec());
+            }
+        }
+        identifiers.push(identifier);
+        members.push(write_elf(obj, false).with_context(|| format!("Writing object {name}"))?);
+    }
+
+    let mut out = Vec::new();
+    let mut builder = ar::GnuBuilder::new_with_symbol_table(
+        &mut out,
+        true,
+        identifiers.clone(),
+        ar::GnuSymbolTableFormat::Size32,
+        symbol_table,
+    )?;
+    for (identifier, data) in identifiers.into_iter().zip(members) {
+        let header = ar::Header::new(identifier, data.len() as u64);
+        builder.append(&header, data.as_slice())?;
+    }
+    builder.into_inner()?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::obj::{
+        testing::{test_section_words, test_symbol},
+        ObjArchitecture, ObjKind, ObjSectionKind,
+    };
+
+    fn test_obj(symbol_name: &str) -> ObjInfo {
+        ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            String::new(),
+            vec![test_symbol(symbol_name, 0, 0, 4)],
+            vec![test_section_words(".text", ObjSectionKind::Code, 0, &[0x4E800020])],
+        )
+    }
+
+    #[test]
+    fn test_read_archive() {
+        let data = write_archive(&[
+            ("a.o".to_string(), test_obj("foo")),
+            ("a_very_long_object_name.o".to_string(), test_obj("bar")),
+        ])
+        .unwrap();
+        assert!(data.starts_with(AR_MAGIC));
+
+        let objects = read_archive(&data).unwrap();
+        assert_eq!(objects.len(), 2);
+        assert_eq!(objects[0].0, "a.o");
+        assert!(objects[0].1.symbols.by_name("foo").unwrap().is_some());
+        assert_eq!(objects[1].0, "a_very_long_object_name.o");
+        assert!(objects[1].1.symbols.by_name("bar").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_write_archive() {
+        let data = write_archive(&[
+            ("a.o".to_string(), test_obj("foo")),
+            ("a_very_long_object_name.o".to_string(), test_obj("bar")),
+        ])
+        .unwrap();
+
+        let mut archive = ar::Archive::new(Cursor::new(data.as_slice()));
+        let symbols = archive
+            .symbols()
+            .unwrap()
+            .map(|s| String::from_utf8(s.to_vec()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(symbols, vec!["foo", "bar"]);
+        let mut names = vec![];
+        while let Some(entry) = archive.next_entry() {
+            names.push(String::from_utf8(entry.unwrap().header().identifier().to_vec()).unwrap());
+        }
+        assert_eq!(names, vec!["a.o", "a_very_long_object_name.o"]);
+        assert!(write_archive(&[
+            ("a.o".to_string(), test_obj("foo")),
+            ("a.o".to_string(), test_obj("bar"))
+        ])
+        .is_err());
+    }
+}
@@ -10,8 +10,8 @@ use ppc750cl::{Argument, Ins, InsIter, Opcode};
 
 use crate::{
     obj::{
-        ObjDataKind, ObjInfo, ObjReloc, ObjRelocKind, ObjSection, ObjSectionKind, ObjSymbol,
-        ObjSymbolKind, SymbolIndex,
+        sda21_field_mask, ObjDataKind, ObjInfo, ObjReloc, ObjRelocKind, ObjSection, ObjSectionKind,
+        ObjSymbol, ObjSymbolKind, SymbolIndex,
     },
     util::nested::NestedVec,
 };
@@ -249,7 +249,7 @@ where
 {
     for (addr, ins) in InsIter::new(data, address) {
         let reloc = relocations.get(&addr);
-        let file_offset = section.file_offset + (addr as u64 - section.address);
+        let file_offset = section.file_offset.unwrap_or_default() + (addr as u64 - section.address);
         write_ins(w, symbols, addr, ins, reloc, file_offset, section.virtual_address)?;
     }
     Ok(())
@@ -281,13 +281,25 @@ where
     if let Some(reloc) = reloc {
         // Zero out relocations
         ins.code = match reloc.kind {
-            ObjRelocKind::Absolute => 0,
-            ObjRelocKind::PpcEmbSda21 => ins.code & !0x1FFFFF,
-            ObjRelocKind::PpcRel24 => ins.code & !0x3FFFFFC,
+            ObjRelocKind::Absolute | ObjRelocKind::PpcRel32 | ObjRelocKind::PpcEmbNaddr32 => 0,
+            ObjRelocKind::PpcEmbSda21 => ins.code & !sda21_field_mask(ins.code),
+            ObjRelocKind::PpcRel24 | ObjRelocKind::PpcAddr24 => ins.code & !0x3FFFFFC,
             ObjRelocKind::PpcRel14 => ins.code & !0xFFFC,
-            ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Ha | ObjRelocKind::PpcAddr16Lo => {
-                ins.code & !0xFFFF
-            }
+            ObjRelocKind::PpcAddr16
+            | ObjRelocKind::PpcAddr16Hi
+            | ObjRelocKind::PpcAddr16Ha
+            | ObjRelocKind::PpcAddr16Lo
+            | ObjRelocKind::PpcTprel16
+            | ObjRelocKind::PpcTprel16Lo
+            | ObjRelocKind::PpcTprel16Hi
+            | ObjRelocKind::PpcTprel16Ha
+            | ObjRelocKind::PpcDtprel16
+            | ObjRelocKind::PpcDtprel16Lo
+            | ObjRelocKind::PpcDtprel16Hi
+            | ObjRelocKind::PpcDtprel16Ha
+            | ObjRelocKind::PpcEmbNaddr16
+            | ObjRelocKind::PpcEmbSdai16
+            | ObjRelocKind::PpcEmbSda2i16 => ins.code & !0xFFFF,
         };
     }
 
@@ -345,7 +357,11 @@ fn write_reloc<W>(w: &mut W, symbols: &[ObjSymbol], reloc: &ObjReloc) -> Result<
 where W: Write + ?Sized {
     write_reloc_symbol(w, symbols, reloc)?;
     match reloc.kind {
-        ObjRelocKind::Absolute | ObjRelocKind::PpcRel24 | ObjRelocKind::PpcRel14 => {
+        ObjRelocKind::Absolute
+        | ObjRelocKind::PpcAddr16
+        | ObjRelocKind::PpcRel24
+        | ObjRelocKind::PpcAddr24
+        | ObjRelocKind::PpcRel14 => {
             // pass
         }
         ObjRelocKind::PpcAddr16Hi => {
@@ -360,6 +376,39 @@ where W: Write + ?Sized {
         ObjRelocKind::PpcEmbSda21 => {
             write!(w, "@sda21")?;
         }
+        ObjRelocKind::PpcRel32 => {
+            write!(w, " - .")?;
+        }
+        ObjRelocKind::PpcTprel16 => {
+            write!(w, "@tprel")?;
+        }
+        ObjRelocKind::PpcTprel16Lo => {
+            write!(w, "@tprel@l")?;
+        }
+        ObjRelocKind::PpcTprel16Hi => {
+            write!(w, "@tprel@h")?;
+        }
+        ObjRelocKind::PpcTprel16Ha => {
+            write!(w, "@tprel@ha")?;
+        }
+        ObjRelocKind::PpcDtprel16 => {
+            write!(w, "@dtprel")?;
+        }
+        ObjRelocKind::PpcDtprel16Lo => {
+            write!(w, "@dtprel@l")?;
+        }
+        ObjRelocKind::PpcDtprel16Hi => {
+            write!(w, "@dtprel@h")?;
+        }
+        ObjRelocKind::PpcDtprel16Ha => {
+            write!(w, "@dtprel@ha")?;
+        }
+        ObjRelocKind::PpcEmbNaddr32
+        | ObjRelocKind::PpcEmbNaddr16
+        | ObjRelocKind::PpcEmbSdai16
+        | ObjRelocKind::PpcEmbSda2i16 => {
+            bail!("Unsupported relocation type {:?} in assembly", reloc.kind)
+        }
     }
     Ok(())
 }
@@ -385,6 +434,7 @@ where
         ObjSymbolKind::Object => "obj",
         ObjSymbolKind::Unknown => "sym",
         ObjSymbolKind::Section => bail!("Attempted to write section symbol: {symbol:?}"),
+        ObjSymbolKind::File => bail!("Attempted to write file symbol: {symbol:?}"),
     };
     let scope = if symbol.flags.is_weak() {
         "weak"
@@ -540,7 +590,9 @@ where
                     ObjSymbolKind::Function => {
                         // handled in write_code_chunk
                     }
-                    ObjSymbolKind::Unknown | ObjSymbolKind::Section => unreachable!(),
+                    ObjSymbolKind::Unknown | ObjSymbolKind::Section | ObjSymbolKind::File => {
+                        unreachable!()
+                    }
                 }
             }
         }
@@ -831,6 +883,12 @@ where
             writeln!(w)?;
             Ok(reloc_address + 4)
         }
+        ObjRelocKind::PpcRel32 => {
+            write!(w, "\t.4byte ")?;
+            write_reloc_symbol(w, symbols, reloc)?;
+            writeln!(w, " - .")?;
+            Ok(reloc_address + 4)
+        }
         _ => Err(anyhow!(
             "Unsupported data relocation type {:?} @ {:#010X}",
             reloc.kind,
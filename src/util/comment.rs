@@ -257,7 +257,7 @@ impl CommentSym {
                     symbol.address as u32
                 } else {
                     match symbol.kind {
-                        ObjSymbolKind::Unknown => 0,
+                        ObjSymbolKind::Unknown | ObjSymbolKind::File => 0,
                         ObjSymbolKind::Function => 4,
                         ObjSymbolKind::Object => {
                             if symbol.address & 3 == 0 {
@@ -10,6 +10,7 @@ use cwdemangle::{demangle, DemangleOptions};
 use filetime::FileTime;
 use once_cell::sync::Lazy;
 use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
 use tracing::{debug, info, warn};
 use typed_path::Utf8NativePath;
 use xxhash_rust::xxh3::xxh3_64;
@@ -176,6 +177,150 @@ pub fn parse_symbol_line(line: &str, obj: &mut ObjInfo) -> Result<Option<ObjSymb
     }
 }
 
+/// A symbol definition in a YAML symbols config file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SymbolConfig {
+    pub name: String,
+    /// Format: `section:address`, e.g. `.text:0x80001234`.
+    pub address: SectionAddressRef,
+    /// One of `function`, `object`, `label`.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u32>,
+    /// One of `global`, `local`, `weak`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub align: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    /// Additional flags: `hidden`, `force_active`, `stripped`, `noexport`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flags: Vec<String>,
+    /// Discovered by analysis rather than provided by the user.
+    /// Autogenerated symbols never replace existing symbols on import.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub autogenerated: bool,
+}
+
+impl SymbolConfig {
+    fn from_symbol(obj: &ObjInfo, symbol: &ObjSymbol) -> Option<Self> {
+        let section = symbol.section.and_then(|idx| obj.sections.get(idx))?;
+        let mut flags = vec![];
+        if symbol.flags.is_hidden() {
+            flags.push("hidden".to_string());
+        }
+        if symbol.flags.is_stripped() {
+            flags.push("stripped".to_string());
+        }
+        if symbol.flags.is_no_export() {
+            flags.push("noexport".to_string());
+        }
+        Some(Self {
+            name: symbol.name.clone(),
+            address: SectionAddressRef::new(Some(section.name.clone()), symbol.address as u32),
+            kind: Some(symbol_kind_to_str(symbol.kind).to_string()),
+            size: (symbol.size_known && symbol.size > 0).then_some(symbol.size as u32),
+            scope: symbol_flags_to_str(symbol.flags).map(str::to_string),
+            align: symbol.align,
+            data: symbol_data_kind_to_str(symbol.data_kind).map(str::to_string),
+            flags,
+            autogenerated: is_auto_symbol(symbol),
+        })
+    }
+
+    fn to_symbol(&self, section_index: SectionIndex) -> Result<ObjSymbol> {
+        let mut symbol = ObjSymbol {
+            name: self.name.clone(),
+            demangled_name: demangle(&self.name, &DemangleOptions::default()),
+            address: self.address.address as u64,
+            section: Some(section_index),
+            ..Default::default()
+        };
+        if let Some(kind) = &self.kind {
+            symbol.kind = symbol_kind_from_str(kind)
+                .ok_or_else(|| anyhow!("Unknown symbol type '{}'", kind))?;
+        }
+        if let Some(size) = self.size {
+            symbol.size = size as u64;
+            symbol.size_known = true;
+        }
+        if let Some(scope) = &self.scope {
+            symbol.flags.0 |= symbol_flags_from_str(scope)
+                .ok_or_else(|| anyhow!("Unknown symbol scope '{}'", scope))?;
+        }
+        symbol.align = self.align;
+        if let Some(data) = &self.data {
+            symbol.data_kind = symbol_data_kind_from_str(data)
+                .ok_or_else(|| anyhow!("Unknown symbol data type '{}'", data))?;
+        }
+        for flag in &self.flags {
+            symbol.flags.0 |= match flag.as_str() {
+                "hidden" => ObjSymbolFlags::Hidden,
+                "force_active" => ObjSymbolFlags::Exported,
+                "stripped" => ObjSymbolFlags::Stripped,
+                "noexport" => ObjSymbolFlags::NoExport,
+                _ => bail!("Unknown symbol flag '{}'", flag),
+            };
+        }
+        Ok(symbol)
+    }
+}
+
+pub fn read_symbols_config(path: &Utf8NativePath) -> Result<Option<Vec<SymbolConfig>>> {
+    if !fs::metadata(path).is_ok_and(|m| m.is_file()) {
+        return Ok(None);
+    }
+    let mut file = open_file(path, true)?;
+    let symbols = serde_yaml::from_reader(file.as_mut())
+        .with_context(|| format!("While parsing symbols config '{}'", path))?;
+    Ok(Some(symbols))
+}
+
+/// Applies symbols from a symbols config, creating any that don't exist.
+/// If a user-defined symbol already exists at an address, its name is kept.
+pub fn apply_symbols(obj: &mut ObjInfo, symbols: &[SymbolConfig]) -> Result<()> {
+    for config in symbols {
+        let addr = config.address.resolve(obj)?;
+        let symbol = config.to_symbol(addr.section)?;
+        if config.autogenerated {
+            obj.add_symbol(symbol, false)?;
+            continue;
+        }
+        let existing = obj
+            .symbols
+            .at_section_address(addr.section, addr.address)
+            .find(|(_, s)| s.kind == symbol.kind || s.kind == ObjSymbolKind::Unknown)
+            .map(|(idx, s)| (idx, s.clone()));
+        match existing {
+            Some((idx, existing)) if existing.name != symbol.name && !is_auto_symbol(&existing) => {
+                warn!(
+                    "Symbol {} @ {} conflicts with existing symbol {}, keeping existing name",
+                    symbol.name, addr, existing.name
+                );
+                obj.symbols.replace(idx, ObjSymbol {
+                    name: existing.name.clone(),
+                    demangled_name: existing.demangled_name.clone(),
+                    size: if symbol.size_known { symbol.size } else { existing.size },
+                    size_known: symbol.size_known || existing.size_known,
+                    flags: ObjSymbolFlagSet(symbol.flags.0 | existing.flags.keep_flags()),
+                    align: symbol.align.or(existing.align),
+                    data_kind: match symbol.data_kind {
+                        ObjDataKind::Unknown => existing.data_kind,
+                        kind => kind,
+                    },
+                    ..symbol
+                })?;
+            }
+            _ => {
+                obj.add_symbol(symbol, true)?;
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn is_skip_symbol(symbol: &ObjSymbol) -> bool {
     if symbol.flags.is_no_write() {
         return true;
@@ -308,6 +453,18 @@ where W: Write + ?Sized {
     Ok(())
 }
 
+/// Serializes all symbols to a YAML symbols config, ordered by section and address.
+#[inline]
+pub fn write_symbols_config(obj: &ObjInfo) -> Result<String> {
+    let symbols = obj
+        .symbols
+        .iter_ordered()
+        .filter(|(_, s)| s.kind != ObjSymbolKind::Section && !is_skip_symbol(s))
+        .filter_map(|(_, s)| SymbolConfig::from_symbol(obj, s))
+        .collect::<Vec<_>>();
+    Ok(serde_yaml::to_string(&symbols)?)
+}
+
 #[inline]
 fn symbol_kind_to_str(kind: ObjSymbolKind) -> &'static str {
     match kind {
@@ -315,6 +472,7 @@ fn symbol_kind_to_str(kind: ObjSymbolKind) -> &'static str {
         ObjSymbolKind::Function => "function",
         ObjSymbolKind::Object => "object",
         ObjSymbolKind::Section => "section",
+        ObjSymbolKind::File => "file",
     }
 }
 
@@ -344,6 +502,7 @@ fn symbol_kind_from_str(s: &str) -> Option<ObjSymbolKind> {
         "function" => Some(ObjSymbolKind::Function),
         "object" => Some(ObjSymbolKind::Object),
         "section" => Some(ObjSymbolKind::Section),
+        "file" => Some(ObjSymbolKind::File),
         _ => None,
     }
 }
@@ -898,3 +1057,80 @@ impl serde::Serialize for SectionAddressRef {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        testing::{test_section, test_symbol},
+        ObjArchitecture, ObjSection,
+    };
+
+    fn test_obj() -> ObjInfo {
+        ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "main.dol".to_string(),
+            vec![test_symbol("fn_80003100", 0, 0x80003100, 0x10)],
+            vec![ObjSection {
+                file_offset: Some(0x100),
+                ..test_section(".text", ObjSectionKind::Code, 0x80003100, 0x100)
+            }],
+        )
+    }
+
+    #[test]
+    fn test_apply_symbols() {
+        let mut obj = test_obj();
+        let symbols: Vec<SymbolConfig> = serde_yaml::from_str(
+            r#"
+- name: main
+  address: .text:0x80003100
+  type: function
+  size: 0x20
+  scope: global
+- name: helper
+  address: .text:0x80003120
+  type: function
+  flags: [hidden]
+"#,
+        )
+        .unwrap();
+        apply_symbols(&mut obj, &symbols).unwrap();
+        assert_eq!(obj.symbols.count(), 2);
+
+        let (_, main) = obj.symbols.by_name("main").unwrap().unwrap();
+        assert_eq!(main.address, 0x80003100);
+        assert_eq!(main.size, 0x20);
+        assert!(main.flags.0.contains(ObjSymbolFlags::Global));
+
+        let (_, helper) = obj.symbols.by_name("helper").unwrap().unwrap();
+        assert_eq!(helper.address, 0x80003120);
+        assert!(helper.flags.is_hidden());
+
+        // User-defined names are not overwritten
+        let rename = vec![SymbolConfig { name: "other".to_string(), ..symbols[0].clone() }];
+        apply_symbols(&mut obj, &rename).unwrap();
+        assert!(obj.symbols.by_name("other").unwrap().is_none());
+        assert!(obj.symbols.by_name("main").unwrap().is_some());
+    }
+
+    #[test]
+    fn test_write_symbols_config() {
+        let mut obj = test_obj();
+        obj.add_symbol(test_symbol("main", 0, 0x80003180, 0x8), false).unwrap();
+        let out = write_symbols_config(&obj).unwrap();
+        assert_eq!(out, write_symbols_config(&obj).unwrap());
+
+        let symbols: Vec<SymbolConfig> = serde_yaml::from_str(&out).unwrap();
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].name, "fn_80003100");
+        assert!(symbols[0].autogenerated);
+        assert_eq!(symbols[1].name, "main");
+        assert!(!symbols[1].autogenerated);
+
+        let mut new_obj = test_obj();
+        apply_symbols(&mut new_obj, &symbols).unwrap();
+        assert_eq!(write_symbols_config(&new_obj).unwrap(), out);
+    }
+}
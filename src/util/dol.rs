@@ -432,9 +432,12 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
             elf_index: 0,
             relocations: Default::default(),
             virtual_address: Some(dol_section.address as u64),
-            file_offset: dol_section.file_offset as u64,
+            file_offset: (kind != ObjSectionKind::Bss).then_some(dol_section.file_offset as u64),
             section_known: known,
+            alloc: true,
+            load: true,
             splits: Default::default(),
+            segment: None,
         });
     }
 
@@ -462,9 +465,12 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
                 elf_index: 0,
                 relocations: Default::default(),
                 virtual_address: Some(addr as u64),
-                file_offset: 0,
+                file_offset: None,
                 section_known: false,
+                alloc: true,
+                load: true,
                 splits: Default::default(),
+                segment: None,
             });
         }
 
@@ -482,9 +488,12 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
                 elf_index: 0,
                 relocations: Default::default(),
                 virtual_address: Some(bss_section.address as u64),
-                file_offset: 0,
+                file_offset: None,
                 section_known: false,
+                alloc: true,
+                load: true,
                 splits: Default::default(),
+                segment: None,
             });
             let mut obj = ObjInfo::new(
                 ObjKind::Executable,
@@ -509,9 +518,12 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
                         elf_index: 0,
                         relocations: Default::default(),
                         virtual_address: Some(bss_sections[0].0 as u64),
-                        file_offset: 0,
+                        file_offset: None,
                         section_known: false,
+                        alloc: true,
+                        load: true,
                         splits: Default::default(),
+                        segment: None,
                     });
                     sections.push(ObjSection {
                         name: ".sbss".to_string(),
@@ -523,9 +535,12 @@ pub fn process_dol(buf: &[u8], name: &str) -> Result<ObjInfo> {
                         elf_index: 0,
                         relocations: Default::default(),
                         virtual_address: Some(bss_sections[1].0 as u64),
-                        file_offset: 0,
+                        file_offset: None,
                         section_known: false,
+                        alloc: true,
+                        load: true,
                         splits: Default::default(),
+                        segment: None,
                     });
                 }
                 n => bail!("Invalid number of BSS sections: {}", n),
@@ -531,6 +531,125 @@ where R: BufRead + Seek + ?Sized {
     Ok(())
 }
 
+/// Reads the name (`DW_AT_name`) of the first compilation unit in a DWARF 2-4
+/// `.debug_info` section, as emitted by GCC.
+///
+/// `debug_info` relocations must already be applied. Returns `None` if the first
+/// DIE isn't a compilation unit or has no name.
+pub fn read_compile_unit_name(
+    debug_info: &[u8],
+    debug_abbrev: &[u8],
+    debug_str: Option<&[u8]>,
+    e: Endian,
+) -> Result<Option<String>> {
+    const DW_TAG_COMPILE_UNIT: u64 = 0x11;
+    const DW_AT_NAME: u64 = 0x03;
+
+    let mut reader = Cursor::new(debug_info);
+    let unit_length = u32::from_reader(&mut reader, e)?;
+    ensure!(unit_length != u32::MAX, "64-bit DWARF is not supported");
+    let version = u16::from_reader(&mut reader, e)?;
+    // DWARF 5 names use forms (e.g. DW_FORM_line_strp, DW_FORM_strx) that aren't supported
+    ensure!((2..=4).contains(&version), "Unsupported DWARF version {version}");
+    let abbrev_offset = u32::from_reader(&mut reader, e)?;
+    let address_size = u8::from_reader(&mut reader, e)?;
+    let code = read_uleb128(&mut reader)?;
+    if code == 0 {
+        return Ok(None);
+    }
+
+    // Locate the abbreviation for the first DIE
+    let mut abbrev_reader = Cursor::new(debug_abbrev);
+    abbrev_reader.set_position(abbrev_offset as u64);
+    let attributes = loop {
+        let abbrev_code = read_uleb128(&mut abbrev_reader)?;
+        ensure!(abbrev_code != 0, "Abbreviation {code} not found");
+        let tag = read_uleb128(&mut abbrev_reader)?;
+        let _has_children = u8::from_reader(&mut abbrev_reader, e)?;
+        let mut attributes = vec![];
+        loop {
+            let name = read_uleb128(&mut abbrev_reader)?;
+            let form = read_uleb128(&mut abbrev_reader)?;
+            if name == 0 && form == 0 {
+                break;
+            }
+            attributes.push((name, form));
+        }
+        if abbrev_code == code {
+            if tag != DW_TAG_COMPILE_UNIT {
+                return Ok(None);
+            }
+            break attributes;
+        }
+    };
+
+    for (name, mut form) in attributes {
+        // DW_FORM_indirect
+        while form == 0x16 {
+            form = read_uleb128(&mut reader)?;
+        }
+        if name == DW_AT_NAME {
+            return match form {
+                // DW_FORM_string
+                0x08 => Ok(Some(read_string(&mut reader)?)),
+                // DW_FORM_strp
+                0x0e => {
+                    let offset = u32::from_reader(&mut reader, e)?;
+                    let debug_str = debug_str.ok_or_else(|| anyhow!("Missing .debug_str"))?;
+                    let mut str_reader = Cursor::new(debug_str);
+                    str_reader.set_position(offset as u64);
+                    Ok(Some(read_string(&mut str_reader)?))
+                }
+                _ => bail!("Unsupported DW_AT_name form {form:#X}"),
+            };
+        }
+        let size = match form {
+            0x0b | 0x0c | 0x11 => 1,                          // data1, flag, ref1
+            0x05 | 0x12 => 2,                                 // data2, ref2
+            0x06 | 0x0e | 0x13 | 0x17 => 4,                   // data4, strp, ref4, sec_offset
+            0x07 | 0x14 | 0x20 => 8,                          // data8, ref8, ref_sig8
+            0x19 => 0,                                        // flag_present
+            0x01 => address_size as u64,                      // addr
+            0x10 if version == 2 => address_size as u64,      // ref_addr
+            0x10 => 4,                                        // ref_addr
+            0x0a => u8::from_reader(&mut reader, e)? as u64,  // block1
+            0x03 => u16::from_reader(&mut reader, e)? as u64, // block2
+            0x04 => u32::from_reader(&mut reader, e)? as u64, // block4
+            0x09 | 0x18 => read_uleb128(&mut reader)?,        // block, exprloc
+            0x0d | 0x0f | 0x15 => {
+                // sdata, udata, ref_udata
+                read_uleb128(&mut reader)?;
+                0
+            }
+            0x08 => {
+                // string
+                read_string(&mut reader)?;
+                0
+            }
+            _ => bail!("Unsupported DWARF form {form:#X}"),
+        };
+        reader.seek(SeekFrom::Current(size as i64))?;
+    }
+    Ok(None)
+}
+
+pub(crate) fn read_uleb128<R>(reader: &mut R) -> Result<u64>
+where R: BufRead + ?Sized {
+    let mut result = 0u64;
+    let mut shift = 0;
+    let mut buf = [0u8; 1];
+    loop {
+        reader.read_exact(&mut buf)?;
+        ensure!(shift < 64, "ULEB128 value too large");
+        result |= ((buf[0] & 0x7F) as u64) << shift;
+        if buf[0] & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    Ok(result)
+}
+
 fn read_tags<R>(
     reader: &mut R,
     data_endian: Endian,
@@ -623,7 +742,7 @@ where
 }
 
 // TODO Shift-JIS?
-fn read_string<R>(reader: &mut R) -> Result<String>
+pub(crate) fn read_string<R>(reader: &mut R) -> Result<String>
 where R: BufRead + ?Sized {
     let mut str = String::new();
     let mut buf = [0u8; 1];
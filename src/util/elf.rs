@@ -1,8 +1,7 @@
 use std::{
     collections::{hash_map, HashMap},
-    io::Cursor,
+    io::{Cursor, Write},
     num::NonZeroU64,
-    path::Path,
 };
 
 use anyhow::{anyhow, bail, ensure, Context, Result};
@@ -13,48 +12,183 @@ use objdiff_core::obj::split_meta::{SplitMeta, SHT_SPLITMETA, SPLITMETA_SECTION}
 use object::{
     elf,
     elf::{SHF_ALLOC, SHF_EXECINSTR, SHF_WRITE, SHT_LOUSER, SHT_NOBITS, SHT_PROGBITS},
+    read::elf::{ElfFile32, SectionHeader as _},
     write::{
-        elf::{ProgramHeader, Rel, SectionHeader, SectionIndex, SymbolIndex, Writer},
-        StringId,
+        elf::{ProgramHeader, Rel, SectionHeader, SectionIndex, Writer},
+        StreamingBuffer, StringId, WritableBuffer,
     },
-    Architecture, Endianness, Object, ObjectKind, ObjectSection, ObjectSymbol, Relocation,
-    RelocationFlags, RelocationTarget, SectionKind, Symbol, SymbolKind, SymbolScope, SymbolSection,
+    Architecture, Endianness, FileFlags, Object, ObjectKind, ObjectSection, ObjectSegment,
+    ObjectSymbol, Relocation, RelocationFlags, RelocationTarget, SectionFlags, SectionKind, Symbol,
+    SymbolFlags, SymbolKind, SymbolScope, SymbolSection,
 };
 use typed_path::Utf8NativePath;
 
 use crate::{
-    array_ref,
+    analysis::cfa::SectionAddress,
+    array_ref, array_ref_mut,
     obj::{
-        ObjArchitecture, ObjInfo, ObjKind, ObjReloc, ObjRelocKind, ObjSection, ObjSectionKind,
+        is_paired_single_load_store, overlapping_sections, sda21_field_mask, ObjArchitecture,
+        ObjInfo, ObjKind, ObjRawSection, ObjReloc, ObjRelocKind, ObjSection, ObjSectionKind,
         ObjSplit, ObjSymbol, ObjSymbolFlagSet, ObjSymbolFlags, ObjSymbolKind, ObjUnit,
         SectionIndex as ObjSectionIndex, SymbolIndex as ObjSymbolIndex,
     },
     util::{
         comment::{CommentSym, MWComment},
-        reader::{Endian, FromReader, ToWriter},
+        dwarf::read_compile_unit_name,
+        reader::{Endian, FromBytes, FromReader, ToWriter},
+        rso::symbol_hash,
     },
     vfs::open_file,
 };
 
 pub const SHT_MWCATS: u32 = SHT_LOUSER + 0x4A2A82C2;
 
+/// Section start address, name and index into the output sections (if known)
+type SectionStart = (u64, String, Option<usize>);
+
 enum BoundaryState {
     /// Looking for a file symbol, any section symbols are queued
-    LookForFile(Vec<(u64, String)>),
+    LookForFile(Vec<SectionStart>),
     /// Looking for section symbols
     LookForSections(String),
     /// Done with files and sections
     FilesEnded,
 }
 
+/// Number of symbols or relocations processed between progress events.
+const PROGRESS_INTERVAL: usize = 1000;
+
+/// Progress reported by [`process_elf_with_progress`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// All sections were loaded, with the number of sections kept.
+    SectionsLoaded(usize),
+    /// Total number of symbols processed so far.
+    SymbolsProcessed(usize),
+    /// Total number of relocations processed so far.
+    RelocationsProcessed(usize),
+}
+
+/// File and section attribution decisions made while reading an ELF,
+/// reported by [`process_elf_with_report`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoundaryEvent {
+    /// A file symbol started a new unit.
+    FileStarted(String),
+    /// A section starting at the address was attributed to a unit.
+    SectionAttributed { section: String, address: u64, file: String },
+    /// A linker generated symbol ended the file list.
+    FilesEnded(String),
+}
+
+/// The final component of a `/` or `\` separated path.
+fn file_name_from_path(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().filter(|s| !s.is_empty()).unwrap_or(path)
+}
+
+/// Runtime startup symbols recorded in [`ObjInfo::entry_chain`], in startup order.
+pub const RUNTIME_SYMBOLS: &[&str] = &[
+    "__start",
+    "__init_registers",
+    "__init_hardware",
+    "__init_data",
+    "__init_user",
+    "__init_cpp",
+    "_prolog",
+    "main",
+    "_epilog",
+    "exit",
+    "__fini_cpp",
+];
+
+/// Options for [`process_elf_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct ReadElfOptions {
+    /// Mirror the ELF symbol table as-is: every symbol, including file symbols and
+    /// symbols in discarded sections, is kept at its original index. File symbols aren't
+    /// filtered (precompiled headers) or renamed (duplicates), and linker generated
+    /// symbols don't end the file list. Units with the same file name share one entry in
+    /// [`ObjInfo::link_order`].
+    pub raw: bool,
+    /// Reduce directory-qualified file symbols (e.g. `src/game/foo.cpp`) to the file
+    /// name, matching the file symbol emitted by [`write_elf`].
+    pub strip_file_paths: bool,
+    /// Additional symbol names recorded in [`ObjInfo::entry_chain`], after the
+    /// [`RUNTIME_SYMBOLS`].
+    pub runtime_symbols: Vec<String>,
+    /// A previous read of the same ELF. The data buffers of sections whose name, file
+    /// offset, size, kind and contents are unchanged are taken over instead of being
+    /// allocated again.
+    pub previous: Option<ObjInfo>,
+}
+
+/// Reads an ELF with the default [`ReadElfOptions`].
+///
+/// File symbols keep their directory (e.g. `src/game/foo.cpp`) in [`ObjInfo::name`] and unit
+/// names, while [`write_elf`] only emits the file name, so paths don't round trip. Use
+/// [`ReadElfOptions::strip_file_paths`] when reads must match written objects.
 pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
+    process_elf_with_progress(path, |_| {})
+}
+
+pub fn process_elf_with_options(path: &Utf8NativePath, options: ReadElfOptions) -> Result<ObjInfo> {
+    let mut file = open_file(path, true)?;
+    process_elf_inner(file.map()?, options, |_| {}, |_| {})
+}
+
+pub fn process_elf_with_progress<F>(path: &Utf8NativePath, progress: F) -> Result<ObjInfo>
+where F: FnMut(ProgressEvent) {
     let mut file = open_file(path, true)?;
-    let obj_file = object::read::File::parse(file.map()?)?;
+    process_elf_data(file.map()?, progress)
+}
+
+/// Reads an ELF, also returning the file and section boundary decisions made
+/// while attributing sections to units. Useful for diagnosing incorrect splits.
+pub fn process_elf_with_report(path: &Utf8NativePath) -> Result<(ObjInfo, Vec<BoundaryEvent>)> {
+    let mut file = open_file(path, true)?;
+    let mut events = vec![];
+    let obj = process_elf_inner(
+        file.map()?,
+        ReadElfOptions::default(),
+        |_| {},
+        |event| events.push(event),
+    )?;
+    Ok((obj, events))
+}
+
+/// Re-reads an ELF that was previously loaded as `previous`, taking over the data buffers
+/// of unchanged sections (see [`ReadElfOptions::previous`]). Intended for watch/rebuild
+/// loops where most changes only touch the symbol table.
+pub fn process_elf_incremental(path: &Utf8NativePath, previous: ObjInfo) -> Result<ObjInfo> {
+    process_elf_with_options(path, ReadElfOptions {
+        previous: Some(previous),
+        ..Default::default()
+    })
+}
+
+/// Reads an ELF from memory, such as a member extracted from an archive.
+pub fn process_elf_data<F>(data: &[u8], progress: F) -> Result<ObjInfo>
+where F: FnMut(ProgressEvent) {
+    process_elf_inner(data, ReadElfOptions::default(), progress, |_| {})
+}
+
+fn process_elf_inner<F, R>(
+    data: &[u8],
+    mut options: ReadElfOptions,
+    mut progress: F,
+    mut report: R,
+) -> Result<ObjInfo>
+where
+    F: FnMut(ProgressEvent),
+    R: FnMut(BoundaryEvent),
+{
+    let obj_file = object::read::File::parse(data)?;
     let architecture = match obj_file.architecture() {
         Architecture::PowerPc => ObjArchitecture::PowerPc,
         arch => bail!("Unexpected architecture: {arch:?}"),
     };
-    ensure!(obj_file.endianness() == Endianness::Big, "Expected big endian");
+    // Used for the raw section header types, which `SectionKind` doesn't always reflect
+    let elf_file = ElfFile32::<Endianness>::parse(data)?;
     let kind = match obj_file.kind() {
         ObjectKind::Executable => ObjKind::Executable,
         ObjectKind::Relocatable => ObjKind::Relocatable,
@@ -70,41 +204,146 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
     let mut sda_base: Option<u32> = None;
     let mut sda2_base: Option<u32> = None;
 
+    // Loadable segment ranges, used to recover the original section grouping
+    let segments = obj_file
+        .segments()
+        .map(|segment| (segment.address(), segment.address() + segment.size()))
+        .collect::<Vec<_>>();
+
+    // Section data from a previous read, keyed by name, file offset, size and kind
+    let mut previous = options.previous.take();
+    let mut previous_sections = previous
+        .iter_mut()
+        .flat_map(|previous| previous.sections.iter_mut())
+        .filter(|(_, s)| s.kind != ObjSectionKind::Bss)
+        .map(|(_, s)| ((s.name.as_str(), s.file_offset, s.size, s.kind), &mut s.data))
+        .collect::<HashMap<_, _>>();
+
     let mut sections: Vec<ObjSection> = vec![];
+    let mut raw_sections: Vec<ObjRawSection> = vec![];
     let mut section_indexes: Vec<Option<usize>> = vec![None /* ELF null section */];
     for section in obj_file.sections() {
-        if section.size() == 0 {
+        // Empty allocated sections are kept as placeholders for link order matching
+        if section.size() == 0
+            && !matches!(
+                section.kind(),
+                SectionKind::Text
+                    | SectionKind::Data
+                    | SectionKind::ReadOnlyData
+                    | SectionKind::UninitializedData
+            )
+        {
             section_indexes.push(None);
             continue;
         }
         let section_name = section.name()?;
+        let header = elf_file.section_by_index(section.index())?.elf_section_header();
+        let alloc = header.sh_flags(elf_file.endian()) & SHF_ALLOC != 0;
+        let alloc_nobits = header.sh_type(elf_file.endian()) == SHT_NOBITS && alloc;
         let section_kind = match section.kind() {
+            // Any allocated no-bits section is BSS, e.g. `SHF_TLS` sections
+            _ if alloc_nobits => ObjSectionKind::Bss,
             SectionKind::Text => ObjSectionKind::Code,
             SectionKind::Data => ObjSectionKind::Data,
             SectionKind::ReadOnlyData => ObjSectionKind::ReadOnlyData,
             SectionKind::UninitializedData => ObjSectionKind::Bss,
             // SectionKind::Other if section_name == ".comment" => ObjSectionKind::Comment,
+            // Preserve unrecognized SHT_PROGBITS sections (e.g. .line) as-is, without their
+            // relocations. ELF debug sections are classified as `Other`.
+            SectionKind::Other | SectionKind::OtherString
+                if section_name != ".comment" && section_name != SPLITMETA_SECTION =>
+            {
+                let SectionFlags::Elf { sh_flags } = section.flags() else {
+                    bail!("Unexpected section flags for {section_name}");
+                };
+                // Compressed sections are stored decompressed, with the original alignment
+                // from the compression header
+                let compressed = sh_flags & elf::SHF_COMPRESSED as u64 != 0;
+                let align = if compressed {
+                    let data = section.data()?;
+                    let header = data
+                        .get(8..12)
+                        .ok_or_else(|| anyhow!("Invalid compression header in {section_name}"))?;
+                    u32::from_bytes(header.try_into()?, obj_file.endianness().into()) as u64
+                } else {
+                    section.align()
+                };
+                raw_sections.push(ObjRawSection {
+                    name: section_name.to_string(),
+                    sh_type: SHT_PROGBITS,
+                    sh_flags: sh_flags & !(elf::SHF_COMPRESSED as u64),
+                    align,
+                    data: section.uncompressed_data()?.to_vec(),
+                });
+                section_indexes.push(None);
+                continue;
+            }
+            // Preserve symbol hash tables as-is
+            SectionKind::Metadata | SectionKind::Elf(_)
+                if section_name == ".hash" || section_name == ".gnu.hash" =>
+            {
+                let SectionFlags::Elf { sh_flags } = section.flags() else {
+                    bail!("Unexpected section flags for {section_name}");
+                };
+                raw_sections.push(ObjRawSection {
+                    name: section_name.to_string(),
+                    sh_type: if section_name == ".hash" {
+                        elf::SHT_HASH
+                    } else {
+                        elf::SHT_GNU_HASH
+                    },
+                    sh_flags,
+                    align: section.align(),
+                    data: section.uncompressed_data()?.to_vec(),
+                });
+                section_indexes.push(None);
+                continue;
+            }
             _ => {
                 section_indexes.push(None);
                 continue;
             }
         };
+        let segment = if kind == ObjKind::Executable {
+            segments.iter().position(|&(start, end)| {
+                section.address() >= start && section.address() + section.size() <= end
+            })
+        } else {
+            None
+        };
+        // NOLOAD sections are allocated outside of any loadable segment
+        let load = alloc && (segment.is_some() || segments.is_empty());
+        let file_offset = section.file_range().map(|(v, _)| v);
+        // BSS sections have a size but no file data
+        let data = if section_kind == ObjSectionKind::Bss {
+            vec![]
+        } else {
+            let data = section.uncompressed_data()?;
+            let key = (section_name, file_offset, section.size(), section_kind);
+            let cached =
+                previous_sections.get_mut(&key).filter(|cached| cached.as_slice() == &*data);
+            cached.map_or_else(|| data.to_vec(), |cached| std::mem::take(*cached))
+        };
         section_indexes.push(Some(sections.len()));
         sections.push(ObjSection {
             name: section_name.to_string(),
             kind: section_kind,
             address: section.address(),
             size: section.size(),
-            data: section.uncompressed_data()?.to_vec(),
+            data,
             align: section.align(),
             elf_index: section.index().0 as ObjSectionIndex,
             relocations: Default::default(),
             virtual_address: None, // Loaded from section symbol
-            file_offset: section.file_range().map(|(v, _)| v).unwrap_or_default(),
+            file_offset,
             section_known: true,
+            alloc,
+            load,
             splits: Default::default(),
+            segment,
         });
     }
+    progress(ProgressEvent::SectionsLoaded(sections.len()));
 
     let mw_comment = if let Some(comment_section) = obj_file.section_by_name(".comment") {
         let data = comment_section.uncompressed_data()?;
@@ -115,17 +354,27 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
             let header = MWComment::from_reader(&mut reader, Endian::Big)
                 .context("While reading .comment section")?;
             log::debug!("Loaded .comment section header {:?}", header);
-            let mut comment_syms = Vec::with_capacity(obj_file.symbols().count());
-            comment_syms.push(CommentSym::from_reader(&mut reader, Endian::Big)?); // ELF null symbol
-            for symbol in obj_file.symbols() {
+            // Including the ELF null symbol
+            let symbol_count = obj_file.symbols().count() + 1;
+            let mut comment_syms = Vec::with_capacity(symbol_count);
+            while comment_syms.len() < symbol_count && (reader.position() as usize) < data.len() {
                 let comment_sym = CommentSym::from_reader(&mut reader, Endian::Big)?;
-                log::debug!("Symbol {:?} -> Comment {:?}", symbol, comment_sym);
+                log::debug!("Symbol {} -> Comment {:?}", comment_syms.len(), comment_sym);
                 comment_syms.push(comment_sym);
             }
             ensure!(
                 data.len() - reader.position() as usize == 0,
                 ".comment section data not fully read"
             );
+            // Objects edited by other tools may drop trailing entries; the remaining symbols
+            // are read without alignment info
+            if comment_syms.len() < symbol_count {
+                log::warn!(
+                    ".comment section has {} symbol entries, expected {}",
+                    comment_syms.len(),
+                    symbol_count
+                );
+            }
             Some((header, comment_syms))
         }
     } else {
@@ -152,11 +401,17 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
 
     let mut symbols: Vec<ObjSymbol> = vec![];
     let mut symbol_indexes: Vec<Option<ObjSymbolIndex>> = vec![None /* ELF null symbol */];
-    let mut section_starts = IndexMap::<String, Vec<(u64, String)>>::new();
+    let mut section_starts = IndexMap::<String, Vec<SectionStart>>::new();
     let mut name_to_index = HashMap::<String, usize>::new(); // for resolving duplicate names
     let mut boundary_state = BoundaryState::LookForFile(Default::default());
 
+    let mut symbols_processed = 0usize;
     for symbol in obj_file.symbols() {
+        symbols_processed += 1;
+        if symbols_processed % PROGRESS_INTERVAL == 0 {
+            progress(ProgressEvent::SymbolsProcessed(symbols_processed));
+        }
+
         // Locate linker-generated symbols
         let symbol_name = symbol.name()?;
         match symbol_name {
@@ -174,17 +429,22 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
         // GCC has section symbols first, then file
         match symbol.kind() {
             SymbolKind::File => {
-                let mut file_name = symbol_name.to_string();
+                let mut file_name = if options.strip_file_paths {
+                    file_name_from_path(symbol_name).to_string()
+                } else {
+                    symbol_name.to_string()
+                };
                 // Try to exclude precompiled header symbols
                 // Make configurable eventually
-                if file_name == "Precompiled.cpp"
-                    || file_name == "stdafx.cpp"
-                    || file_name.ends_with(".h")
-                    || file_name.starts_with("Pch.")
-                    || file_name.contains("precompiled_")
-                    || file_name.contains("Precompiled")
-                    || file_name.contains(".pch")
-                    || file_name.contains("_PCH.")
+                if !options.raw
+                    && (file_name == "Precompiled.cpp"
+                        || file_name == "stdafx.cpp"
+                        || file_name.ends_with(".h")
+                        || file_name.starts_with("Pch.")
+                        || file_name.contains("precompiled_")
+                        || file_name.contains("Precompiled")
+                        || file_name.contains(".pch")
+                        || file_name.contains("_PCH."))
                 {
                     symbol_indexes.push(None);
                     continue;
@@ -193,34 +453,45 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
                     obj_name.clone_from(&file_name);
                 }
                 let sections = match section_starts.entry(file_name.clone()) {
+                    indexmap::map::Entry::Occupied(e) if options.raw => e.into_mut(),
                     indexmap::map::Entry::Occupied(_) => {
                         let index = match name_to_index.entry(file_name.clone()) {
                             hash_map::Entry::Occupied(e) => e.into_mut(),
                             hash_map::Entry::Vacant(e) => e.insert(0),
                         };
-                        *index += 1;
-                        let new_name = format!("{}_{}", file_name, index);
+                        // Skip suffixes taken by other files
+                        let new_name = loop {
+                            *index += 1;
+                            let new_name = format!("{}_{}", file_name, index);
+                            if !section_starts.contains_key(&new_name) {
+                                break new_name;
+                            }
+                        };
                         // log::info!("Renaming {} to {}", file_name, new_name);
                         file_name.clone_from(&new_name);
-                        match section_starts.entry(new_name.clone()) {
-                            indexmap::map::Entry::Occupied(_) => {
-                                bail!("Duplicate filename '{}'", new_name)
-                            }
-                            indexmap::map::Entry::Vacant(e) => e.insert(Default::default()),
-                        }
+                        section_starts.entry(new_name).or_default()
                     }
                     indexmap::map::Entry::Vacant(e) => e.insert(Default::default()),
                 };
                 match &mut boundary_state {
                     BoundaryState::LookForFile(queue) => {
+                        report(BoundaryEvent::FileStarted(file_name.clone()));
                         if queue.is_empty() {
                             boundary_state = BoundaryState::LookForSections(file_name);
                         } else {
+                            for (address, section, _) in queue.iter() {
+                                report(BoundaryEvent::SectionAttributed {
+                                    section: section.clone(),
+                                    address: *address,
+                                    file: file_name.clone(),
+                                });
+                            }
                             // Clears queue
                             sections.append(queue);
                         }
                     }
                     BoundaryState::LookForSections(_) => {
+                        report(BoundaryEvent::FileStarted(file_name.clone()));
                         boundary_state = BoundaryState::LookForSections(file_name);
                     }
                     BoundaryState::FilesEnded => {
@@ -246,16 +517,22 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
 
                 let section = obj_file.section_by_index(section_index)?;
                 let section_name = section.name()?.to_string();
+                let out_section_index = section_indexes[section_index.0];
                 match &mut boundary_state {
                     BoundaryState::LookForFile(queue) => {
-                        queue.push((symbol.address(), section_name));
+                        queue.push((symbol.address(), section_name, out_section_index));
                     }
                     BoundaryState::LookForSections(file_name) => {
-                        if section_indexes[section_index.0].is_some() {
+                        if out_section_index.is_some() {
                             let sections = section_starts
                                 .get_mut(file_name)
                                 .ok_or_else(|| anyhow!("Failed to create entry"))?;
-                            sections.push((symbol.address(), section_name));
+                            report(BoundaryEvent::SectionAttributed {
+                                section: section_name.clone(),
+                                address: symbol.address(),
+                                file: file_name.clone(),
+                            });
+                            sections.push((symbol.address(), section_name, out_section_index));
                         }
                     }
                     BoundaryState::FilesEnded => {
@@ -268,30 +545,47 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
                 }
             }
             _ => match symbol.section() {
+                SymbolSection::Absolute if options.raw => {}
                 // Linker generated symbols indicate the end
                 SymbolSection::Absolute => {
+                    if !matches!(boundary_state, BoundaryState::FilesEnded) {
+                        report(BoundaryEvent::FilesEnded(symbol_name.to_string()));
+                    }
                     boundary_state = BoundaryState::FilesEnded;
                 }
                 SymbolSection::Section(section_index) => match &mut boundary_state {
                     BoundaryState::LookForFile(_) => {}
                     BoundaryState::LookForSections(file_name) => {
-                        if section_indexes[section_index.0].is_some() {
+                        let out_section_index = section_indexes[section_index.0];
+                        if out_section_index.is_some() {
                             let sections = section_starts
                                 .get_mut(file_name)
                                 .ok_or_else(|| anyhow!("Failed to create entry"))?;
                             let section = obj_file.section_by_index(section_index)?;
                             let section_name = section.name()?;
-                            if let Some((addr, _)) = sections
+                            // Match by section index, since names may not be unique
+                            // (e.g. multiple .text sections from overlays)
+                            if let Some((addr, _, _)) = sections
                                 .iter_mut()
-                                .find(|(addr, name)| *addr == 0 && name == section_name)
+                                .find(|(addr, _, idx)| *addr == 0 && *idx == out_section_index)
                             {
                                 // If the section symbol had address 0, determine address
                                 // from first symbol within that section.
                                 *addr = symbol.address();
-                            } else if !sections.iter().any(|(_, name)| name == section_name) {
+                            } else if !sections.iter().any(|(_, _, idx)| *idx == out_section_index)
+                            {
                                 // Otherwise, if there was no section symbol, assume this
                                 // symbol indicates the section address.
-                                sections.push((symbol.address(), section_name.to_string()));
+                                report(BoundaryEvent::SectionAttributed {
+                                    section: section_name.to_string(),
+                                    address: symbol.address(),
+                                    file: file_name.clone(),
+                                });
+                                sections.push((
+                                    symbol.address(),
+                                    section_name.to_string(),
+                                    out_section_index,
+                                ));
                             }
                         }
                     }
@@ -302,17 +596,22 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
             },
         }
 
-        // Generate symbols
-        if matches!(symbol.kind(), SymbolKind::File)
-            || matches!(symbol.section_index(), Some(idx) if section_indexes[idx.0].is_none())
+        // Generate symbols. Raw reads keep every symbol at its original index.
+        if !options.raw
+            && (matches!(symbol.kind(), SymbolKind::File)
+                || matches!(symbol.section_index(), Some(idx) if section_indexes[idx.0].is_none()))
         {
             symbol_indexes.push(None);
             continue;
         }
         symbol_indexes.push(Some(symbols.len() as ObjSymbolIndex));
-        let align = mw_comment.as_ref().map(|(_, vec)| vec[symbol.index().0].align);
+        let align = mw_comment
+            .as_ref()
+            .and_then(|(_, vec)| vec.get(symbol.index().0))
+            .map(|comment_sym| comment_sym.align);
         symbols.push(to_obj_symbol(&obj_file, &symbol, &section_indexes, align)?);
     }
+    progress(ProgressEvent::SymbolsProcessed(symbols_processed));
 
     let mut link_order = Vec::<ObjUnit>::new();
     if kind == ObjKind::Executable {
@@ -326,10 +625,31 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
             });
         }
 
+        // Overlays can cause allocated sections to overlap, in which case
+        // split placement by address alone is ambiguous
+        for (a, b) in overlapping_sections(&sections) {
+            let (a, b) = (&sections[a as usize], &sections[b as usize]);
+            log::warn!(
+                "Section {} ({:#010X}-{:#010X}) overlaps section {} ({:#010X}-{:#010X})",
+                a.name,
+                a.address,
+                a.address + a.size,
+                b.name,
+                b.address,
+                b.address + b.size
+            );
+        }
+
         // Create a map of address -> file splits
         for (file_name, section_addrs) in section_starts {
-            for (address, _) in section_addrs {
-                let Some(section) = sections.iter_mut().find(|s| s.contains(address as u32)) else {
+            for (address, _, section_index) in section_addrs {
+                // Prefer the section the symbol was defined in, falling back to
+                // searching by address
+                let section = match section_index {
+                    Some(idx) if sections[idx].contains(address as u32) => Some(&mut sections[idx]),
+                    _ => sections.iter_mut().find(|s| s.contains(address as u32)),
+                };
+                let Some(section) = section else {
                     log::warn!(
                         "Failed to find section containing address {:#010X} in file {}",
                         address,
@@ -352,6 +672,7 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
         // TODO rebuild common symbols
     }
 
+    let mut relocations_processed = 0usize;
     for section in obj_file.sections() {
         let out_section =
             match section_indexes[section.index().0].and_then(|idx| sections.get_mut(idx)) {
@@ -359,20 +680,56 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
                 None => continue,
             };
         // Generate relocations
-        for (address, reloc) in section.relocations() {
+        let relocations = section.relocations().collect::<Vec<_>>();
+        let pairs = find_implicit_pairs(&relocations);
+        for ((address, reloc), pair) in relocations.into_iter().zip(pairs) {
+            relocations_processed += 1;
+            if relocations_processed % PROGRESS_INTERVAL == 0 {
+                progress(ProgressEvent::RelocationsProcessed(relocations_processed));
+            }
             let Some(reloc) =
-                to_obj_reloc(&obj_file, &symbol_indexes, &out_section.data, address, reloc)?
+                to_obj_reloc(&obj_file, kind, &symbol_indexes, out_section, address, reloc, pair)?
             else {
                 continue;
             };
             out_section.relocations.insert(address as u32, reloc)?;
         }
     }
+    progress(ProgressEvent::RelocationsProcessed(relocations_processed));
+
+    // GCC objects may omit the file symbol, fall back to the DWARF compilation unit name
+    if obj_name.is_empty() && kind == ObjKind::Relocatable {
+        match read_dwarf_unit_name(&obj_file) {
+            Ok(Some(name)) => obj_name = name,
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to read DWARF compilation unit name: {e:?}"),
+        }
+    }
 
     let mut obj = ObjInfo::new(kind, architecture, obj_name, symbols, sections);
+    obj.endian = obj_file.endianness().into();
     obj.entry = NonZeroU64::new(obj_file.entry()).map(|n| n.get());
+    if let FileFlags::Elf { e_flags, .. } = obj_file.flags() {
+        obj.e_flags = Some(e_flags);
+    }
+    if kind == ObjKind::Relocatable {
+        obj.start_address = ["__start", "_start"].iter().find_map(|name| {
+            obj.symbols.for_name(name).find_map(|(_, symbol)| {
+                symbol.section.map(|section| SectionAddress::new(section, symbol.address as u32))
+            })
+        });
+    }
+    let runtime_symbols = RUNTIME_SYMBOLS.iter().copied();
+    for name in runtime_symbols.chain(options.runtime_symbols.iter().map(String::as_str)) {
+        if let Some(address) = obj.symbols.for_name(name).find_map(|(_, symbol)| {
+            symbol.section.map(|section| SectionAddress::new(section, symbol.address as u32))
+        }) {
+            obj.entry_chain.push((name.to_string(), address));
+        }
+    }
     obj.mw_comment = mw_comment.map(|(header, _)| header);
     obj.split_meta = split_meta;
+    obj.raw_sections = raw_sections;
     obj.sda2_base = sda2_base;
     obj.sda_base = sda_base;
     obj.stack_address = stack_address;
@@ -384,9 +741,169 @@ pub fn process_elf(path: &Utf8NativePath) -> Result<ObjInfo> {
     Ok(obj)
 }
 
+/// Symbol table ordering used by [`write_elf_sorted`]. Local symbols are always
+/// written before global symbols; the ordering applies within each group.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum SymbolSort {
+    /// Keep the order of the input [`ObjInfo`].
+    #[default]
+    Preserve,
+    /// Order by section, address, then name.
+    ByAddress,
+    /// Order by name, section, then address.
+    ByName,
+}
+
+/// Options for [`write_elf_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct WriteElfOptions {
+    /// Mark all symbols as exported in the `.comment` section.
+    pub export_all: bool,
+    /// Symbol table ordering.
+    pub sort: SymbolSort,
+    /// Generate a SysV `.hash` table for exported symbols. Ignored if the object
+    /// has no exported symbols. A `.hash` section read from the input is always
+    /// regenerated, since it indexes the symbol table being written. A `.gnu.hash`
+    /// section read from the input is dropped.
+    pub hash_table: bool,
+    /// Glob patterns of passthrough section names (e.g. `.debug*`) to zlib compress
+    /// with `SHF_COMPRESSED`. Allocated sections are never compressed.
+    pub compress_sections: Vec<String>,
+    /// Order of the section headers, as indices into [`ObjInfo::sections`]. Must list
+    /// every section exactly once. If empty, sections are written in their natural order.
+    pub section_order: Vec<ObjSectionIndex>,
+}
+
 pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
+    write_elf_sorted(obj, export_all, SymbolSort::Preserve)
+}
+
+/// Writes the object directly to `out` without buffering the entire output in memory.
+pub fn write_elf_to<W>(obj: &ObjInfo, export_all: bool, out: W) -> Result<()>
+where W: Write {
+    let mut buffer = StreamingBuffer::new(out);
+    let options = WriteElfOptions { export_all, ..Default::default() };
+    write_elf_inner(obj, &options, &mut buffer, false)?;
+    buffer.result()?;
+    Ok(())
+}
+
+pub fn write_elf_sorted(obj: &ObjInfo, export_all: bool, sort: SymbolSort) -> Result<Vec<u8>> {
+    write_elf_with_options(obj, &WriteElfOptions { export_all, sort, ..Default::default() })
+}
+
+pub fn write_elf_with_options(obj: &ObjInfo, options: &WriteElfOptions) -> Result<Vec<u8>> {
     let mut out_data = Vec::new();
-    let mut writer = Writer::new(Endianness::Big, false, &mut out_data);
+    write_elf_inner(obj, options, &mut out_data, false)?;
+    Ok(out_data)
+}
+
+fn to_elf_symbol(
+    symbol: &ObjSymbol,
+    section_index: Option<SectionIndex>,
+    name_index: Option<StringId>,
+) -> object::write::elf::Sym {
+    object::write::elf::Sym {
+        name: name_index,
+        section: section_index,
+        st_info: {
+            let st_type = match symbol.kind {
+                ObjSymbolKind::Unknown => elf::STT_NOTYPE,
+                ObjSymbolKind::Function => elf::STT_FUNC,
+                ObjSymbolKind::Object if symbol.flags.is_tls() => elf::STT_TLS,
+                ObjSymbolKind::Object => elf::STT_OBJECT,
+                ObjSymbolKind::Section => elf::STT_SECTION,
+                ObjSymbolKind::File => elf::STT_FILE,
+            };
+            let st_bind = if symbol.flags.is_weak() {
+                elf::STB_WEAK
+            } else if symbol.flags.is_local() {
+                elf::STB_LOCAL
+            } else {
+                elf::STB_GLOBAL
+            };
+            (st_bind << 4) + st_type
+        },
+        st_other: (symbol.st_other & !3)
+            | if symbol.flags.is_hidden() { elf::STV_HIDDEN } else { elf::STV_DEFAULT },
+        st_shndx: if section_index.is_some() {
+            0
+        } else if symbol.flags.is_common() {
+            elf::SHN_COMMON
+        } else if symbol.address != 0 || symbol.kind == ObjSymbolKind::File {
+            elf::SHN_ABS
+        } else {
+            elf::SHN_UNDEF
+        },
+        st_value: symbol.address,
+        st_size: symbol.size,
+    }
+}
+
+/// Compresses section data with zlib, prefixed with an `Elf32_Chdr`.
+fn compress_section_data(data: &[u8], align: u64, e: Endian) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(12 + data.len() / 2);
+    elf::ELFCOMPRESS_ZLIB.to_writer(&mut out, e)?;
+    (data.len() as u32).to_writer(&mut out, e)?;
+    (align as u32).to_writer(&mut out, e)?;
+    let mut encoder = flate2::write::ZlibEncoder::new(out, flate2::Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Computes the size of the output of [`write_elf`] without writing any data.
+pub fn write_elf_size(obj: &ObjInfo) -> Result<usize> {
+    write_elf_inner(obj, &WriteElfOptions::default(), &mut Vec::<u8>::new(), true)
+}
+
+/// Writes the object to `out`, returning the output size. If `size_only` is set,
+/// stops after reserving space and nothing is written.
+fn write_elf_inner(
+    obj: &ObjInfo,
+    options: &WriteElfOptions,
+    out: &mut dyn WritableBuffer,
+    size_only: bool,
+) -> Result<usize> {
+    for (section_index, section) in obj.sections.iter() {
+        // Some linkers require strictly ascending r_offset
+        let mut last_offset = None;
+        for (addr, reloc) in section.relocations.iter() {
+            validate_reloc_addend(obj, section_index, addr, reloc)?;
+            let (r_offset, _) = reloc.to_elf(addr);
+            if let Some(last_offset) = last_offset {
+                ensure!(
+                    r_offset > last_offset,
+                    "Relocation {:?} @ {}:{:#010X} collides with previous relocation (offset {:#X})",
+                    reloc.kind,
+                    section.name,
+                    addr,
+                    r_offset
+                );
+            }
+            last_offset = Some(r_offset);
+        }
+    }
+
+    let section_order = if options.section_order.is_empty() {
+        obj.sections.iter().map(|(index, _)| index).collect::<Vec<_>>()
+    } else {
+        let mut seen = vec![false; obj.sections.len() as usize];
+        for &index in &options.section_order {
+            let entry = seen
+                .get_mut(index as usize)
+                .ok_or_else(|| anyhow!("Invalid section index {} in section order", index))?;
+            ensure!(!*entry, "Section index {} repeated in section order", index);
+            *entry = true;
+        }
+        ensure!(
+            seen.iter().all(|&b| b),
+            "Section order must include all {} sections",
+            obj.sections.len()
+        );
+        options.section_order.clone()
+    };
+
+    let mut writer = Writer::new(obj.endian.into(), false, out);
 
     struct OutSection {
         index: SectionIndex,
@@ -397,17 +914,15 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         rela_name: Option<StringId>,
         virtual_address: Option<u64>,
     }
-    struct OutSymbol {
-        #[allow(dead_code)]
-        index: SymbolIndex,
-        sym: object::write::elf::Sym,
-    }
 
     writer.reserve_null_section_index();
+    let mut section_indexes = vec![SectionIndex(0); obj.sections.len() as usize];
+    for &section_index in &section_order {
+        section_indexes[section_index as usize] = writer.reserve_section_index();
+    }
     let mut out_sections: Vec<OutSection> = Vec::with_capacity(obj.sections.len() as usize);
-    for (_, section) in obj.sections.iter() {
+    for ((_, section), &index) in obj.sections.iter().zip(&section_indexes) {
         let name = writer.add_section_name(section.name.as_bytes());
-        let index = writer.reserve_section_index();
         out_sections.push(OutSection {
             index,
             rela_index: None,
@@ -420,13 +935,17 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
     }
 
     let mut rela_names: Vec<String> = vec![Default::default(); obj.sections.len() as usize];
-    for (((_, section), out_section), rela_name) in
-        obj.sections.iter().zip(&mut out_sections).zip(&mut rela_names)
-    {
-        if section.relocations.is_empty() {
+    for ((_, section), rela_name) in obj.sections.iter().zip(&mut rela_names) {
+        if !section.relocations.is_empty() {
+            *rela_name = format!(".rela{}", section.name);
+        }
+    }
+    for &section_index in &section_order {
+        let rela_name = &rela_names[section_index as usize];
+        if rela_name.is_empty() {
             continue;
         }
-        *rela_name = format!(".rela{}", section.name);
+        let out_section = &mut out_sections[section_index as usize];
         out_section.rela_name = Some(writer.add_section_name(rela_name.as_bytes()));
         out_section.rela_index = Some(writer.reserve_section_index());
     }
@@ -488,38 +1007,62 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         None
     };
 
-    let mut out_symbols: Vec<OutSymbol> = Vec::with_capacity(obj.symbols.count() as usize);
-    let mut symbol_map = vec![None; obj.symbols.count() as usize];
-    let mut section_symbol_offset = 0;
+    // Reserve passthrough sections
+    let compress_patterns = options
+        .compress_sections
+        .iter()
+        .map(|pattern| glob::Pattern::new(pattern))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Hash tables index the symbol table: SysV hash tables are always regenerated, and
+    // GNU hash tables, which require a hash-ordered symbol table, are dropped
+    let raw_sections = obj
+        .raw_sections
+        .iter()
+        .filter(|s| s.sh_type != elf::SHT_HASH && s.sh_type != elf::SHT_GNU_HASH)
+        .collect::<Vec<_>>();
+    let mut raw_out_sections = Vec::with_capacity(raw_sections.len());
+    for section in &raw_sections {
+        let name = writer.add_section_name(section.name.as_bytes());
+        writer.reserve_section_index();
+        let compressed = if section.sh_flags & SHF_ALLOC as u64 == 0
+            && compress_patterns.iter().any(|p| p.matches(&section.name))
+        {
+            Some(compress_section_data(&section.data, section.align, obj.endian)?)
+        } else {
+            None
+        };
+        raw_out_sections.push((name, 0usize, compressed));
+    }
+
+    // Reserve .hash section
+    let mut hash_section = if (options.hash_table
+        || obj.raw_sections.iter().any(|s| s.sh_type == elf::SHT_HASH))
+        && obj.symbols.iter().any(|(_, s)| is_exported_symbol(s))
+    {
+        let name = writer.add_section_name(".hash".as_bytes());
+        writer.reserve_section_index();
+        Some((name, 0usize, Vec::<u32>::new()))
+    } else {
+        None
+    };
+    let mut exported_symbols = Vec::new();
+
+    // Symbols are reserved here and written in a second pass, so only the output index
+    // and name of each symbol is retained in between.
+    let mut symbol_map: Vec<Option<(u32, Option<StringId>)>> =
+        vec![None; obj.symbols.count() as usize];
+    let mut section_symbol_indexes = Vec::with_capacity(obj.sections.len() as usize);
     let mut num_local = 0;
 
     // Add file symbol
     let obj_name;
+    let mut file_name_index = None;
     if !obj.name.is_empty() {
         // Only write filename
-        obj_name = Path::new(&obj.name)
-            .file_name()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_else(|| obj.name.clone());
-
-        let name_index = writer.add_string(obj_name.as_bytes());
-        let index = writer.reserve_symbol_index(None);
-        out_symbols.push(OutSymbol {
-            index,
-            sym: object::write::elf::Sym {
-                name: Some(name_index),
-                section: None,
-                st_info: {
-                    let st_type = elf::STT_FILE;
-                    let st_bind = elf::STB_LOCAL;
-                    (st_bind << 4) + st_type
-                },
-                st_other: elf::STV_DEFAULT,
-                st_shndx: elf::SHN_ABS,
-                st_value: 0,
-                st_size: 0,
-            },
-        });
+        obj_name = file_name_from_path(&obj.name).to_string();
+
+        file_name_index = Some(writer.add_string(obj_name.as_bytes()));
+        writer.reserve_symbol_index(None);
         if let Some((comment_data, _)) = &mut comment_data {
             CommentSym { align: 1, vis_flags: 0, active_flags: 0 }
                 .to_writer_static(comment_data, Endian::Big)?;
@@ -529,7 +1072,6 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         {
             virtual_addresses.push(0);
         }
-        section_symbol_offset += 1;
     }
 
     // Add section symbols for relocatable objects
@@ -537,17 +1079,8 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         for (section_index, section) in obj.sections.iter() {
             let out_section_index = out_sections.get(section_index as usize).map(|s| s.index);
             let index = writer.reserve_symbol_index(out_section_index);
-            let sym = object::write::elf::Sym {
-                name: None,
-                section: out_section_index,
-                st_info: (elf::STB_LOCAL << 4) + elf::STT_SECTION,
-                st_other: elf::STV_DEFAULT,
-                st_shndx: 0,
-                st_value: 0,
-                st_size: 0,
-            };
+            section_symbol_indexes.push(index.0);
             num_local = writer.symbol_count();
-            out_symbols.push(OutSymbol { index, sym });
             if let Some((comment_data, _)) = &mut comment_data {
                 CommentSym { align: section.align as u32, vis_flags: 0, active_flags: 0 }
                     .to_writer_static(comment_data, Endian::Big)?;
@@ -561,18 +1094,34 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
     }
 
     // Add symbols, starting with local symbols
-    for (symbol_index, symbol) in obj
-        .symbols
-        .iter()
-        .filter(|&(_, s)| s.flags.is_local())
-        .chain(obj.symbols.iter().filter(|&(_, s)| !s.flags.is_local()))
-    {
+    let symbols = match options.sort {
+        SymbolSort::Preserve => obj.symbols.iter().collect::<Vec<_>>(),
+        SymbolSort::ByAddress => obj
+            .sorted_symbol_order()
+            .into_iter()
+            .map(|index| (index, &obj.symbols[index]))
+            .collect(),
+        SymbolSort::ByName => {
+            let mut symbols = obj.symbols.iter().collect::<Vec<_>>();
+            symbols.sort_by(|(_, a), (_, b)| {
+                (&a.name, a.section, a.address).cmp(&(&b.name, b.section, b.address))
+            });
+            symbols
+        }
+    };
+    let (local_symbols, mut global_symbols): (Vec<_>, Vec<_>) =
+        symbols.into_iter().partition(|&(_, s)| s.flags.is_local());
+    let mut ordered_symbols = local_symbols;
+    ordered_symbols.append(&mut global_symbols);
+    for &(symbol_index, symbol) in &ordered_symbols {
         if obj.kind == ObjKind::Relocatable && symbol.kind == ObjSymbolKind::Section {
             // We wrote section symbols above, so skip them here
             let section_index =
                 symbol.section.ok_or_else(|| anyhow!("section symbol without section index"))?;
-            symbol_map[symbol_index as usize] =
-                Some(section_symbol_offset as ObjSectionIndex + section_index);
+            let index = *section_symbol_indexes
+                .get(section_index as usize)
+                .ok_or_else(|| anyhow!("Invalid section symbol index {}", section_index))?;
+            symbol_map[symbol_index as usize] = Some((index, None));
             continue;
         }
 
@@ -584,45 +1133,16 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         } else {
             Some(writer.add_string(symbol.name.as_bytes()))
         };
-        let sym = object::write::elf::Sym {
-            name: name_index,
-            section: section_index,
-            st_info: {
-                let st_type = match symbol.kind {
-                    ObjSymbolKind::Unknown => elf::STT_NOTYPE,
-                    ObjSymbolKind::Function => elf::STT_FUNC,
-                    ObjSymbolKind::Object => elf::STT_OBJECT,
-                    ObjSymbolKind::Section => elf::STT_SECTION,
-                };
-                let st_bind = if symbol.flags.is_weak() {
-                    elf::STB_WEAK
-                } else if symbol.flags.is_local() {
-                    elf::STB_LOCAL
-                } else {
-                    elf::STB_GLOBAL
-                };
-                (st_bind << 4) + st_type
-            },
-            st_other: if symbol.flags.is_hidden() { elf::STV_HIDDEN } else { elf::STV_DEFAULT },
-            st_shndx: if section_index.is_some() {
-                0
-            } else if symbol.flags.is_common() {
-                elf::SHN_COMMON
-            } else if symbol.address != 0 {
-                elf::SHN_ABS
-            } else {
-                elf::SHN_UNDEF
-            },
-            st_value: symbol.address,
-            st_size: symbol.size,
-        };
-        if sym.st_info >> 4 == elf::STB_LOCAL {
+        if to_elf_symbol(symbol, section_index, name_index).st_info >> 4 == elf::STB_LOCAL {
             num_local = writer.symbol_count();
         }
-        out_symbols.push(OutSymbol { index, sym });
-        symbol_map[symbol_index as usize] = Some(index.0);
+        symbol_map[symbol_index as usize] = Some((index.0, name_index));
+        if hash_section.is_some() && is_exported_symbol(symbol) {
+            exported_symbols.push((index.0, symbol.name.as_str()));
+        }
         if let Some((comment_data, _)) = &mut comment_data {
-            CommentSym::from(symbol, export_all).to_writer_static(comment_data, Endian::Big)?;
+            CommentSym::from(symbol, options.export_all)
+                .to_writer_static(comment_data, Endian::Big)?;
         }
         if let Some(virtual_addresses) =
             split_meta.as_mut().and_then(|(m, _)| m.virtual_addresses.as_mut())
@@ -637,23 +1157,65 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
 
     writer.reserve_file_header();
 
+    // Empty sections don't get a program header. Consecutive sections read from the same
+    // loadable segment share one, otherwise each section gets its own.
+    let mut segments: Vec<Vec<ObjSectionIndex>> = vec![];
     if obj.kind == ObjKind::Executable {
-        writer.reserve_program_headers(obj.sections.len());
+        let mut last: Option<&ObjSection> = None;
+        for (section_index, section) in obj.sections.iter() {
+            if section.size == 0 || !section.load {
+                continue;
+            }
+            match (segments.last_mut(), last) {
+                (Some(segment), Some(last))
+                    if section.segment.is_some()
+                        && section.segment == last.segment
+                        // File data can't follow BSS within a segment
+                        && (last.kind != ObjSectionKind::Bss
+                            || section.kind == ObjSectionKind::Bss) =>
+                {
+                    segment.push(section_index)
+                }
+                _ => segments.push(vec![section_index]),
+            }
+            last = Some(section);
+        }
+        writer.reserve_program_headers(segments.len() as u32);
+    }
+    // The first section of the segment, for sections following it in a shared segment
+    let mut segment_starts: Vec<Option<ObjSectionIndex>> = vec![None; obj.sections.len() as usize];
+    for segment in &segments {
+        for &section_index in &segment[1..] {
+            segment_starts[section_index as usize] = Some(segment[0]);
+        }
     }
 
-    for ((_, section), out_section) in obj.sections.iter().zip(&mut out_sections) {
+    for (section_index, section) in obj.sections.iter() {
         if section.kind == ObjSectionKind::Bss {
             continue;
         }
         ensure!(section.data.len() as u64 == section.size);
-        if section.size == 0 {
+        let offset = if let Some(start_index) = segment_starts[section_index as usize] {
+            // Sections sharing a segment keep their relative layout in the file
+            let start = &obj.sections[start_index];
+            let offset = out_sections[start_index as usize].offset
+                + (section.address - start.address) as usize;
+            ensure!(
+                offset >= writer.reserved_len(),
+                "Section {} overlaps the previous section in its segment",
+                section.name
+            );
+            writer.reserve_until(offset);
+            writer.reserve(section.data.len(), 1)
+        } else if section.size == 0 {
             // Bug in Writer::reserve doesn't align when len is 0
             let offset = (writer.reserved_len() + 31) & !31;
             writer.reserve_until(offset);
-            out_section.offset = offset;
+            offset
         } else {
-            out_section.offset = writer.reserve(section.data.len(), 32);
-        }
+            writer.reserve(section.data.len(), 32)
+        };
+        out_sections[section_index as usize].offset = offset;
     }
 
     for ((_, section), out_section) in obj.sections.iter().zip(&mut out_sections) {
@@ -663,7 +1225,13 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         out_section.rela_offset = writer.reserve_relocations(section.relocations.len(), true);
     }
 
+    // Symbols in sections past SHN_LORESERVE store their index in .symtab_shndx
+    if writer.symtab_shndx_needed() {
+        writer.reserve_symtab_shndx_section_index();
+    }
+
     writer.reserve_symtab();
+    writer.reserve_symtab_shndx();
     writer.reserve_strtab();
     writer.reserve_shstrtab();
 
@@ -679,7 +1247,23 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         out_section.offset = writer.reserve(metadata.write_size(false), 32);
     }
 
+    for (section, (_, offset, compressed)) in raw_sections.iter().zip(&mut raw_out_sections) {
+        *offset = match compressed {
+            Some(data) => writer.reserve(data.len(), 4),
+            None => writer.reserve(section.data.len(), section.align.max(1) as usize),
+        };
+    }
+
+    // Reserve .hash section
+    if let Some((_, offset, words)) = &mut hash_section {
+        *words = build_sysv_hash(writer.symbol_count(), &exported_symbols);
+        *offset = writer.reserve(words.len() * 4, 4);
+    }
+
     writer.reserve_section_headers();
+    if size_only {
+        return Ok(writer.reserved_len());
+    }
 
     writer.write_file_header(&object::write::elf::FileHeader {
         os_abi: elf::ELFOSABI_SYSV,
@@ -690,27 +1274,37 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         },
         e_machine: elf::EM_PPC,
         e_entry: obj.entry.unwrap_or(0),
-        e_flags: elf::EF_PPC_EMB,
+        e_flags: obj.e_flags.unwrap_or(elf::EF_PPC_EMB),
     })?;
 
     if obj.kind == ObjKind::Executable {
         writer.write_align_program_headers();
-        for ((_, section), out_section) in obj.sections.iter().zip(&out_sections) {
-            writer.write_program_header(&ProgramHeader {
-                p_type: elf::PT_LOAD,
-                p_flags: match section.kind {
+        for segment in &segments {
+            let start = &obj.sections[segment[0]];
+            let mut p_flags = 0;
+            let mut p_filesz = 0;
+            let mut p_memsz = 0;
+            for &section_index in segment {
+                let section = &obj.sections[section_index];
+                p_flags |= match section.kind {
                     ObjSectionKind::Code => elf::PF_R | elf::PF_X,
                     ObjSectionKind::Data | ObjSectionKind::Bss => elf::PF_R | elf::PF_W,
                     ObjSectionKind::ReadOnlyData => elf::PF_R,
-                },
-                p_offset: out_section.offset as u64,
-                p_vaddr: section.address,
+                };
+                let end = section.address + section.size - start.address;
+                if section.kind != ObjSectionKind::Bss {
+                    p_filesz = end;
+                }
+                p_memsz = end;
+            }
+            writer.write_program_header(&ProgramHeader {
+                p_type: elf::PT_LOAD,
+                p_flags,
+                p_offset: out_sections[segment[0] as usize].offset as u64,
+                p_vaddr: start.address,
                 p_paddr: 0,
-                p_filesz: match section.kind {
-                    ObjSectionKind::Bss => 0,
-                    _ => section.size,
-                },
-                p_memsz: section.size,
+                p_filesz,
+                p_memsz,
                 p_align: 32,
             });
         }
@@ -720,10 +1314,10 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         if section.kind == ObjSectionKind::Bss {
             continue;
         }
-        writer.write_align(32);
-        ensure!(writer.len() == out_section.offset);
+        ensure!(writer.len() <= out_section.offset);
+        writer.pad_until(out_section.offset);
         if obj.kind == ObjKind::Relocatable {
-            write_relocatable_section_data(&mut writer, section)?;
+            write_relocatable_section_data(&mut writer, section, obj.endian)?;
         } else {
             writer.write(&section.data);
         }
@@ -737,17 +1331,52 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         ensure!(writer.len() == out_section.rela_offset);
         for (addr, reloc) in section.relocations.iter() {
             let (r_offset, r_type) = reloc.to_elf(addr);
-            let r_sym = symbol_map[reloc.target_symbol as usize]
+            let (r_sym, _) = symbol_map[reloc.target_symbol as usize]
                 .ok_or_else(|| anyhow!("Relocation against stripped symbol"))?;
             writer.write_relocation(true, &Rel { r_offset, r_sym, r_type, r_addend: reloc.addend });
         }
     }
 
     writer.write_null_symbol();
-    for out_symbol in &out_symbols {
-        writer.write_symbol(&out_symbol.sym);
+    if let Some(name_index) = file_name_index {
+        writer.write_symbol(&object::write::elf::Sym {
+            name: Some(name_index),
+            section: None,
+            st_info: {
+                let st_type = elf::STT_FILE;
+                let st_bind = elf::STB_LOCAL;
+                (st_bind << 4) + st_type
+            },
+            st_other: elf::STV_DEFAULT,
+            st_shndx: elf::SHN_ABS,
+            st_value: 0,
+            st_size: 0,
+        });
+    }
+    if obj.kind == ObjKind::Relocatable {
+        for (section_index, _) in obj.sections.iter() {
+            writer.write_symbol(&object::write::elf::Sym {
+                name: None,
+                section: out_sections.get(section_index as usize).map(|s| s.index),
+                st_info: (elf::STB_LOCAL << 4) + elf::STT_SECTION,
+                st_other: elf::STV_DEFAULT,
+                st_shndx: 0,
+                st_value: 0,
+                st_size: 0,
+            });
+        }
+    }
+    for &(symbol_index, symbol) in &ordered_symbols {
+        if obj.kind == ObjKind::Relocatable && symbol.kind == ObjSymbolKind::Section {
+            continue;
+        }
+        let section_index =
+            symbol.section.and_then(|idx| out_sections.get(idx as usize)).map(|s| s.index);
+        let name_index = symbol_map[symbol_index as usize].and_then(|(_, name)| name);
+        writer.write_symbol(&to_elf_symbol(symbol, section_index, name_index));
     }
 
+    writer.write_symtab_shndx();
     writer.write_strtab();
     writer.write_shstrtab();
 
@@ -766,12 +1395,38 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         ensure!(writer.len() == out_section.offset);
         // object::write::elf::Writer doesn't implement std::io::Write...
         let mut data = Vec::with_capacity(metadata.write_size(false));
-        metadata.to_writer(&mut data, object::BigEndian, false)?;
+        metadata.to_writer(&mut data, Endianness::from(obj.endian), false)?;
+        writer.write(&data);
+    }
+
+    // Write passthrough sections
+    for (section, (_, offset, compressed)) in raw_sections.iter().zip(&raw_out_sections) {
+        if let Some(data) = compressed {
+            writer.write_align(4);
+            ensure!(writer.len() == *offset);
+            writer.write(data);
+        } else {
+            writer.write_align(section.align.max(1) as usize);
+            ensure!(writer.len() == *offset);
+            writer.write(&section.data);
+        }
+    }
+
+    // Write .hash section
+    if let Some((_, offset, words)) = &hash_section {
+        writer.write_align(4);
+        ensure!(writer.len() == *offset);
+        let mut data = Vec::with_capacity(words.len() * 4);
+        for word in words {
+            word.to_writer(&mut data, obj.endian)?;
+        }
         writer.write(&data);
     }
 
     writer.write_null_section_header();
-    for ((_, section), out_section) in obj.sections.iter().zip(&out_sections) {
+    for &section_index in &section_order {
+        let section = &obj.sections[section_index];
+        let out_section = &out_sections[section_index as usize];
         writer.write_section_header(&SectionHeader {
             name: Some(out_section.name),
             sh_type: match section.kind {
@@ -780,11 +1435,18 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
                 }
                 ObjSectionKind::Bss => SHT_NOBITS,
             },
-            sh_flags: match section.kind {
-                ObjSectionKind::Code => SHF_ALLOC | SHF_EXECINSTR,
-                ObjSectionKind::Data | ObjSectionKind::Bss => SHF_ALLOC | SHF_WRITE,
-                ObjSectionKind::ReadOnlyData => SHF_ALLOC,
-            } as u64,
+            sh_flags: {
+                let flags = match section.kind {
+                    ObjSectionKind::Code => SHF_ALLOC | SHF_EXECINSTR,
+                    ObjSectionKind::Data | ObjSectionKind::Bss => SHF_ALLOC | SHF_WRITE,
+                    ObjSectionKind::ReadOnlyData => SHF_ALLOC,
+                };
+                if section.alloc {
+                    flags as u64
+                } else {
+                    (flags & !SHF_ALLOC) as u64
+                }
+            },
             sh_addr: section.address,
             sh_offset: out_section.offset as u64,
             sh_size: section.size,
@@ -794,7 +1456,9 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
             sh_entsize: 0, // TODO?
         });
     }
-    for ((_, section), out_section) in obj.sections.iter().zip(&out_sections) {
+    for &section_index in &section_order {
+        let section = &obj.sections[section_index];
+        let out_section = &out_sections[section_index as usize];
         let Some(rela_name) = out_section.rela_name else {
             continue;
         };
@@ -853,16 +1517,121 @@ pub fn write_elf(obj: &ObjInfo, export_all: bool) -> Result<Vec<u8>> {
         });
     }
 
+    // Write passthrough section headers
+    for (section, (name, offset, compressed)) in raw_sections.iter().zip(&raw_out_sections) {
+        writer.write_section_header(&SectionHeader {
+            name: Some(*name),
+            sh_type: section.sh_type,
+            sh_flags: match compressed {
+                Some(_) => section.sh_flags | elf::SHF_COMPRESSED as u64,
+                None => section.sh_flags,
+            },
+            sh_addr: 0,
+            sh_offset: *offset as u64,
+            sh_size: compressed.as_ref().map_or(section.data.len(), |data| data.len()) as u64,
+            sh_link: 0,
+            sh_info: 0,
+            sh_addralign: if compressed.is_some() { 4 } else { section.align },
+            sh_entsize: 0,
+        });
+    }
+
+    // Write .hash section header
+    if let Some((name, offset, words)) = &hash_section {
+        writer.write_section_header(&SectionHeader {
+            name: Some(*name),
+            sh_type: elf::SHT_HASH,
+            sh_flags: 0,
+            sh_addr: 0,
+            sh_offset: *offset as u64,
+            sh_size: words.len() as u64 * 4,
+            sh_link: symtab.0,
+            sh_info: 0,
+            sh_addralign: 4,
+            sh_entsize: 4,
+        });
+    }
+
+    writer.write_symtab_shndx_section_header();
+
     ensure!(writer.reserved_len() == writer.len());
-    Ok(out_data)
+    Ok(writer.len())
 }
 
-fn to_obj_symbol(
-    obj_file: &object::File<'_>,
-    symbol: &Symbol<'_, '_>,
-    section_indexes: &[Option<usize>],
-    align: Option<u32>,
-) -> Result<ObjSymbol> {
+/// Whether the symbol is a named, defined global or weak symbol.
+fn is_exported_symbol(symbol: &ObjSymbol) -> bool {
+    !symbol.flags.is_local()
+        && symbol.section.is_some()
+        && symbol.kind != ObjSymbolKind::Section
+        && !symbol.name.is_empty()
+}
+
+/// Builds the words of a SysV `.hash` section: `nbucket`, `nchain`, buckets, then chains.
+/// `symbol_count` includes the null symbol.
+fn build_sysv_hash(symbol_count: u32, symbols: &[(u32, &str)]) -> Vec<u32> {
+    // Bucket counts used by GNU ld
+    const BUCKET_COUNTS: [u32; 16] =
+        [1, 3, 17, 37, 67, 97, 131, 197, 263, 521, 1031, 2053, 4099, 8209, 16411, 32771];
+    let nbucket = BUCKET_COUNTS
+        .iter()
+        .copied()
+        .take_while(|&n| n <= symbols.len() as u32)
+        .last()
+        .unwrap_or(1);
+    let mut words = vec![0u32; 2 + nbucket as usize + symbol_count as usize];
+    words[0] = nbucket;
+    words[1] = symbol_count;
+    let (buckets, chains) = words[2..].split_at_mut(nbucket as usize);
+    for &(index, name) in symbols {
+        let bucket = &mut buckets[(symbol_hash(name) % nbucket) as usize];
+        chains[index as usize] = *bucket;
+        *bucket = index;
+    }
+    words
+}
+
+fn read_dwarf_unit_name(obj_file: &object::File<'_>) -> Result<Option<String>> {
+    let (Some(debug_info), Some(debug_abbrev)) =
+        (obj_file.section_by_name(".debug_info"), obj_file.section_by_name(".debug_abbrev"))
+    else {
+        return Ok(None);
+    };
+    let mut info_data = debug_info.uncompressed_data()?.into_owned();
+    // Resolve offsets into .debug_abbrev and .debug_str
+    for (address, reloc) in debug_info.relocations() {
+        if !matches!(reloc.flags(), RelocationFlags::Elf { r_type: elf::R_PPC_ADDR32 }) {
+            continue;
+        }
+        let offset = address as usize;
+        ensure!(offset + 4 <= info_data.len(), "Relocation @ {:#X} outside of .debug_info", offset);
+        let target = match reloc.target() {
+            RelocationTarget::Symbol(index) => obj_file.symbol_by_index(index)?.address() as i64,
+            _ => 0,
+        };
+        let data = array_ref_mut!(info_data, offset, 4);
+        let mut value = target + reloc.addend();
+        if reloc.has_implicit_addend() {
+            value += u32::from_bytes(*data, obj_file.endianness().into()) as i64;
+        }
+        data.copy_from_slice(&(value as u32).to_bytes(obj_file.endianness().into())?);
+    }
+    let abbrev_data = debug_abbrev.uncompressed_data()?;
+    let str_data =
+        obj_file.section_by_name(".debug_str").map(|s| s.uncompressed_data()).transpose()?;
+    read_compile_unit_name(
+        &info_data,
+        &abbrev_data,
+        str_data.as_deref(),
+        obj_file.endianness().into(),
+    )
+}
+
+fn to_obj_symbol(
+    obj_file: &object::File<'_>,
+    symbol: &Symbol<'_, '_>,
+    section_indexes: &[Option<usize>],
+    align: Option<u32>,
+) -> Result<ObjSymbol> {
     let section = match symbol.section_index() {
         Some(idx) => Some(obj_file.section_by_index(idx)?),
         None => None,
@@ -891,6 +1660,9 @@ fn to_obj_symbol(
     if symbol.scope() == SymbolScope::Linkage {
         flags = ObjSymbolFlagSet(flags.0 | ObjSymbolFlags::Hidden);
     }
+    if symbol.kind() == SymbolKind::Tls {
+        flags = ObjSymbolFlagSet(flags.0 | ObjSymbolFlags::Tls);
+    }
     let section_idx = section.as_ref().and_then(|section| section_indexes[section.index().0]);
     Ok(ObjSymbol {
         name: name.to_string(),
@@ -902,27 +1674,70 @@ fn to_obj_symbol(
         flags,
         kind: match symbol.kind() {
             SymbolKind::Text => ObjSymbolKind::Function,
-            SymbolKind::Data => ObjSymbolKind::Object,
+            SymbolKind::Data | SymbolKind::Tls => ObjSymbolKind::Object,
             SymbolKind::Unknown | SymbolKind::Label => ObjSymbolKind::Unknown,
             SymbolKind::Section => ObjSymbolKind::Section,
+            SymbolKind::File => ObjSymbolKind::File,
             _ => bail!("Unsupported symbol kind: {:?}", symbol),
         },
         align,
+        st_other: match symbol.flags() {
+            // Visibility is interpreted into flags above
+            SymbolFlags::Elf { st_other, .. } => st_other & !3,
+            _ => 0,
+        },
         ..Default::default()
     })
 }
 
+/// Names of embedded PPC ABI relocation types without an [`ObjRelocKind`].
+fn ppc_reloc_name(r_type: u32) -> Option<&'static str> {
+    Some(match r_type {
+        elf::R_PPC_EMB_NADDR16_LO => "R_PPC_EMB_NADDR16_LO",
+        elf::R_PPC_EMB_NADDR16_HI => "R_PPC_EMB_NADDR16_HI",
+        elf::R_PPC_EMB_NADDR16_HA => "R_PPC_EMB_NADDR16_HA",
+        elf::R_PPC_EMB_SDA2REL => "R_PPC_EMB_SDA2REL",
+        elf::R_PPC_EMB_MRKREF => "R_PPC_EMB_MRKREF",
+        elf::R_PPC_EMB_RELSEC16 => "R_PPC_EMB_RELSEC16",
+        elf::R_PPC_EMB_RELST_LO => "R_PPC_EMB_RELST_LO",
+        elf::R_PPC_EMB_RELST_HI => "R_PPC_EMB_RELST_HI",
+        elf::R_PPC_EMB_RELST_HA => "R_PPC_EMB_RELST_HA",
+        elf::R_PPC_EMB_BIT_FLD => "R_PPC_EMB_BIT_FLD",
+        elf::R_PPC_EMB_RELSDA => "R_PPC_EMB_RELSDA",
+        elf::R_PPC_SDAREL16 => "R_PPC_SDAREL16",
+        _ => return None,
+    })
+}
+
 pub fn to_obj_reloc_kind(flags: RelocationFlags) -> Result<ObjRelocKind> {
     Ok(match flags {
         RelocationFlags::Elf { r_type } => match r_type {
             elf::R_PPC_ADDR32 | elf::R_PPC_UADDR32 => ObjRelocKind::Absolute,
+            elf::R_PPC_ADDR16 => ObjRelocKind::PpcAddr16,
             elf::R_PPC_ADDR16_LO => ObjRelocKind::PpcAddr16Lo,
             elf::R_PPC_ADDR16_HI => ObjRelocKind::PpcAddr16Hi,
             elf::R_PPC_ADDR16_HA => ObjRelocKind::PpcAddr16Ha,
             elf::R_PPC_REL24 => ObjRelocKind::PpcRel24,
+            elf::R_PPC_ADDR24 => ObjRelocKind::PpcAddr24,
             elf::R_PPC_REL14 => ObjRelocKind::PpcRel14,
             elf::R_PPC_EMB_SDA21 => ObjRelocKind::PpcEmbSda21,
-            kind => bail!("Unhandled ELF relocation type: {kind}"),
+            elf::R_PPC_REL32 => ObjRelocKind::PpcRel32,
+            elf::R_PPC_TPREL16 => ObjRelocKind::PpcTprel16,
+            elf::R_PPC_TPREL16_LO => ObjRelocKind::PpcTprel16Lo,
+            elf::R_PPC_TPREL16_HI => ObjRelocKind::PpcTprel16Hi,
+            elf::R_PPC_TPREL16_HA => ObjRelocKind::PpcTprel16Ha,
+            elf::R_PPC_DTPREL16 => ObjRelocKind::PpcDtprel16,
+            elf::R_PPC_DTPREL16_LO => ObjRelocKind::PpcDtprel16Lo,
+            elf::R_PPC_DTPREL16_HI => ObjRelocKind::PpcDtprel16Hi,
+            elf::R_PPC_DTPREL16_HA => ObjRelocKind::PpcDtprel16Ha,
+            elf::R_PPC_EMB_NADDR32 => ObjRelocKind::PpcEmbNaddr32,
+            elf::R_PPC_EMB_NADDR16 => ObjRelocKind::PpcEmbNaddr16,
+            elf::R_PPC_EMB_SDAI16 => ObjRelocKind::PpcEmbSdai16,
+            elf::R_PPC_EMB_SDA2I16 => ObjRelocKind::PpcEmbSda2i16,
+            kind => match ppc_reloc_name(kind) {
+                Some(name) => bail!("Unhandled ELF relocation type: {name}"),
+                None => bail!("Unhandled ELF relocation type: {kind}"),
+            },
         },
         flags => bail!("Unhandled relocation type: {:?}", flags),
     })
@@ -930,12 +1745,32 @@ pub fn to_obj_reloc_kind(flags: RelocationFlags) -> Result<ObjRelocKind> {
 
 fn to_obj_reloc(
     obj_file: &object::File<'_>,
+    kind: ObjKind,
     symbol_indexes: &[Option<ObjSymbolIndex>],
-    section_data: &[u8],
+    section: &ObjSection,
     address: u64,
     reloc: Relocation,
+    pair: Option<(u64, ObjRelocKind)>,
 ) -> Result<Option<ObjReloc>> {
-    let reloc_kind = to_obj_reloc_kind(reloc.flags())?;
+    if reloc.flags() == (RelocationFlags::Elf { r_type: elf::R_PPC_NONE }) {
+        log::debug!("Skipping R_PPC_NONE relocation at {:#010X}", address);
+        return Ok(None);
+    }
+    let e = Endian::from(obj_file.endianness());
+    // Relocation offsets in executables are virtual addresses
+    let section_offset = |address: u64| -> Result<u64> {
+        address.checked_sub(section.address).ok_or_else(|| {
+            anyhow!("Relocation @ {:#010X} precedes section {}", address, section.name)
+        })
+    };
+    let section_data = section.data.as_slice();
+    let mut reloc_kind = to_obj_reloc_kind(reloc.flags())?;
+    if reloc_kind == ObjRelocKind::PpcRel24
+        && is_absolute_branch(section_data, section_offset(address)?, e)
+    {
+        // Older writers emitted R_PPC_REL24 for absolute branches
+        reloc_kind = ObjRelocKind::PpcAddr24;
+    }
     let symbol = match reloc.target() {
         RelocationTarget::Symbol(idx) => {
             obj_file.symbol_by_index(idx).context("Failed to locate relocation target symbol")?
@@ -950,58 +1785,1774 @@ fn to_obj_reloc(
     };
     let target_symbol = symbol_indexes[symbol.index().0]
         .ok_or_else(|| anyhow!("Relocation against stripped symbol: {symbol:?}"))?;
-    let addend = match symbol.kind() {
-        SymbolKind::Text | SymbolKind::Data | SymbolKind::Unknown | SymbolKind::Label => {
-            Ok(reloc.addend())
+    // REL objects store the addend in the relocated field, RELA objects in the relocation
+    let addend = if reloc.has_implicit_addend() {
+        let offset = section_offset(address)?;
+        let stored = match (reloc_kind, pair) {
+            (ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Ha, Some((lo_address, _))) => {
+                implicit_pair_addend(
+                    section_data,
+                    offset,
+                    reloc_kind,
+                    section_offset(lo_address)?,
+                    e,
+                )?
+            }
+            (ObjRelocKind::PpcAddr16Lo, Some((hi_address, hi_kind))) => {
+                implicit_pair_addend(section_data, section_offset(hi_address)?, hi_kind, offset, e)?
+            }
+            _ => implicit_addend(section_data, offset, reloc_kind, e)?,
+        };
+        if kind == ObjKind::Executable {
+            executable_addend(reloc_kind, stored, symbol.address(), address, pair.is_some())?
+        } else {
+            stored
         }
+    } else {
+        reloc.addend()
+    };
+    match symbol.kind() {
+        SymbolKind::Text
+        | SymbolKind::Data
+        | SymbolKind::Tls
+        | SymbolKind::Unknown
+        | SymbolKind::Label => {}
         SymbolKind::Section => {
-            let addend = if reloc.has_implicit_addend() {
-                let addend = u32::from_be_bytes(
-                    section_data[address as usize..address as usize + 4].try_into()?,
-                ) as i64;
-                match reloc_kind {
-                    ObjRelocKind::Absolute => addend,
-                    _ => bail!("Unsupported implicit relocation type {reloc_kind:?}"),
-                }
-            } else {
-                reloc.addend()
-            };
             ensure!(addend >= 0, "Negative addend in section reloc: {addend}");
-            Ok(addend)
+            if !section_reloc_in_bounds(obj_file, &symbol, addend)? {
+                // Usually indicates a misparsed addend
+                log::warn!(
+                    "Section relocation @ {:#010X} targets {:?} + {:#X}, outside of the section",
+                    address,
+                    symbol.section(),
+                    addend
+                );
+            }
         }
-        _ => Err(anyhow!("Unhandled relocation symbol type {:?}", symbol.kind())),
-    }?;
+        _ => bail!("Unhandled relocation symbol type {:?}", symbol.kind()),
+    }
     Ok(Some(ObjReloc { kind: reloc_kind, target_symbol, addend, module: None }))
 }
 
+/// Whether the branch instruction at `address` has the AA bit set (`ba`/`bla`).
+fn is_absolute_branch(section_data: &[u8], address: u64, e: Endian) -> bool {
+    let address = (address & !3) as usize;
+    section_data
+        .get(address..address + 4)
+        .is_some_and(|data| u32::from_bytes(data.try_into().unwrap(), e) & 2 != 0)
+}
+
+/// Whether a section symbol relocation target lies within the section. The end of the
+/// section is allowed, as a table end pointer.
+fn section_reloc_in_bounds(
+    obj_file: &object::File<'_>,
+    symbol: &Symbol<'_, '_>,
+    addend: i64,
+) -> Result<bool> {
+    let Some(section_index) = symbol.section_index() else {
+        return Ok(true);
+    };
+    let section = obj_file.section_by_index(section_index)?;
+    let start = section.address();
+    let target = symbol.address().wrapping_add(addend as u64);
+    Ok(target >= start && target <= start + section.size())
+}
+
+/// Reads the addend of a REL relocation from the relocated field.
+fn implicit_addend(
+    section_data: &[u8],
+    address: u64,
+    reloc_kind: ObjRelocKind,
+    e: Endian,
+) -> Result<i64> {
+    let read_u32 = |address: u64| -> Result<u32> {
+        let data = section_data
+            .get(address as usize..address as usize + 4)
+            .ok_or_else(|| anyhow!("Relocation @ {:#010X} out of bounds", address))?;
+        Ok(u32::from_bytes(data.try_into()?, e))
+    };
+    Ok(match reloc_kind {
+        ObjRelocKind::Absolute | ObjRelocKind::PpcRel32 | ObjRelocKind::PpcEmbNaddr32 => {
+            read_u32(address)? as i32 as i64
+        }
+        ObjRelocKind::PpcEmbSda21 => {
+            let ins = read_u32(address & !3)?;
+            if is_paired_single_load_store(ins) {
+                ((ins << 20) as i32 >> 20) as i64
+            } else {
+                ins as u16 as i16 as i64
+            }
+        }
+        ObjRelocKind::PpcAddr16
+        | ObjRelocKind::PpcAddr16Lo
+        | ObjRelocKind::PpcTprel16
+        | ObjRelocKind::PpcTprel16Lo
+        | ObjRelocKind::PpcDtprel16
+        | ObjRelocKind::PpcDtprel16Lo
+        | ObjRelocKind::PpcEmbNaddr16
+        | ObjRelocKind::PpcEmbSdai16
+        | ObjRelocKind::PpcEmbSda2i16 => read_u32(address & !3)? as u16 as i16 as i64,
+        ObjRelocKind::PpcRel24 | ObjRelocKind::PpcAddr24 => {
+            (((read_u32(address & !3)? & 0x3FFFFFC) << 6) as i32 >> 6) as i64
+        }
+        ObjRelocKind::PpcRel14 => (read_u32(address & !3)? & 0xFFFC) as u16 as i16 as i64,
+        ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Ha => {
+            bail!("Implicit {reloc_kind:?} relocation @ {address:#010X} without a paired LO")
+        }
+        ObjRelocKind::PpcTprel16Hi
+        | ObjRelocKind::PpcTprel16Ha
+        | ObjRelocKind::PpcDtprel16Hi
+        | ObjRelocKind::PpcDtprel16Ha => {
+            bail!("Unsupported implicit {reloc_kind:?} relocation @ {address:#010X}")
+        }
+    })
+}
+
+/// Pairs each REL HI/HA relocation with the next LO relocation against the same target,
+/// and each LO relocation with the closest preceding HI/HA relocation.
+fn find_implicit_pairs(relocations: &[(u64, Relocation)]) -> Vec<Option<(u64, ObjRelocKind)>> {
+    let entries = relocations
+        .iter()
+        .map(|&(address, ref reloc)| {
+            let RelocationTarget::Symbol(target) = reloc.target() else {
+                return None;
+            };
+            if !reloc.has_implicit_addend() {
+                return None;
+            }
+            let kind = to_obj_reloc_kind(reloc.flags()).ok()?;
+            Some((target, address, kind))
+        })
+        .collect::<Vec<_>>();
+    let mut pairs = vec![None; relocations.len()];
+    let mut last_hi = HashMap::<object::SymbolIndex, (u64, ObjRelocKind)>::new();
+    for (i, &(target, address, kind)) in
+        entries.iter().enumerate().filter_map(|(i, e)| Some((i, e.as_ref()?)))
+    {
+        match kind {
+            ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Ha => {
+                last_hi.insert(target, (address, kind));
+            }
+            ObjRelocKind::PpcAddr16Lo => pairs[i] = last_hi.get(&target).copied(),
+            _ => {}
+        }
+    }
+    let mut next_lo = HashMap::<object::SymbolIndex, (u64, ObjRelocKind)>::new();
+    for (i, &(target, address, kind)) in
+        entries.iter().enumerate().rev().filter_map(|(i, e)| Some((i, e.as_ref()?)))
+    {
+        match kind {
+            ObjRelocKind::PpcAddr16Lo => {
+                next_lo.insert(target, (address, kind));
+            }
+            ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Ha => {
+                pairs[i] = next_lo.get(&target).copied()
+            }
+            _ => {}
+        }
+    }
+    pairs
+}
+
+/// Combines the 16-bit fields of a REL HI/HA and LO relocation pair into the full addend.
+/// HA fields are adjusted for the sign extension of the LO half.
+fn implicit_pair_addend(
+    section_data: &[u8],
+    hi_address: u64,
+    hi_kind: ObjRelocKind,
+    lo_address: u64,
+    e: Endian,
+) -> Result<i64> {
+    let field = |address: u64| -> Result<u32> {
+        Ok(implicit_addend(section_data, address, ObjRelocKind::PpcAddr16Lo, e)? as u16 as u32)
+    };
+    let hi = field(hi_address)? << 16;
+    let lo = field(lo_address)?;
+    let value = match hi_kind {
+        ObjRelocKind::PpcAddr16Ha => hi.wrapping_add(lo as u16 as i16 as u32),
+        _ => hi | lo,
+    };
+    Ok(value as i32 as i64)
+}
+
+/// Recovers the addend of a REL relocation in an executable, where the field holds the
+/// resolved value `S + A`, or `S + A - P` for PC-relative kinds. Unpaired 16-bit fields
+/// only hold the low half of the value, so the addend is assumed to fit in 16 bits.
+fn executable_addend(
+    reloc_kind: ObjRelocKind,
+    stored: i64,
+    target: u64,
+    address: u64,
+    paired: bool,
+) -> Result<i64> {
+    let value = stored.wrapping_sub(target as i64);
+    Ok(match reloc_kind {
+        ObjRelocKind::PpcRel24 | ObjRelocKind::PpcRel14 | ObjRelocKind::PpcRel32 => {
+            value.wrapping_add(address as i64) as i32 as i64
+        }
+        ObjRelocKind::Absolute | ObjRelocKind::PpcAddr24 => value as i32 as i64,
+        ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Ha | ObjRelocKind::PpcAddr16Lo
+            if paired =>
+        {
+            value as i32 as i64
+        }
+        ObjRelocKind::PpcAddr16 | ObjRelocKind::PpcAddr16Lo => value as i16 as i64,
+        ObjRelocKind::PpcEmbNaddr32 => (-stored).wrapping_sub(target as i64) as i32 as i64,
+        ObjRelocKind::PpcEmbNaddr16 => (-stored).wrapping_sub(target as i64) as i16 as i64,
+        _ => {
+            bail!("Unsupported implicit {reloc_kind:?} relocation @ {address:#010X} in executable")
+        }
+    })
+}
+
+/// Ensures the relocation value fits in the instruction or data field it targets.
+/// Relative relocations against a symbol in the same section are checked using the
+/// final displacement; otherwise only the addend itself is checked.
+fn validate_reloc_addend(
+    obj: &ObjInfo,
+    section_index: ObjSectionIndex,
+    addr: u32,
+    reloc: &ObjReloc,
+) -> Result<()> {
+    ensure!(
+        reloc.target_symbol < obj.symbols.count(),
+        "Relocation target symbol {} not found",
+        reloc.target_symbol
+    );
+    let symbol = &obj.symbols[reloc.target_symbol];
+    let value = match reloc.kind {
+        ObjRelocKind::PpcRel24 | ObjRelocKind::PpcRel14 | ObjRelocKind::PpcRel32
+            if symbol.section == Some(section_index) =>
+        {
+            symbol.address as i64 + reloc.addend - addr as i64
+        }
+        _ => reloc.addend,
+    };
+    let (min, max, align) = match reloc.kind {
+        ObjRelocKind::Absolute
+        | ObjRelocKind::PpcAddr16Hi
+        | ObjRelocKind::PpcAddr16Ha
+        | ObjRelocKind::PpcAddr16Lo
+        | ObjRelocKind::PpcTprel16Lo
+        | ObjRelocKind::PpcTprel16Hi
+        | ObjRelocKind::PpcTprel16Ha
+        | ObjRelocKind::PpcDtprel16Lo
+        | ObjRelocKind::PpcDtprel16Hi
+        | ObjRelocKind::PpcDtprel16Ha
+        | ObjRelocKind::PpcEmbNaddr32 => (i32::MIN as i64, u32::MAX as i64, 1),
+        ObjRelocKind::PpcAddr16
+        | ObjRelocKind::PpcTprel16
+        | ObjRelocKind::PpcDtprel16
+        | ObjRelocKind::PpcEmbNaddr16
+        | ObjRelocKind::PpcEmbSdai16
+        | ObjRelocKind::PpcEmbSda2i16 => (i16::MIN as i64, u16::MAX as i64, 1),
+        ObjRelocKind::PpcRel24 | ObjRelocKind::PpcAddr24 => (-0x2000000, 0x1FFFFFC, 4),
+        ObjRelocKind::PpcRel14 => (-0x8000, 0x7FFC, 4),
+        ObjRelocKind::PpcEmbSda21 => (i16::MIN as i64, i16::MAX as i64, 1),
+        ObjRelocKind::PpcRel32 => (i32::MIN as i64, i32::MAX as i64, 1),
+    };
+    ensure!(
+        (min..=max).contains(&value) && value % align == 0,
+        "Relocation {:?} @ {}:{:#010X} against {}: value {:#X} out of range",
+        reloc.kind,
+        obj.sections[section_index].name,
+        addr,
+        symbol.name,
+        value
+    );
+    Ok(())
+}
+
 /// Writes section data while zeroing out relocations.
-fn write_relocatable_section_data(w: &mut Writer, section: &ObjSection) -> Result<()> {
+fn write_relocatable_section_data(w: &mut Writer, section: &ObjSection, e: Endian) -> Result<()> {
     ensure!(section.address == 0);
     let mut current_address = 0;
     for (addr, reloc) in section.relocations.iter() {
         w.write(&section.data[current_address..addr as usize]);
-        let mut ins = u32::from_be_bytes(*array_ref!(section.data, addr as usize, 4));
+        let mut ins = u32::from_bytes(*array_ref!(section.data, addr as usize, 4), e);
         match reloc.kind {
-            ObjRelocKind::Absolute => {
+            ObjRelocKind::Absolute | ObjRelocKind::PpcRel32 | ObjRelocKind::PpcEmbNaddr32 => {
                 ins = 0;
             }
-            ObjRelocKind::PpcAddr16Hi | ObjRelocKind::PpcAddr16Ha | ObjRelocKind::PpcAddr16Lo => {
+            ObjRelocKind::PpcAddr16
+            | ObjRelocKind::PpcAddr16Hi
+            | ObjRelocKind::PpcAddr16Ha
+            | ObjRelocKind::PpcAddr16Lo
+            | ObjRelocKind::PpcTprel16
+            | ObjRelocKind::PpcTprel16Lo
+            | ObjRelocKind::PpcTprel16Hi
+            | ObjRelocKind::PpcTprel16Ha
+            | ObjRelocKind::PpcDtprel16
+            | ObjRelocKind::PpcDtprel16Lo
+            | ObjRelocKind::PpcDtprel16Hi
+            | ObjRelocKind::PpcDtprel16Ha
+            | ObjRelocKind::PpcEmbNaddr16
+            | ObjRelocKind::PpcEmbSdai16
+            | ObjRelocKind::PpcEmbSda2i16 => {
                 ins &= !0xFFFF;
             }
-            ObjRelocKind::PpcRel24 => {
+            ObjRelocKind::PpcRel24 | ObjRelocKind::PpcAddr24 => {
                 ins &= !0x3FFFFFC;
             }
             ObjRelocKind::PpcRel14 => {
                 ins &= !0xFFFC;
             }
             ObjRelocKind::PpcEmbSda21 => {
-                ins &= !0x1FFFFF;
+                ins &= !sda21_field_mask(ins);
             }
         }
-        w.write(&ins.to_be_bytes());
+        w.write(&ins.to_bytes(e)?);
         current_address = addr as usize + 4;
     }
     // Write remaining data
     w.write(&section.data[current_address..]);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        section_kind_for_section, split_section_suffix,
+        testing::{test_section, test_section_data, test_section_words, test_symbol},
+        ObjInfoBuilder, ObjRelocations, ObjSymbols,
+    };
+
+    fn test_obj() -> ObjInfo {
+        let mut builder = ObjInfoBuilder::new(ObjKind::Relocatable, "test.o".to_string());
+        let text = builder.add_section(test_section_words(".text", ObjSectionKind::Code, 0, &[
+            0x48000001, 0x4E800020, 0x60000000,
+        ]));
+        builder.add_symbol(test_symbol("foo", text, 0, 4));
+        let bar = builder.add_symbol(test_symbol("bar", text, 4, 4));
+        builder.add_reloc(text, 0, ObjReloc {
+            kind: ObjRelocKind::PpcRel24,
+            target_symbol: bar,
+            addend: 0,
+            module: None,
+        });
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_write_elf_large_symbol_table() {
+        const COUNT: u32 = 20000;
+        let mut builder = ObjInfoBuilder::new(ObjKind::Relocatable, "large.o".to_string());
+        let words = (0..COUNT)
+            .map(|i| if i % 16 == 0 { 0x48000001 } else { 0x4E800020 })
+            .collect::<Vec<_>>();
+        let text =
+            builder.add_section(test_section_words(".text", ObjSectionKind::Code, 0, &words));
+        for i in 0..COUNT {
+            let flags = if i % 3 == 0 { ObjSymbolFlags::Local } else { ObjSymbolFlags::Global };
+            builder.add_symbol(ObjSymbol {
+                flags: ObjSymbolFlagSet(flags.into()),
+                ..test_symbol(&format!("fn_{:05}", i), text, i as u64 * 4, 4)
+            });
+            if i % 16 == 0 {
+                builder.add_reloc(text, i * 4, ObjReloc {
+                    kind: ObjRelocKind::PpcRel24,
+                    target_symbol: (i + 1) % COUNT,
+                    addend: 0,
+                    module: None,
+                });
+            }
+        }
+        builder.set_mw_comment(MWComment::new(8).unwrap());
+        let obj = builder.build().unwrap();
+
+        let data = write_elf(&obj, false).unwrap();
+        assert_eq!(write_elf_size(&obj).unwrap(), data.len());
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        // Local symbols first, in their original order
+        let expected = (0..COUNT)
+            .filter(|i| i % 3 == 0)
+            .chain((0..COUNT).filter(|i| i % 3 != 0))
+            .map(|i| format!("fn_{:05}", i))
+            .collect::<Vec<_>>();
+        let names = file
+            .symbols()
+            .filter(|s| s.kind() == SymbolKind::Text)
+            .map(|s| s.name().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, expected);
+        let first_global = file.symbols().find(|s| s.is_global()).unwrap().index().0;
+        assert_eq!(first_global, 3 + (COUNT as usize).div_ceil(3));
+        let relocations = file.section_by_name(".text").unwrap().relocations().collect::<Vec<_>>();
+        assert_eq!(relocations.len(), COUNT.div_ceil(16) as usize);
+        for (offset, relocation) in relocations {
+            let RelocationTarget::Symbol(index) = relocation.target() else { panic!() };
+            let target = file.symbol_by_index(index).unwrap();
+            assert_eq!(target.name().unwrap(), format!("fn_{:05}", offset / 4 + 1));
+        }
+
+        // Re-reading and writing produces identical bytes
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        assert_eq!(write_elf(&obj, false).unwrap(), data);
+    }
+
+    #[test]
+    fn test_write_elf_sorted() {
+        let obj = test_obj();
+        let mut reversed = test_obj();
+        let symbols = reversed.symbols.iter().map(|(_, s)| s.clone()).rev().collect::<Vec<_>>();
+        reversed.symbols = ObjSymbols::new(reversed.kind, symbols);
+        // Relocation targets must follow the reordered symbols
+        let bar = reversed.symbols.by_name("bar").unwrap().unwrap().0;
+        reversed.sections[0].relocations.replace(0, ObjReloc {
+            kind: ObjRelocKind::PpcRel24,
+            target_symbol: bar,
+            addend: 0,
+            module: None,
+        });
+
+        assert_ne!(write_elf(&obj, false).unwrap(), write_elf(&reversed, false).unwrap());
+        assert_eq!(
+            write_elf_sorted(&obj, false, SymbolSort::ByAddress).unwrap(),
+            write_elf_sorted(&reversed, false, SymbolSort::ByAddress).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_validate_reloc_addend() {
+        let mut obj = test_obj();
+        assert!(write_elf(&obj, false).is_ok());
+
+        let bar = obj.symbols.by_name("bar").unwrap().unwrap().0;
+        obj.sections[0].relocations.replace(0, ObjReloc {
+            kind: ObjRelocKind::PpcRel24,
+            target_symbol: bar,
+            addend: 0x2000000,
+            module: None,
+        });
+        assert!(write_elf(&obj, false).is_err());
+    }
+
+    #[test]
+    fn test_symbol_aliases() {
+        let mut obj = test_obj();
+        let alias = obj.symbols.add_direct(test_symbol("bar_alias", 0, 4, 4)).unwrap();
+        obj.sections[0]
+            .relocations
+            .insert(4, ObjReloc {
+                kind: ObjRelocKind::Absolute,
+                target_symbol: alias,
+                addend: 0,
+                module: None,
+            })
+            .unwrap();
+        assert_eq!(obj.symbols.aliases_at(0, 4).len(), 2);
+
+        let data = write_elf(&obj, false).unwrap();
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (section_index, section) = obj.sections.by_name(".text").unwrap().unwrap();
+        let names = obj
+            .symbols
+            .aliases_at(section_index, 4)
+            .iter()
+            .map(|&idx| obj.symbols[idx].name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["bar", "bar_alias"]);
+        let targets = section
+            .relocations
+            .iter()
+            .map(|(_, reloc)| obj.symbols[reloc.target_symbol].name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(targets, vec!["bar", "bar_alias"]);
+    }
+
+    #[test]
+    fn test_write_elf_size() {
+        let mut obj = test_obj();
+        assert_eq!(write_elf_size(&obj).unwrap(), write_elf(&obj, false).unwrap().len());
+
+        obj.mw_comment = Some(MWComment::new(8).unwrap());
+        assert_eq!(write_elf_size(&obj).unwrap(), write_elf(&obj, false).unwrap().len());
+
+        obj.name = String::new();
+        obj.kind = ObjKind::Executable;
+        obj.sections[0].relocations = Default::default();
+        assert_eq!(write_elf_size(&obj).unwrap(), write_elf(&obj, false).unwrap().len());
+    }
+
+    #[test]
+    fn test_short_comment_section() {
+        use object::read::elf::FileHeader;
+
+        let mut obj = test_obj();
+        obj.mw_comment = Some(MWComment::new(8).unwrap());
+        let mut data = write_elf(&obj, false).unwrap();
+
+        // Drop the last symbol's entry from .comment
+        let offset = {
+            let file = ElfFile32::<Endianness>::parse(data.as_slice()).unwrap();
+            let index = file.section_by_name(".comment").unwrap().index();
+            let e_shoff = file.elf_header().e_shoff(file.endian()) as usize;
+            e_shoff + index.0 * size_of::<elf::SectionHeader32<Endianness>>()
+        };
+        // sh_size follows sh_name, sh_type, sh_flags, sh_addr and sh_offset
+        let size = array_ref_mut!(data, offset + 20, 4);
+        *size = (u32::from_be_bytes(*size) - 8).to_be_bytes();
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, foo) = obj.symbols.by_name("foo").unwrap().unwrap();
+        assert_eq!(foo.align, Some(4));
+        let (_, bar) = obj.symbols.by_name("bar").unwrap().unwrap();
+        assert_eq!(bar.align, None);
+
+        // A longer .comment is still an error
+        let size = array_ref_mut!(data, offset + 20, 4);
+        *size = (u32::from_be_bytes(*size) + 16).to_be_bytes();
+        assert!(process_elf_data(&data, |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_write_elf_little_endian() {
+        let mut obj = test_obj();
+        obj.endian = Endian::Little;
+        obj.sections[0].data =
+            vec![0x05, 0x00, 0x00, 0x48, 0x20, 0x00, 0x80, 0x4E, 0x00, 0x00, 0x00, 0x60];
+        let data = write_elf(&obj, false).unwrap();
+        assert_eq!(write_elf_size(&obj).unwrap(), data.len());
+
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        assert_eq!(file.endianness(), Endianness::Little);
+        assert_eq!(file.architecture(), Architecture::PowerPc);
+        let text = file.section_by_name(".text").unwrap();
+        // Branch displacement masked out in little-endian order
+        assert_eq!(&text.data().unwrap()[..8], &[0x01, 0x00, 0x00, 0x48, 0x20, 0x00, 0x80, 0x4E]);
+        let bar = file.symbol_by_name("bar").unwrap();
+        assert_eq!(bar.address(), 4);
+        let relocations = text.relocations().collect::<Vec<_>>();
+        assert_eq!(relocations.len(), 1);
+        let (offset, reloc) = &relocations[0];
+        assert_eq!(*offset, 0);
+        assert_eq!(reloc.flags(), RelocationFlags::Elf { r_type: elf::R_PPC_REL24 });
+        assert_eq!(reloc.target(), RelocationTarget::Symbol(bar.index()));
+    }
+
+    #[test]
+    fn test_read_elf_little_endian() {
+        let mut obj = test_obj();
+        obj.endian = Endian::Little;
+        obj.sections[0].data =
+            vec![0x01, 0x00, 0x00, 0x48, 0x20, 0x00, 0x80, 0x4E, 0x00, 0x00, 0x00, 0x60];
+        let data = write_elf(&obj, false).unwrap();
+
+        let read = process_elf_data(&data, |_| {}).unwrap();
+        assert_eq!(read.endian, Endian::Little);
+        let (_, text) = read.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(text.data, obj.sections[0].data);
+        let (bar, _) = read.symbols.by_name("bar").unwrap().unwrap();
+        let reloc = text.relocations.at(0).unwrap();
+        assert_eq!(reloc.kind, ObjRelocKind::PpcRel24);
+        assert_eq!(reloc.target_symbol, bar);
+        assert_eq!(reloc.addend, 0);
+
+        // Instruction fields are decoded in the file's byte order
+        let bla = [0x03, 0x00, 0x00, 0x48];
+        assert!(is_absolute_branch(&bla, 0, Endian::Little));
+        assert!(!is_absolute_branch(&bla, 0, Endian::Big));
+        let addi = [0xF8, 0xFF, 0x62, 0x38]; // addi r3, r2, -8
+        assert_eq!(
+            implicit_addend(&addi, 0, ObjRelocKind::PpcAddr16Lo, Endian::Little).unwrap(),
+            -8
+        );
+    }
+
+    #[test]
+    fn test_r_ppc_none() {
+        let mut obj = test_obj();
+        let (foo, _) = obj.symbols.by_name("foo").unwrap().unwrap();
+        let (bar, _) = obj.symbols.by_name("bar").unwrap().unwrap();
+        for (address, target_symbol) in [(4, foo), (8, bar)] {
+            obj.sections[0]
+                .relocations
+                .insert(address, ObjReloc {
+                    kind: ObjRelocKind::Absolute,
+                    target_symbol,
+                    addend: 0,
+                    module: None,
+                })
+                .unwrap();
+        }
+        let mut data = write_elf(&obj, false).unwrap();
+
+        // Replace the type of the second relocation with R_PPC_NONE
+        let (rela_offset, _) = object::read::File::parse(data.as_slice())
+            .unwrap()
+            .section_by_name(".rela.text")
+            .unwrap()
+            .file_range()
+            .unwrap();
+        data[rela_offset as usize + 12 + 7] = elf::R_PPC_NONE as u8;
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, section) = obj.sections.by_name(".text").unwrap().unwrap();
+        let addresses = section.relocations.iter().map(|(addr, _)| addr).collect::<Vec<_>>();
+        assert_eq!(addresses, vec![0, 8]);
+    }
+
+    #[test]
+    fn test_reloc_offsets() {
+        let mut obj = test_obj();
+        let (bar, _) = obj.symbols.by_name("bar").unwrap().unwrap();
+        obj.sections[0].relocations = ObjRelocations::new(vec![
+            (0, ObjReloc {
+                kind: ObjRelocKind::PpcAddr16Ha,
+                target_symbol: bar,
+                addend: 0,
+                module: None,
+            }),
+            (4, ObjReloc {
+                kind: ObjRelocKind::PpcAddr16Lo,
+                target_symbol: bar,
+                addend: 0,
+                module: None,
+            }),
+        ])
+        .unwrap();
+        let data = write_elf(&obj, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let offsets = file
+            .section_by_name(".text")
+            .unwrap()
+            .relocations()
+            .map(|(offset, _)| offset)
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![2, 6]);
+
+        // An unaligned absolute relocation can share an offset with a 16-bit relocation
+        obj.sections[0].relocations.replace(2, ObjReloc {
+            kind: ObjRelocKind::Absolute,
+            target_symbol: bar,
+            addend: 0,
+            module: None,
+        });
+        assert!(write_elf(&obj, false).is_err());
+    }
+
+    /// Writes an object with a single `.text` section and local symbols (`STT_FILE` symbols
+    /// are absolute), optionally with a REL relocation against the first symbol.
+    /// `write_elf` can't produce REL relocations or multiple file symbols.
+    fn write_test_object(
+        e_type: u16,
+        text: &[u8],
+        symbols: &[(&str, u8, u64)],
+        rels: &[(u64, u32)],
+    ) -> Vec<u8> {
+        write_test_object_at(e_type, 0, text, symbols, rels)
+    }
+
+    fn write_test_object_at(
+        e_type: u16,
+        text_address: u64,
+        text: &[u8],
+        symbols: &[(&str, u8, u64)],
+        rels: &[(u64, u32)],
+    ) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut writer = Writer::new(Endianness::Big, false, &mut data);
+        writer.reserve_null_section_index();
+        let text_name = writer.add_section_name(b".text");
+        let text_index = writer.reserve_section_index();
+        let rel_name = (!rels.is_empty()).then(|| {
+            let name = writer.add_section_name(b".rel.text");
+            writer.reserve_section_index();
+            name
+        });
+        writer.reserve_null_symbol_index();
+        let symbol_names = symbols
+            .iter()
+            .map(|&(name, st_type, _)| {
+                let section = if st_type == elf::STT_FILE { None } else { Some(text_index) };
+                writer.reserve_symbol_index(section);
+                writer.add_string(name.as_bytes())
+            })
+            .collect::<Vec<_>>();
+        let symtab = writer.reserve_symtab_section_index();
+        writer.reserve_strtab_section_index();
+        writer.reserve_shstrtab_section_index();
+        writer.reserve_file_header();
+        let text_offset = writer.reserve(text.len(), 4);
+        let rel_offset = (!rels.is_empty()).then(|| writer.reserve_relocations(rels.len(), false));
+        writer.reserve_symtab();
+        writer.reserve_strtab();
+        writer.reserve_shstrtab();
+        writer.reserve_section_headers();
+
+        writer
+            .write_file_header(&object::write::elf::FileHeader {
+                os_abi: elf::ELFOSABI_SYSV,
+                abi_version: 0,
+                e_type,
+                e_machine: elf::EM_PPC,
+                e_entry: 0,
+                e_flags: 0,
+            })
+            .unwrap();
+        writer.write_align(4);
+        writer.write(text);
+        if !rels.is_empty() {
+            writer.write_align_relocation();
+        }
+        for &(r_offset, r_type) in rels {
+            writer.write_relocation(false, &Rel { r_offset, r_sym: 1, r_type, r_addend: 0 });
+        }
+        writer.write_null_symbol();
+        for (&(_, st_type, st_value), &name) in symbols.iter().zip(&symbol_names) {
+            let is_file = st_type == elf::STT_FILE;
+            writer.write_symbol(&object::write::elf::Sym {
+                name: Some(name),
+                section: if is_file { None } else { Some(text_index) },
+                st_info: (elf::STB_LOCAL << 4) + st_type,
+                st_other: elf::STV_DEFAULT,
+                st_shndx: if is_file { elf::SHN_ABS } else { 0 },
+                st_value,
+                st_size: if is_file { 0 } else { 4 },
+            });
+        }
+        writer.write_strtab();
+        writer.write_shstrtab();
+        writer.write_null_section_header();
+        writer.write_section_header(&SectionHeader {
+            name: Some(text_name),
+            sh_type: SHT_PROGBITS,
+            sh_flags: (SHF_ALLOC | SHF_EXECINSTR) as u64,
+            sh_addr: text_address,
+            sh_offset: text_offset as u64,
+            sh_size: text.len() as u64,
+            sh_link: 0,
+            sh_info: 0,
+            sh_addralign: 4,
+            sh_entsize: 0,
+        });
+        if let (Some(rel_name), Some(rel_offset)) = (rel_name, rel_offset) {
+            writer.write_relocation_section_header(
+                rel_name,
+                text_index,
+                symtab,
+                rel_offset,
+                rels.len(),
+                false,
+            );
+        }
+        writer.write_symtab_section_header(symbols.len() as u32 + 1);
+        writer.write_strtab_section_header();
+        writer.write_shstrtab_section_header();
+        data
+    }
+
+    #[test]
+    fn test_reloc_addends() {
+        // RELA: addend stored in the relocation
+        let mut obj = test_obj();
+        let (foo, _) = obj.symbols.by_name("foo").unwrap().unwrap();
+        obj.sections[0]
+            .relocations
+            .insert(8, ObjReloc {
+                kind: ObjRelocKind::Absolute,
+                target_symbol: foo,
+                addend: 8,
+                module: None,
+            })
+            .unwrap();
+        let obj = process_elf_data(&write_elf(&obj, false).unwrap(), |_| {}).unwrap();
+        let (_, section) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(section.relocations.at(8).unwrap().addend, 8);
+
+        // REL: addends stored in section data
+        let text = [0x4E, 0x80, 0x00, 0x20, 0x00, 0x00, 0x00, 0x08];
+        let data = write_test_object(elf::ET_REL, &text, &[("foo", elf::STT_FUNC, 0)], &[(
+            4,
+            elf::R_PPC_ADDR32,
+        )]);
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, section) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(section.relocations.at(4).unwrap().addend, 8);
+
+        let data = write_test_object(
+            elf::ET_REL,
+            &[0x4B, 0xFF, 0xFF, 0xFD],
+            &[("foo", elf::STT_FUNC, 0)],
+            &[(0, elf::R_PPC_REL24)],
+        );
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, section) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(section.relocations.at(0).unwrap().addend, -4);
+
+        // Re-emitted as RELA with the same addend
+        let obj = process_elf_data(&write_elf(&obj, false).unwrap(), |_| {}).unwrap();
+        let (_, section) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(section.relocations.at(0).unwrap().addend, -4);
+    }
+
+    #[test]
+    fn test_self_relative_reloc() {
+        // A table of pointers into its own section
+        let text = [0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x0C, 0, 0, 0, 0, 0, 0, 0, 0];
+        let data = write_test_object(elf::ET_REL, &text, &[("", elf::STT_SECTION, 0)], &[
+            (0, elf::R_PPC_ADDR32),
+            (4, elf::R_PPC_ADDR32),
+        ]);
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, section) = obj.sections.by_name(".text").unwrap().unwrap();
+        let targets = section
+            .relocations
+            .iter()
+            .map(|(_, r)| (obj.symbols[r.target_symbol].kind, r.addend))
+            .collect::<Vec<_>>();
+        assert_eq!(targets, vec![(ObjSymbolKind::Section, 8), (ObjSymbolKind::Section, 12)]);
+
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let symbol = file.symbol_by_index(object::SymbolIndex(1)).unwrap();
+        assert!(section_reloc_in_bounds(&file, &symbol, 0x10).unwrap());
+        assert!(!section_reloc_in_bounds(&file, &symbol, 0x14).unwrap());
+    }
+
+    #[test]
+    fn test_absolute_branch_reloc() {
+        let mut obj = test_obj();
+        let section = &mut obj.sections[0];
+        section.data[..4].copy_from_slice(&0x48000003u32.to_be_bytes()); // bla bar
+        section.relocations.at_mut(0).unwrap().kind = ObjRelocKind::PpcAddr24;
+        let data = write_elf(&obj, false).unwrap();
+
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let text = file.section_by_name(".text").unwrap();
+        let (_, reloc) = text.relocations().next().unwrap();
+        assert_eq!(reloc.flags(), RelocationFlags::Elf { r_type: elf::R_PPC_ADDR24 });
+        assert_eq!(&text.data().unwrap()[..4], &[0x48, 0x00, 0x00, 0x03]);
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let reloc = obj.sections[0].relocations.at(0).unwrap();
+        assert_eq!(reloc.kind, ObjRelocKind::PpcAddr24);
+        assert_eq!(obj.symbols[reloc.target_symbol].name, "bar");
+
+        // R_PPC_REL24 against an absolute branch is read as R_PPC_ADDR24
+        let text = [0x48, 0x00, 0x00, 0x03];
+        let data = write_test_object(elf::ET_REL, &text, &[("foo", elf::STT_FUNC, 0)], &[(
+            0,
+            elf::R_PPC_REL24,
+        )]);
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, section) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(section.relocations.at(0).unwrap().kind, ObjRelocKind::PpcAddr24);
+    }
+
+    #[test]
+    fn test_section_file_offset() {
+        let mut obj = test_obj();
+        obj.sections.push(test_section_data(".data", ObjSectionKind::Data, 0, vec![1, 2, 3, 4]));
+        obj.sections.push(test_section(".bss", ObjSectionKind::Bss, 0, 4));
+        let data = write_elf(&obj, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let (data_offset, _) = file.section_by_name(".data").unwrap().file_range().unwrap();
+        assert_ne!(data_offset, 0);
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, data_section) = obj.sections.by_name(".data").unwrap().unwrap();
+        assert_eq!(data_section.file_offset, Some(data_offset));
+        let (_, bss_section) = obj.sections.by_name(".bss").unwrap().unwrap();
+        assert_eq!(bss_section.file_offset, None);
+    }
+
+    #[test]
+    fn test_tls_nobits_section() {
+        use object::read::elf::FileHeader;
+
+        let mut obj = test_obj();
+        obj.sections.push(test_section(".tbss", ObjSectionKind::Bss, 0, 0x20));
+        let mut data = write_elf(&obj, false).unwrap();
+
+        // Mark the section thread-local, which `object` reports as `UninitializedTls`
+        let (index, offset) = {
+            let file = ElfFile32::<Endianness>::parse(data.as_slice()).unwrap();
+            let index = file.section_by_name(".tbss").unwrap().index();
+            let e_shoff = file.elf_header().e_shoff(file.endian()) as usize;
+            (index, e_shoff + index.0 * size_of::<elf::SectionHeader32<Endianness>>())
+        };
+        // sh_flags follows sh_name and sh_type
+        let flags = array_ref_mut!(data, offset + 8, 4);
+        *flags = (u32::from_be_bytes(*flags) | elf::SHF_TLS).to_be_bytes();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        assert_eq!(file.section_by_index(index).unwrap().kind(), SectionKind::UninitializedTls);
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, section) = obj.sections.by_name(".tbss").unwrap().unwrap();
+        assert_eq!(section.kind, ObjSectionKind::Bss);
+        assert_eq!(section.size, 0x20);
+        assert!(section.data.is_empty());
+    }
+
+    #[test]
+    fn test_e_flags() {
+        let mut obj = test_obj();
+        assert_eq!(e_flags(&write_elf(&obj, false).unwrap()), elf::EF_PPC_EMB);
+
+        obj.e_flags = Some(elf::EF_PPC_RELOCATABLE_LIB | elf::EF_PPC_EMB);
+        let data = write_elf(&obj, false).unwrap();
+        assert_eq!(e_flags(&data), elf::EF_PPC_RELOCATABLE_LIB | elf::EF_PPC_EMB);
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        assert_eq!(obj.e_flags, Some(elf::EF_PPC_RELOCATABLE_LIB | elf::EF_PPC_EMB));
+        assert_eq!(write_elf(&obj, false).unwrap(), data);
+
+        fn e_flags(data: &[u8]) -> u32 {
+            match object::read::File::parse(data).unwrap().flags() {
+                FileFlags::Elf { e_flags, .. } => e_flags,
+                flags => panic!("Unexpected flags {flags:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_symtab_shndx() {
+        let mut obj = test_obj();
+        let mut last = 0;
+        for i in 0..elf::SHN_LORESERVE as u32 {
+            last = obj.sections.push(test_section(&format!(".bss{i}"), ObjSectionKind::Bss, 0, 0));
+        }
+        obj.symbols
+            .add_direct(ObjSymbol {
+                name: "last".to_string(),
+                section: Some(last),
+                flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
+                kind: ObjSymbolKind::Object,
+                ..Default::default()
+            })
+            .unwrap();
+        let data = write_elf(&obj, false).unwrap();
+
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        assert!(file.section_by_name(".symtab_shndx").is_some());
+        let symbol = file.symbol_by_name("last").unwrap();
+        let section = file.section_by_index(symbol.section_index().unwrap()).unwrap();
+        assert_eq!(section.name().unwrap(), format!(".bss{}", elf::SHN_LORESERVE - 1));
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, symbol) = obj.symbols.by_name("last").unwrap().unwrap();
+        assert_eq!(symbol.section, Some(last));
+    }
+
+    #[test]
+    fn test_symbol_st_other() {
+        let mut obj = test_obj();
+        let mut symbol = obj.symbols[1].clone();
+        symbol.st_other = 3 << 5; // PPC64 local entry point offset
+        symbol.flags = ObjSymbolFlagSet(symbol.flags.0 | ObjSymbolFlags::Hidden);
+        obj.symbols.replace(1, symbol).unwrap();
+        let data = write_elf(&obj, false).unwrap();
+
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        match file.symbol_by_name("bar").unwrap().flags() {
+            SymbolFlags::Elf { st_other, .. } => assert_eq!(st_other, (3 << 5) | elf::STV_HIDDEN),
+            flags => panic!("Unexpected flags {flags:?}"),
+        }
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, symbol) = obj.symbols.by_name("bar").unwrap().unwrap();
+        assert_eq!(symbol.st_other, 3 << 5);
+        assert!(symbol.flags.is_hidden());
+        assert_eq!(write_elf(&obj, false).unwrap(), data);
+    }
+
+    #[test]
+    fn test_tls_reloc() {
+        let mut obj = test_obj();
+        obj.sections[0].data[8..].copy_from_slice(&0x38620000u32.to_be_bytes()); // addi r3, r2, 0
+        let tls_var = obj
+            .symbols
+            .add_direct(ObjSymbol {
+                name: "tls_var".to_string(),
+                flags: ObjSymbolFlagSet(ObjSymbolFlags::Global | ObjSymbolFlags::Tls),
+                kind: ObjSymbolKind::Object,
+                ..Default::default()
+            })
+            .unwrap();
+        obj.sections[0]
+            .relocations
+            .insert(10, ObjReloc {
+                kind: ObjRelocKind::PpcTprel16Lo,
+                target_symbol: tls_var,
+                addend: 0,
+                module: None,
+            })
+            .unwrap();
+        let data = write_elf(&obj, false).unwrap();
+
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let text = file.section_by_name(".text").unwrap();
+        let (address, reloc) = text.relocations().nth(1).unwrap();
+        assert_eq!(address, 10);
+        assert_eq!(reloc.flags(), RelocationFlags::Elf { r_type: elf::R_PPC_TPREL16_LO });
+        assert_eq!(
+            file.symbols().find(|s| s.name().ok() == Some("tls_var")).unwrap().kind(),
+            SymbolKind::Tls
+        );
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let reloc = obj.sections[0].relocation_at(10).unwrap();
+        assert_eq!(reloc.kind, ObjRelocKind::PpcTprel16Lo);
+        let symbol = &obj.symbols[reloc.target_symbol];
+        assert_eq!(symbol.name, "tls_var");
+        assert!(symbol.flags.is_tls());
+    }
+
+    #[test]
+    fn test_emb_naddr32_reloc() {
+        let mut obj = test_obj();
+        obj.sections[0].data[8..].fill(0);
+        obj.sections[0]
+            .relocations
+            .insert(8, ObjReloc {
+                kind: ObjRelocKind::PpcEmbNaddr32,
+                target_symbol: 1,
+                addend: 4,
+                module: None,
+            })
+            .unwrap();
+        let data = write_elf(&obj, false).unwrap();
+
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let text = file.section_by_name(".text").unwrap();
+        let (address, reloc) = text.relocations().nth(1).unwrap();
+        assert_eq!(address, 8);
+        assert_eq!(reloc.flags(), RelocationFlags::Elf { r_type: elf::R_PPC_EMB_NADDR32 });
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let reloc = obj.sections[0].relocations.at(8).unwrap();
+        assert_eq!(reloc.kind, ObjRelocKind::PpcEmbNaddr32);
+        assert_eq!(reloc.addend, 4);
+        assert_eq!(obj.symbols[reloc.target_symbol].name, "bar");
+
+        let err =
+            to_obj_reloc_kind(RelocationFlags::Elf { r_type: elf::R_PPC_EMB_RELSDA }).unwrap_err();
+        assert!(err.to_string().contains("R_PPC_EMB_RELSDA"));
+    }
+
+    #[test]
+    fn test_paired_single_sda21_reloc() {
+        let mut obj = test_obj();
+        // psq_l f1, 0(r0), 1, 2
+        obj.sections[0].data[8..].copy_from_slice(&0xE020A000u32.to_be_bytes());
+        let sdata_var = obj
+            .symbols
+            .add_direct(ObjSymbol {
+                name: "sdata_var".to_string(),
+                flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
+                kind: ObjSymbolKind::Object,
+                ..Default::default()
+            })
+            .unwrap();
+        obj.sections[0]
+            .relocations
+            .insert(8, ObjReloc {
+                kind: ObjRelocKind::PpcEmbSda21,
+                target_symbol: sdata_var,
+                addend: 8,
+                module: None,
+            })
+            .unwrap();
+        let data = write_elf(&obj, false).unwrap();
+
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let text = file.section_by_name(".text").unwrap();
+        let (address, reloc) = text.relocations().nth(1).unwrap();
+        assert_eq!(address, 8);
+        assert_eq!(reloc.flags(), RelocationFlags::Elf { r_type: elf::R_PPC_EMB_SDA21 });
+        // The W and I fields are preserved
+        assert_eq!(&text.data().unwrap()[8..12], &0xE020A000u32.to_be_bytes());
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let reloc = obj.sections[0].relocations.at(8).unwrap();
+        assert_eq!((reloc.kind, reloc.addend), (ObjRelocKind::PpcEmbSda21, 8));
+        assert_eq!(obj.symbols[reloc.target_symbol].name, "sdata_var");
+        assert_eq!(&obj.sections[0].data[8..12], &0xE020A000u32.to_be_bytes());
+
+        // Implicit addends are read from the 12-bit displacement
+        let ins = 0xE020AFF8u32.to_be_bytes(); // psq_l f1, -8(r0), 1, 2
+        assert_eq!(implicit_addend(&ins, 0, ObjRelocKind::PpcEmbSda21, Endian::Big).unwrap(), -8);
+    }
+
+    #[test]
+    fn test_implicit_hi_lo_addends() {
+        let text = [
+            0x3C, 0x60, 0x00, 0x01, // lis r3, 0x1
+            0x38, 0x63, 0x80, 0x00, // addi r3, r3, -0x8000
+            0x3C, 0x80, 0x00, 0x01, // lis r4, 0x1
+            0x60, 0x84, 0x80, 0x00, // ori r4, r4, 0x8000
+        ];
+        let data = write_test_object(elf::ET_EXEC, &text, &[("foo", elf::STT_FUNC, 0)], &[
+            (2, elf::R_PPC_ADDR16_HA),
+            (6, elf::R_PPC_ADDR16_LO),
+            (10, elf::R_PPC_ADDR16_HI),
+            (14, elf::R_PPC_ADDR16_LO),
+        ]);
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, section) = obj.sections.by_name(".text").unwrap().unwrap();
+        let addends =
+            section.relocations.iter().map(|(_, r)| (r.kind, r.addend)).collect::<Vec<_>>();
+        assert_eq!(addends, vec![
+            (ObjRelocKind::PpcAddr16Ha, 0x8000),
+            (ObjRelocKind::PpcAddr16Lo, 0x8000),
+            (ObjRelocKind::PpcAddr16Hi, 0x18000),
+            (ObjRelocKind::PpcAddr16Lo, 0x18000),
+        ]);
+
+        // HI/HA without a paired LO can't be decoded
+        let data = write_test_object(elf::ET_EXEC, &text[..4], &[("foo", elf::STT_FUNC, 0)], &[(
+            2,
+            elf::R_PPC_ADDR16_HA,
+        )]);
+        assert!(process_elf_data(&data, |_| {}).is_err());
+    }
+
+    #[test]
+    fn test_executable_implicit_addends() {
+        // Executable fields hold the resolved value S + A, or S + A - P for REL24
+        let text = [
+            0x3C, 0x60, 0x80, 0x00, // lis r3, foo+0x10@ha
+            0x38, 0x63, 0x31, 0x18, // addi r3, r3, foo+0x10@l
+            0x48, 0x00, 0x00, 0x05, // bl foo+0x4
+            0x80, 0x00, 0x31, 0x18, // .4byte foo+0x10
+        ];
+        let data = write_test_object_at(
+            elf::ET_EXEC,
+            0x80003100,
+            &text,
+            &[("foo", elf::STT_FUNC, 0x80003108)],
+            &[
+                (0x80003102, elf::R_PPC_ADDR16_HA),
+                (0x80003106, elf::R_PPC_ADDR16_LO),
+                (0x80003108, elf::R_PPC_REL24),
+                (0x8000310C, elf::R_PPC_ADDR32),
+            ],
+        );
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, section) = obj.sections.by_name(".text").unwrap().unwrap();
+        let addends = section
+            .relocations
+            .iter()
+            .map(|(addr, r)| (addr, r.kind, r.addend))
+            .collect::<Vec<_>>();
+        assert_eq!(addends, vec![
+            (0x80003100, ObjRelocKind::PpcAddr16Ha, 0x10),
+            (0x80003104, ObjRelocKind::PpcAddr16Lo, 0x10),
+            (0x80003108, ObjRelocKind::PpcRel24, 0x4),
+            (0x8000310C, ObjRelocKind::Absolute, 0x10),
+        ]);
+    }
+
+    #[test]
+    fn test_write_elf_to() {
+        let obj = test_obj();
+        let mut out = Cursor::new(Vec::new());
+        write_elf_to(&obj, false, &mut out).unwrap();
+        assert_eq!(out.into_inner(), write_elf(&obj, false).unwrap());
+    }
+
+    #[test]
+    fn test_duplicate_file_names() {
+        let data = write_test_object(
+            elf::ET_EXEC,
+            &[0; 16],
+            &[
+                ("foo.cpp", elf::STT_FILE, 0),
+                ("fn_0", elf::STT_FUNC, 0),
+                ("foo.cpp_1", elf::STT_FILE, 0),
+                ("fn_4", elf::STT_FUNC, 4),
+                ("foo.cpp", elf::STT_FILE, 0),
+                ("fn_8", elf::STT_FUNC, 8),
+                ("foo.cpp", elf::STT_FILE, 0),
+                ("fn_c", elf::STT_FUNC, 12),
+            ],
+            &[],
+        );
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let names = obj.link_order.iter().map(|u| u.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["foo.cpp", "foo.cpp_1", "foo.cpp_2", "foo.cpp_3"]);
+    }
+
+    #[test]
+    fn test_bss_round_trip() {
+        let mut obj = test_obj();
+        let bss = obj.sections.push(test_section(".bss", ObjSectionKind::Bss, 0, 0x20));
+        obj.symbols
+            .add_direct(ObjSymbol {
+                kind: ObjSymbolKind::Object,
+                ..test_symbol("buffer", bss, 0x10, 0x10)
+            })
+            .unwrap();
+
+        let data = write_elf(&obj, false).unwrap();
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (bss, section) = obj.sections.by_name(".bss").unwrap().unwrap();
+        assert_eq!(section.kind, ObjSectionKind::Bss);
+        assert_eq!(section.size, 0x20);
+        assert!(section.data.is_empty());
+        let (_, symbol) = obj.symbols.by_name("buffer").unwrap().unwrap();
+        assert_eq!((symbol.section, symbol.address, symbol.size), (Some(bss), 0x10, 0x10));
+        assert_eq!(write_elf(&obj, false).unwrap(), data);
+    }
+
+    #[test]
+    fn test_start_address() {
+        let mut obj = test_obj();
+        let data = write_elf(&obj, false).unwrap();
+        assert_eq!(process_elf_data(&data, |_| {}).unwrap().start_address, None);
+
+        obj.symbols.add_direct(test_symbol("__start", 0, 8, 4)).unwrap();
+        let data = write_elf(&obj, false).unwrap();
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        assert_eq!(obj.entry, None);
+        assert_eq!(obj.start_address, Some(SectionAddress::new(0, 8)));
+    }
+
+    #[test]
+    fn test_entry_chain() {
+        let text = [0x4E800020u32; 3].iter().flat_map(|w| w.to_be_bytes()).collect::<Vec<_>>();
+        let data = write_test_object(
+            elf::ET_EXEC,
+            &text,
+            &[
+                ("__init_user", elf::STT_FUNC, 4),
+                ("__start", elf::STT_FUNC, 0),
+                ("game_init", elf::STT_FUNC, 8),
+            ],
+            &[],
+        );
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        assert_eq!(obj.entry_chain, vec![
+            ("__start".to_string(), SectionAddress::new(0, 0)),
+            ("__init_user".to_string(), SectionAddress::new(0, 4))
+        ]);
+
+        let options =
+            ReadElfOptions { runtime_symbols: vec!["game_init".to_string()], ..Default::default() };
+        let obj = process_elf_inner(&data, options, |_| {}, |_| {}).unwrap();
+        assert_eq!(
+            obj.entry_chain.last(),
+            Some(&("game_init".to_string(), SectionAddress::new(0, 8)))
+        );
+    }
+
+    #[test]
+    fn test_read_raw() {
+        let text = [0x4E800020u32; 3].iter().flat_map(|w| w.to_be_bytes()).collect::<Vec<_>>();
+        let data = write_test_object(
+            elf::ET_EXEC,
+            &text,
+            &[
+                ("a.c", elf::STT_FILE, 0),
+                ("foo", elf::STT_FUNC, 0),
+                ("Precompiled.cpp", elf::STT_FILE, 0),
+                ("pch_fn", elf::STT_FUNC, 4),
+                ("a.c", elf::STT_FILE, 0),
+                ("bar", elf::STT_FUNC, 8),
+            ],
+            &[],
+        );
+        let unit_names =
+            |obj: &ObjInfo| obj.link_order.iter().map(|u| u.name.clone()).collect::<Vec<_>>();
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        assert_eq!(unit_names(&obj), vec!["a.c", "a.c_1"]);
+
+        let options = ReadElfOptions { raw: true, ..Default::default() };
+        let obj = process_elf_inner(&data, options, |_| {}, |_| {}).unwrap();
+        assert_eq!(unit_names(&obj), vec!["a.c", "Precompiled.cpp"]);
+        // Every symbol is kept at its original index, without the null symbol
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        assert_eq!(obj.symbols.count() as usize, file.symbols().count());
+        for symbol in file.symbols() {
+            let obj_symbol = &obj.symbols[symbol.index().0 as ObjSymbolIndex - 1];
+            assert_eq!(obj_symbol.name, symbol.name().unwrap());
+        }
+        let files = obj.symbols.iter().filter(|(_, s)| s.kind == ObjSymbolKind::File).count();
+        assert_eq!(files, 3);
+    }
+
+    #[test]
+    fn test_strip_file_paths() {
+        let text = 0x4E800020u32.to_be_bytes();
+        let data = write_test_object(
+            elf::ET_REL,
+            &text,
+            &[("src\\game/foo.cpp", elf::STT_FILE, 0), ("foo", elf::STT_FUNC, 0)],
+            &[],
+        );
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        assert_eq!(obj.name, "src\\game/foo.cpp");
+
+        let options = ReadElfOptions { strip_file_paths: true, ..Default::default() };
+        let obj = process_elf_inner(&data, options.clone(), |_| {}, |_| {}).unwrap();
+        assert_eq!(obj.name, "foo.cpp");
+        // The written file symbol agrees with the unit name
+        let data = write_elf(&obj, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let file_symbol = file.symbols().find(|s| s.kind() == SymbolKind::File).unwrap();
+        assert_eq!(file_symbol.name().unwrap(), "foo.cpp");
+        let obj = process_elf_inner(&data, options, |_| {}, |_| {}).unwrap();
+        assert_eq!(obj.name, "foo.cpp");
+    }
+
+    #[test]
+    fn test_noload_section() {
+        let mut obj = test_obj();
+        obj.kind = ObjKind::Executable;
+        obj.sections[0].relocations = Default::default();
+        obj.sections.push(ObjSection {
+            load: false,
+            ..test_section(".noload", ObjSectionKind::Bss, 0x100, 0x20)
+        });
+
+        let data = write_elf(&obj, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        assert_eq!(file.segments().count(), 1);
+        let section = file.section_by_name(".noload").unwrap();
+        assert_eq!(section.flags(), SectionFlags::Elf { sh_flags: (SHF_ALLOC | SHF_WRITE) as u64 });
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert!(text.alloc && text.load);
+        let (_, section) = obj.sections.by_name(".noload").unwrap().unwrap();
+        assert_eq!(section.kind, ObjSectionKind::Bss);
+        assert!(section.alloc && !section.load);
+        let data = write_elf(&obj, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        assert_eq!(file.segments().count(), 1);
+    }
+
+    #[test]
+    fn test_shared_segment_round_trip() {
+        let mut obj = test_obj();
+        obj.kind = ObjKind::Executable;
+        obj.sections[0].relocations = Default::default();
+        obj.sections[0].segment = Some(0);
+        let data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        obj.sections.push(ObjSection {
+            segment: Some(0),
+            ..test_section_data(".data", ObjSectionKind::Data, 0x10, data)
+        });
+        obj.sections.push(ObjSection {
+            segment: Some(0),
+            ..test_section(".bss", ObjSectionKind::Bss, 0x18, 8)
+        });
+
+        let data = write_elf(&obj, false).unwrap();
+        assert_eq!(write_elf_size(&obj).unwrap(), data.len());
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let segments = file.segments().collect::<Vec<_>>();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].address(), 0);
+        assert_eq!(segments[0].size(), 0x20);
+        assert_eq!(segments[0].file_range().1, 0x18);
+        let text_offset = file.section_by_name(".text").unwrap().file_range().unwrap().0;
+        assert_eq!(segments[0].file_range().0, text_offset);
+        let data_section = file.section_by_name(".data").unwrap();
+        assert_eq!(data_section.file_range().unwrap().0, text_offset + 0x10);
+
+        let read = process_elf_data(&data, |_| {}).unwrap();
+        for (_, section) in read.sections.iter() {
+            assert_eq!(section.segment, Some(0), "{}", section.name);
+        }
+        let (_, section) = read.sections.by_name(".data").unwrap().unwrap();
+        assert_eq!(section.data, obj.sections[1].data);
+        let data = write_elf(&read, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        assert_eq!(file.segments().count(), 1);
+    }
+
+    #[test]
+    fn test_section_order() {
+        let mut obj = test_obj();
+        obj.sections.push(test_section(".data", ObjSectionKind::Data, 0, 4));
+
+        let options = WriteElfOptions { section_order: vec![1, 0], ..Default::default() };
+        let data = write_elf_with_options(&obj, &options).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let names = file.sections().map(|s| s.name().unwrap().to_string()).collect::<Vec<_>>();
+        assert_eq!(&names[1..4], [".data", ".text", ".rela.text"]);
+        let text = file.section_by_name(".text").unwrap();
+        assert_eq!(text.index().0, 2);
+        let (address, reloc) = text.relocations().next().unwrap();
+        assert_eq!(address, 0);
+        let RelocationTarget::Symbol(symbol_index) = reloc.target() else { panic!() };
+        assert_eq!(file.symbol_by_index(symbol_index).unwrap().name().unwrap(), "bar");
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!(text.relocations.len(), 1);
+
+        let options = WriteElfOptions { section_order: vec![1, 1], ..Default::default() };
+        assert!(write_elf_with_options(&obj, &options).is_err());
+    }
+
+    #[test]
+    fn test_overlay_section_names() {
+        let mut obj = test_obj();
+        obj.sections.push(test_section_words(".text.a", ObjSectionKind::Code, 0, &[0x4E800020]));
+        assert_eq!(section_kind_for_section(".text.a").unwrap(), ObjSectionKind::Code);
+        assert!(section_kind_for_section(".foo.a").is_err());
+        // GCC function sections aren't overlays
+        assert_eq!(split_section_suffix(".text.foo"), (".text.foo", None));
+        assert!(section_kind_for_section(".text.foo").is_err());
+
+        let data = write_elf(&obj, false).unwrap();
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, text) = obj.sections.by_name(".text").unwrap().unwrap();
+        assert_eq!((text.base_name(), text.suffix()), (".text", None));
+        let (_, overlay) = obj.sections.by_name(".text.a").unwrap().unwrap();
+        assert_eq!(overlay.kind, ObjSectionKind::Code);
+        assert_eq!((overlay.base_name(), overlay.suffix()), (".text", Some("a")));
+
+        let data = write_elf(&obj, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        assert!(file.section_by_name(".text").is_some());
+        assert!(file.section_by_name(".text.a").is_some());
+    }
+
+    #[test]
+    fn test_empty_section_round_trip() {
+        let mut obj = test_obj();
+        obj.sections
+            .push(ObjSection { align: 8, ..test_section(".data", ObjSectionKind::Data, 0, 0) });
+
+        let data = write_elf(&obj, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let section = file.section_by_name(".data").unwrap();
+        assert_eq!((section.size(), section.align()), (0, 8));
+        let (offset, _) = section.file_range().unwrap_or((0, 0));
+        assert!(offset != 0 && offset <= data.len() as u64);
+
+        let mut obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, section) = obj.sections.by_name(".data").unwrap().unwrap();
+        assert_eq!((section.kind, section.size, section.align), (ObjSectionKind::Data, 0, 8));
+        assert_eq!(write_elf(&obj, false).unwrap(), data);
+
+        // No program header for the empty section
+        obj.kind = ObjKind::Executable;
+        for (_, section) in obj.sections.iter_mut() {
+            section.relocations = Default::default();
+        }
+        let data = write_elf(&obj, false).unwrap();
+        assert_eq!(write_elf_size(&obj).unwrap(), data.len());
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        assert_eq!(file.segments().count(), 1);
+    }
+
+    #[test]
+    fn test_dwarf_unit_name() {
+        let mut obj = test_obj();
+        // GCC objects may have no file symbol
+        obj.name.clear();
+        let abbrev = vec![
+            1, 0x11, 0, // DW_TAG_compile_unit, no children
+            0x25, 0x08, // DW_AT_producer, DW_FORM_string
+            0x13, 0x0B, // DW_AT_language, DW_FORM_data1
+            0x03, 0x08, // DW_AT_name, DW_FORM_string
+            0, 0, 0,
+        ];
+        let mut die = vec![0, 2, 0, 0, 0, 0, 4, 1];
+        die.extend_from_slice(b"GNU C 4.8.0\0");
+        die.push(1);
+        die.extend_from_slice(b"src/foo.c\0");
+        die.push(0);
+        let mut info = (die.len() as u32).to_be_bytes().to_vec();
+        info.extend(die);
+        for (name, data) in [(".debug_abbrev", abbrev), (".debug_info", info)] {
+            obj.raw_sections.push(ObjRawSection {
+                name: name.to_string(),
+                sh_type: SHT_PROGBITS,
+                sh_flags: 0,
+                align: 1,
+                data,
+            });
+        }
+
+        let data = write_elf(&obj, false).unwrap();
+        let read = process_elf_data(&data, |_| {}).unwrap();
+        assert_eq!(read.name, "src/foo.c");
+
+        // DWARF 5 units are rejected, leaving the name empty
+        let debug_info = obj.raw_sections.iter_mut().find(|s| s.name == ".debug_info").unwrap();
+        debug_info.data[5] = 5;
+        let data = write_elf(&obj, false).unwrap();
+        let read = process_elf_data(&data, |_| {}).unwrap();
+        assert_eq!(read.name, "");
+
+        let data = write_elf(&test_obj(), false).unwrap();
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        assert_eq!(obj.name, "test.o");
+    }
+
+    #[test]
+    fn test_boundary_report() {
+        let data = write_test_object(
+            elf::ET_EXEC,
+            &[0x4E, 0x80, 0x00, 0x20, 0x4E, 0x80, 0x00, 0x20],
+            &[
+                ("a.c", elf::STT_FILE, 0),
+                (".text", elf::STT_SECTION, 0),
+                ("foo", elf::STT_FUNC, 0),
+                ("b.c", elf::STT_FILE, 0),
+                ("bar", elf::STT_FUNC, 4),
+            ],
+            &[],
+        );
+        let mut events = vec![];
+        process_elf_inner(&data, ReadElfOptions::default(), |_| {}, |event| events.push(event))
+            .unwrap();
+        assert_eq!(events, vec![
+            BoundaryEvent::FileStarted("a.c".to_string()),
+            BoundaryEvent::SectionAttributed {
+                section: ".text".to_string(),
+                address: 0,
+                file: "a.c".to_string(),
+            },
+            BoundaryEvent::FileStarted("b.c".to_string()),
+            BoundaryEvent::SectionAttributed {
+                section: ".text".to_string(),
+                address: 4,
+                file: "b.c".to_string(),
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_eh_frame_round_trip() {
+        let mut obj = test_obj();
+        let (foo, _) = obj.symbols.by_name("foo").unwrap().unwrap();
+        #[rustfmt::skip]
+        let data = vec![
+            // CIE
+            0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x01, 0x7A, 0x52, 0x00, 0x04, 0x7C,
+            0x41, 0x01, 0x1B, 0x0C, 0x01, 0x00,
+            // FDE, pc_begin relative to foo
+            0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x08, 0x00, 0x00, 0x00, 0x00,
+            // Terminator
+            0x00, 0x00, 0x00, 0x00,
+        ];
+        let eh_frame = obj.sections.push(test_section_data(
+            ".eh_frame",
+            ObjSectionKind::ReadOnlyData,
+            0,
+            data,
+        ));
+        obj.sections[eh_frame]
+            .relocations
+            .insert(0x1C, ObjReloc {
+                kind: ObjRelocKind::PpcRel32,
+                target_symbol: foo,
+                addend: 0,
+                module: None,
+            })
+            .unwrap();
+
+        let data = write_elf(&obj, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let (offset, reloc) =
+            file.section_by_name(".eh_frame").unwrap().relocations().next().unwrap();
+        assert_eq!(offset, 0x1C);
+        assert_eq!(reloc.flags(), RelocationFlags::Elf { r_type: elf::R_PPC_REL32 });
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let (_, section) = obj.sections.by_name(".eh_frame").unwrap().unwrap();
+        assert_eq!(section.size, 0x2C);
+        let reloc = section.relocations.at(0x1C).unwrap();
+        assert_eq!(reloc.kind, ObjRelocKind::PpcRel32);
+        assert_eq!(obj.symbols[reloc.target_symbol].name, "foo");
+        assert_eq!(write_elf(&obj, false).unwrap(), data);
+    }
+
+    #[test]
+    fn test_duplicate_section_names() {
+        let mut builder = ObjInfoBuilder::new(ObjKind::Executable, "test".to_string());
+        let mut sections = vec![];
+        for address in [0x80003000, 0x80004000] {
+            sections.push(builder.add_section(test_section_words(
+                ".text",
+                ObjSectionKind::Code,
+                address,
+                &[0x4E800020],
+            )));
+        }
+        for (name, (section, address)) in
+            ["foo", "bar"].into_iter().zip(sections.iter().zip([0x80003000, 0x80004000]))
+        {
+            builder.add_symbol(test_symbol(name, *section, address, 4));
+        }
+        let data = write_elf(&builder.build().unwrap(), false).unwrap();
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        assert_eq!(obj.sections.len(), 2);
+        for (name, section_index, address) in [("foo", 0, 0x80003000), ("bar", 1, 0x80004000)] {
+            let (_, symbol) = obj.symbols.by_name(name).unwrap().unwrap();
+            assert_eq!(symbol.section, Some(section_index));
+            let splits = obj.sections[section_index].splits.iter().collect::<Vec<_>>();
+            assert_eq!(splits.len(), 1);
+            assert_eq!((splits[0].0, splits[0].1.unit.as_str()), (address, "test"));
+        }
+    }
+
+    #[test]
+    fn test_raw_section_round_trip() {
+        let mut obj = test_obj();
+        obj.raw_sections.push(ObjRawSection {
+            name: ".line".to_string(),
+            sh_type: SHT_PROGBITS,
+            sh_flags: 0,
+            align: 4,
+            data: vec![0, 0, 0, 0x10, 0, 0, 0, 0, 0, 0, 0, 0x0C, 0xFF, 0xFF, 0, 0],
+        });
+
+        let data = write_elf(&obj, false).unwrap();
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        assert!(obj.sections.by_name(".line").unwrap().is_none());
+        assert_eq!(obj.raw_sections.len(), 1);
+        let section = &obj.raw_sections[0];
+        assert_eq!(section.name, ".line");
+        assert_eq!((section.sh_type, section.sh_flags, section.align), (SHT_PROGBITS, 0, 4));
+        assert_eq!(section.data.len(), 16);
+        assert_eq!(write_elf(&obj, false).unwrap(), data);
+    }
+
+    #[test]
+    fn test_hash_table() {
+        let obj = test_obj();
+        let options = WriteElfOptions { hash_table: true, ..Default::default() };
+        let data = write_elf_with_options(&obj, &options).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let hash = file.section_by_name(".hash").unwrap().data().unwrap();
+        let words = hash
+            .chunks_exact(4)
+            .map(|c| u32::from_be_bytes(c.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        let (nbucket, nchain) = (words[0] as usize, words[1] as usize);
+        assert_eq!(words.len(), 2 + nbucket + nchain);
+        assert_eq!(nchain, file.symbols().count() + 1);
+        for name in ["foo", "bar"] {
+            assert!(hash_contains(&data, name), "{name} not found in hash table");
+        }
+
+        // Existing hash tables are regenerated rather than passed through
+        let mut obj = process_elf_data(&data, |_| {}).unwrap();
+        assert_eq!(obj.raw_sections.len(), 1);
+        assert_eq!(obj.raw_sections[0].sh_type, elf::SHT_HASH);
+        assert_eq!(write_elf_with_options(&obj, &options).unwrap(), data);
+        assert_eq!(write_elf(&obj, false).unwrap(), data);
+        assert!(write_elf(&test_obj(), false).unwrap().len() < data.len());
+        obj.add_symbol(test_symbol("baz", 0, 0, 0), false).unwrap();
+        let data = write_elf(&obj, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        assert_eq!(file.sections().filter(|s| s.name().is_ok_and(|n| n == ".hash")).count(), 1);
+        assert!(hash_contains(&data, "baz"));
+
+        // GNU hash tables can't be regenerated for the symbol table, so they're dropped
+        obj.raw_sections.push(ObjRawSection {
+            name: ".gnu.hash".to_string(),
+            sh_type: elf::SHT_GNU_HASH,
+            sh_flags: 0,
+            align: 4,
+            data: vec![0; 16],
+        });
+        let data = write_elf(&obj, false).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        assert!(file.section_by_name(".gnu.hash").is_none());
+        assert!(hash_contains(&data, "baz"));
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        assert!(obj.raw_sections.iter().all(|s| s.sh_type == elf::SHT_HASH));
+    }
+
+    /// Looks up `name` in the SysV `.hash` section of an ELF file.
+    fn hash_contains(data: &[u8], name: &str) -> bool {
+        let file = object::read::File::parse(data).unwrap();
+        let hash = file.section_by_name(".hash").unwrap().data().unwrap();
+        let words = hash
+            .chunks_exact(4)
+            .map(|c| u32::from_be_bytes(c.try_into().unwrap()))
+            .collect::<Vec<_>>();
+        let nbucket = words[0] as usize;
+        let (buckets, chains) = words[2..].split_at(nbucket);
+        let mut index = buckets[symbol_hash(name) as usize % nbucket];
+        while index != 0 {
+            let symbol = file.symbol_by_index(object::SymbolIndex(index as usize)).unwrap();
+            if symbol.name().unwrap() == name {
+                return true;
+            }
+            index = chains[index as usize];
+        }
+        false
+    }
+
+    #[test]
+    fn test_compressed_sections() {
+        let mut obj = test_obj();
+        let debug_info = b"debug info ".repeat(64);
+        for (name, data) in [(".debug_info", debug_info.clone()), (".line", vec![1, 2, 3, 4])] {
+            obj.raw_sections.push(ObjRawSection {
+                name: name.to_string(),
+                sh_type: SHT_PROGBITS,
+                sh_flags: 0,
+                align: 1,
+                data,
+            });
+        }
+        let options = WriteElfOptions {
+            compress_sections: vec![".debug*".to_string()],
+            ..Default::default()
+        };
+        let data = write_elf_with_options(&obj, &options).unwrap();
+        let file = object::read::File::parse(data.as_slice()).unwrap();
+        let flags = |name: &str| match file.section_by_name(name).unwrap().flags() {
+            SectionFlags::Elf { sh_flags } => sh_flags,
+            _ => unreachable!(),
+        };
+        assert_ne!(flags(".debug_info") & elf::SHF_COMPRESSED as u64, 0);
+        assert_eq!(flags(".line") & elf::SHF_COMPRESSED as u64, 0);
+        assert!(file.section_by_name(".debug_info").unwrap().size() < debug_info.len() as u64);
+
+        let obj = process_elf_data(&data, |_| {}).unwrap();
+        let section = obj.raw_sections.iter().find(|s| s.name == ".debug_info").unwrap();
+        assert_eq!(section.data, debug_info);
+        assert_eq!((section.sh_flags, section.align), (0, 1));
+    }
+
+    #[test]
+    fn test_incremental_reuses_section_data() {
+        let mut obj = test_obj();
+        let previous = process_elf_data(&write_elf(&obj, false).unwrap(), |_| {}).unwrap();
+        let text_ptr = previous.sections[0].data.as_ptr();
+
+        let (foo, _) = obj.symbols.by_name("foo").unwrap().unwrap();
+        let symbol = ObjSymbol { name: "baz".to_string(), ..obj.symbols[foo].clone() };
+        obj.symbols.replace(foo, symbol).unwrap();
+        let data = write_elf(&obj, false).unwrap();
+        let options = ReadElfOptions { previous: Some(previous), ..Default::default() };
+        let obj = process_elf_inner(&data, options, |_| {}, |_| {}).unwrap();
+        assert!(obj.symbols.by_name("baz").unwrap().is_some());
+        assert_eq!(obj.sections[0].data.as_ptr(), text_ptr);
+        assert_eq!(obj.sections[0].data, test_obj().sections[0].data);
+
+        // Changed contents are re-read
+        let mut changed = test_obj();
+        changed.sections[0].data[8..12].copy_from_slice(&[0x4E, 0x80, 0x00, 0x20]);
+        let data = write_elf(&changed, false).unwrap();
+        let options = ReadElfOptions { previous: Some(obj), ..Default::default() };
+        let obj = process_elf_inner(&data, options, |_| {}, |_| {}).unwrap();
+        assert_ne!(obj.sections[0].data.as_ptr(), text_ptr);
+        assert_eq!(obj.sections[0].data, changed.sections[0].data);
+    }
+
+    #[test]
+    fn test_process_elf_progress() {
+        let data = write_elf(&test_obj(), false).unwrap();
+        let mut events = vec![];
+        let obj = process_elf_data(&data, |event| events.push(event)).unwrap();
+        assert!(obj.symbols.by_name("bar").unwrap().is_some());
+        assert!(events.contains(&ProgressEvent::SectionsLoaded(1)));
+        assert!(events.iter().any(|e| matches!(e, ProgressEvent::SymbolsProcessed(_))));
+        assert!(events.contains(&ProgressEvent::RelocationsProcessed(1)));
+    }
+}
@@ -23,7 +23,7 @@ use crate::{
         ObjSections, ObjSplit, ObjSymbol, ObjSymbolFlagSet, ObjSymbolFlags, ObjSymbolKind,
         ObjSymbols, ObjUnit, SectionIndex,
     },
-    util::nested::NestedVec,
+    util::{nested::NestedVec, split::default_section_align},
     vfs::open_file,
 };
 
@@ -908,7 +908,7 @@ pub fn create_obj(result: &MapInfo) -> Result<ObjInfo> {
             let name = s.name.clone();
             let address = s.address as u64;
             let size = s.size as u64;
-            let file_offset = s.file_offset as u64;
+            let file_offset = Some(s.file_offset as u64);
             let kind = section_kind_for_section(&name).unwrap_or(ObjSectionKind::ReadOnlyData);
             ObjSection {
                 name,
@@ -922,19 +922,27 @@ pub fn create_obj(result: &MapInfo) -> Result<ObjInfo> {
                 virtual_address: None,
                 file_offset,
                 section_known: true,
+                alloc: true,
+                load: true,
                 splits: Default::default(),
+                segment: None,
             }
         })
         .collect();
     let mut obj = ObjInfo {
         kind: ObjKind::Executable,
         architecture: ObjArchitecture::PowerPc,
+        endian: Default::default(),
         name: "".to_string(),
         symbols: ObjSymbols::new(ObjKind::Executable, vec![]),
         sections: ObjSections::new(ObjKind::Executable, sections),
         entry: None, // TODO result.entry_point
+        start_address: None,
+        entry_chain: vec![],
+        e_flags: None,
         mw_comment: None,
         split_meta: None,
+        raw_sections: vec![],
         sda2_base: None,
         sda_base: None,
         stack_address: None,
@@ -1009,6 +1017,85 @@ pub fn create_obj(result: &MapInfo) -> Result<ObjInfo> {
     Ok(obj)
 }
 
+/// Writes a Metrowerks linker style map for the object: the layout of each section,
+/// listing units in split order followed by their symbols, then the memory map and
+/// linker generated (absolute) symbols. Sections without splits are attributed to a
+/// single unit named after the object. No link map is written, since the references
+/// needed to reconstruct it aren't recorded.
+pub fn write_map(obj: &ObjInfo) -> String {
+    let mut out = String::new();
+    for (section_index, section) in obj.sections.iter() {
+        let section_end = (section.address + section.size) as u32;
+        let mut units = section
+            .splits
+            .iter()
+            .map(|(addr, split)| {
+                let end = split.range(addr).end.min(section_end);
+                let align = split.alignment(obj, section_index, section, addr);
+                (addr, end, split.unit.as_str(), align)
+            })
+            .collect_vec();
+        if units.is_empty() {
+            let align = section.align.max(default_section_align(section)) as u32;
+            units.push((section.address as u32, section_end, obj.name.as_str(), align));
+        }
+
+        out.push_str(&format!("\n{} section layout\n", section.name));
+        out.push_str("  Starting        Virtual\n");
+        out.push_str("  address  Size   address\n");
+        out.push_str("  -----------------------\n");
+        for (start, end, unit, align) in units {
+            let offset = start - section.address as u32;
+            out.push_str(&format!(
+                "  {:08x} {:06x} {:08x} {:2} {} \t{}\n",
+                offset,
+                end - start,
+                start,
+                align,
+                section.name,
+                unit
+            ));
+            for (_, symbol) in obj.symbols.for_section_range(section_index, start..end) {
+                if symbol.name.is_empty() || symbol.kind == ObjSymbolKind::Section {
+                    continue;
+                }
+                out.push_str(&format!(
+                    "  {:08x} {:06x} {:08x} {:2} {} \t{}\n",
+                    symbol.address as u32 - section.address as u32,
+                    symbol.size,
+                    symbol.address,
+                    symbol.align.unwrap_or(align),
+                    symbol.name,
+                    unit
+                ));
+            }
+        }
+    }
+
+    out.push_str("\n\nMemory map:\n");
+    out.push_str("                   Starting Size     File\n");
+    out.push_str("                   address           Offset\n");
+    for (_, section) in obj.sections.iter() {
+        out.push_str(&format!(
+            "  {:>16} {:08x} {:08x} {:08x}\n",
+            section.name,
+            section.address,
+            section.size,
+            section.file_offset.unwrap_or_default()
+        ));
+    }
+
+    out.push_str("\n\nLinker generated symbols:\n");
+    for (_, symbol) in obj
+        .symbols
+        .iter()
+        .filter(|(_, s)| s.section.is_none() && s.address != 0 && !s.flags.is_common())
+    {
+        out.push_str(&format!("  {:>25} {:08x}\n", symbol.name, symbol.address));
+    }
+    out
+}
+
 fn add_symbol(
     obj: &mut ObjInfo,
     symbol_entry: &SymbolEntry,
@@ -1051,3 +1138,83 @@ fn add_symbol(
     )?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::testing::{test_section, test_symbol};
+
+    #[test]
+    fn test_write_map() {
+        let mut text = test_section(".text", ObjSectionKind::Code, 0x80003100, 0x10);
+        text.file_offset = Some(0x100);
+        for (address, unit) in [(0x80003100, "a.c"), (0x80003108, "b.c")] {
+            text.splits.push(address, ObjSplit {
+                unit: unit.to_string(),
+                end: address + 8,
+                align: None,
+                common: false,
+                autogenerated: false,
+                skip: false,
+                rename: None,
+            });
+        }
+        let mut data = test_section(".data", ObjSectionKind::Data, 0x80004000, 0x8);
+        data.file_offset = Some(0x120);
+        let obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "main.dol".to_string(),
+            vec![
+                test_symbol("foo", 0, 0x80003100, 8),
+                test_symbol("bar", 0, 0x80003108, 8),
+                ObjSymbol {
+                    kind: ObjSymbolKind::Object,
+                    align: Some(4),
+                    ..test_symbol("baz", 1, 0x80004000, 4)
+                },
+                ObjSymbol {
+                    kind: ObjSymbolKind::Object,
+                    section: None,
+                    ..test_symbol("_stack_addr", 0, 0x80500000, 0)
+                },
+            ],
+            vec![text, data],
+        );
+
+        let map = write_map(&obj);
+        assert_eq!(
+            map,
+            "\n.text section layout\n\
+             \x20 Starting        Virtual\n\
+             \x20 address  Size   address\n\
+             \x20 -----------------------\n\
+             \x20 00000000 000008 80003100  4 .text \ta.c\n\
+             \x20 00000000 000008 80003100  4 foo \ta.c\n\
+             \x20 00000008 000008 80003108  4 .text \tb.c\n\
+             \x20 00000008 000008 80003108  4 bar \tb.c\n\
+             \n.data section layout\n\
+             \x20 Starting        Virtual\n\
+             \x20 address  Size   address\n\
+             \x20 -----------------------\n\
+             \x20 00000000 000008 80004000  8 .data \tmain.dol\n\
+             \x20 00000000 000004 80004000  4 baz \tmain.dol\n\
+             \n\nMemory map:\n\
+             \x20                  Starting Size     File\n\
+             \x20                  address           Offset\n\
+             \x20            .text 80003100 00000010 00000100\n\
+             \x20            .data 80004000 00000008 00000120\n\
+             \n\nLinker generated symbols:\n\
+             \x20               _stack_addr 80500000\n"
+        );
+
+        // Readable by the map parser
+        let info = process_map(&mut map.as_bytes(), None, None).unwrap();
+        assert_eq!(info.sections.len(), 2);
+        assert_eq!(info.section_units[".text"], vec![
+            (0x80003100, "a.c".to_string()),
+            (0x80003108, "b.c".to_string())
+        ]);
+        assert_eq!(info.section_symbols[".data"][&0x80004000][0].name, "baz");
+    }
+}
@@ -1,6 +1,7 @@
 use std::{borrow::Cow, ops::Deref};
 
 pub mod alf;
+pub mod ar;
 pub mod asm;
 pub mod bin2c;
 pub mod comment;
@@ -21,6 +22,7 @@ pub mod rarc;
 pub mod read;
 pub mod reader;
 pub mod rel;
+pub mod report;
 pub mod rso;
 pub mod signatures;
 pub mod split;
@@ -1,12 +1,15 @@
 use std::{
+    fmt::UpperHex,
     io,
     io::{Error, ErrorKind, Read, Seek, SeekFrom},
+    marker::PhantomData,
 };
 
 use io::Write;
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum Endian {
+    #[default]
     Big,
     Little,
 }
@@ -20,6 +23,15 @@ impl From<object::Endianness> for Endian {
     }
 }
 
+impl From<Endian> for object::Endianness {
+    fn from(value: Endian) -> Self {
+        match value {
+            Endian::Big => object::Endianness::Big,
+            Endian::Little => object::Endianness::Little,
+        }
+    }
+}
+
 pub const DYNAMIC_SIZE: usize = 0;
 
 pub const fn struct_size<const N: usize>(fields: [usize; N]) -> usize {
@@ -97,6 +109,34 @@ macro_rules! impl_from_reader {
 
 impl_from_reader!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
 
+/// Fixed-size types that are valid for any bit pattern, allowing slices of them
+/// to be read directly and endian corrected in place.
+///
+/// # Safety
+/// Implementors must have no padding and no invalid bit patterns.
+pub unsafe trait FromReaderInPlace: FromReader + Copy {
+    /// Convert a value read as raw bytes in the given endianness to native endianness.
+    fn fix_endian(&mut self, e: Endian);
+}
+
+macro_rules! impl_from_reader_in_place {
+    ($($t:ty),*) => {
+        $(
+            unsafe impl FromReaderInPlace for $t {
+                #[inline]
+                fn fix_endian(&mut self, e: Endian) {
+                    *self = match e {
+                        Endian::Big => <$t>::from_be(*self),
+                        Endian::Little => <$t>::from_le(*self),
+                    };
+                }
+            }
+        )*
+    };
+}
+
+impl_from_reader_in_place!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
 impl<const N: usize> FromReader for [u8; N] {
     type Args = ();
 
@@ -111,27 +151,28 @@ impl<const N: usize> FromReader for [u8; N] {
     }
 }
 
-impl<const N: usize> FromReader for [u32; N] {
-    type Args = ();
+macro_rules! impl_from_reader_array {
+    ($($t:ty),*) => {
+        $(
+            impl<const N: usize> FromReader for [$t; N] {
+                type Args = ();
 
-    const STATIC_SIZE: usize = N * u32::STATIC_SIZE;
+                const STATIC_SIZE: usize = N * <$t>::STATIC_SIZE;
 
-    #[inline]
-    fn from_reader_args<R>(reader: &mut R, e: Endian, _args: Self::Args) -> io::Result<Self>
-    where R: Read + Seek + ?Sized {
-        let mut buf = [0u32; N];
-        reader.read_exact(unsafe {
-            std::slice::from_raw_parts_mut(buf.as_mut_ptr() as *mut u8, Self::STATIC_SIZE)
-        })?;
-        if e == Endian::Big {
-            for x in buf.iter_mut() {
-                *x = u32::from_be(*x);
+                #[inline]
+                fn from_reader_args<R>(reader: &mut R, e: Endian, _args: Self::Args) -> io::Result<Self>
+                where R: Read + Seek + ?Sized {
+                    let mut buf = [0 as $t; N];
+                    read_array_into(reader, &mut buf, e)?;
+                    Ok(buf)
+                }
             }
-        }
-        Ok(buf)
-    }
+        )*
+    };
 }
 
+impl_from_reader_array!(u16, i16, u32, i32);
+
 #[inline]
 pub fn read_bytes<R>(reader: &mut R, count: usize) -> io::Result<Vec<u8>>
 where R: Read + Seek + ?Sized {
@@ -154,6 +195,23 @@ where
     Ok(vec)
 }
 
+/// Fill `dest` with a single read, then endian correct each element in place.
+/// Avoids the per-element overhead of [`read_vec`] for large tables.
+#[inline]
+pub fn read_array_into<T, R>(reader: &mut R, dest: &mut [T], e: Endian) -> io::Result<()>
+where
+    T: FromReaderInPlace,
+    R: Read + Seek + ?Sized,
+{
+    reader.read_exact(unsafe {
+        std::slice::from_raw_parts_mut(dest.as_mut_ptr() as *mut u8, std::mem::size_of_val(dest))
+    })?;
+    for x in dest.iter_mut() {
+        x.fix_endian(e);
+    }
+    Ok(())
+}
+
 #[inline]
 pub fn read_vec_args<T, R>(
     reader: &mut R,
@@ -188,6 +246,25 @@ where
     String::from_utf8(buf).map_err(|e| Error::new(ErrorKind::InvalidData, e))
 }
 
+/// Reads an integer of type `T` and converts it to `E`, failing with
+/// [`ErrorKind::InvalidData`] if the value isn't a valid discriminant.
+#[inline]
+pub fn read_enum<E, T, R>(reader: &mut R, e: Endian) -> io::Result<E>
+where
+    E: TryFrom<T>,
+    T: FromReader + Copy + UpperHex,
+    T::Args: Default,
+    R: Read + Seek + ?Sized,
+{
+    let value = T::from_reader(reader, e)?;
+    E::try_from(value).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("invalid {} value {:#X}", std::any::type_name::<E>(), value),
+        )
+    })
+}
+
 pub trait ToWriter: Sized {
     fn to_writer<W>(&self, writer: &mut W, e: Endian) -> io::Result<()>
     where W: Write + ?Sized;
@@ -204,6 +281,14 @@ pub trait ToWriter: Sized {
         Ok(buf)
     }
 
+    /// Overwrites the bytes at `offset` in `buf` with this value.
+    fn to_writer_at(&self, buf: &mut [u8], offset: usize, e: Endian) -> io::Result<()> {
+        let mut slice = buf
+            .get_mut(offset..offset + self.write_size())
+            .ok_or_else(|| Error::new(ErrorKind::UnexpectedEof, "Write out of bounds"))?;
+        self.to_writer(&mut slice, e)
+    }
+
     fn write_size(&self) -> usize;
 }
 
@@ -263,6 +348,55 @@ impl ToWriter for Vec<u8> {
     fn write_size(&self) -> usize { self.len() }
 }
 
+/// A position reserved by [`PatchWriter::reserve_patch`], to be filled later.
+#[derive(Debug)]
+pub struct PatchHandle<T> {
+    offset: usize,
+    _marker: PhantomData<T>,
+}
+
+/// Buffer for formats with back-references, where a field (e.g. a table offset)
+/// is only known after later data has been written.
+#[derive(Debug, Default)]
+pub struct PatchWriter {
+    buf: Vec<u8>,
+}
+
+impl PatchWriter {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn position(&self) -> usize { self.buf.len() }
+
+    /// Writes a placeholder for a value of type `T`, returning a handle to fill it later.
+    pub fn reserve_patch<T>(&mut self) -> PatchHandle<T>
+    where T: ToWriter + Default {
+        let offset = self.buf.len();
+        self.buf.resize(offset + T::default().write_size(), 0);
+        PatchHandle { offset, _marker: PhantomData }
+    }
+
+    /// Writes `value` at the position reserved by `handle`.
+    pub fn fill_patch<T>(
+        &mut self,
+        handle: PatchHandle<T>,
+        value: &T,
+        e: Endian,
+    ) -> io::Result<()>
+    where
+        T: ToWriter,
+    {
+        value.to_writer_at(&mut self.buf, handle.offset, e)
+    }
+
+    pub fn into_inner(self) -> Vec<u8> { self.buf }
+}
+
+impl Write for PatchWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.buf.write(buf) }
+
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
 pub fn write_vec<T, W>(writer: &mut W, vec: &[T], e: Endian) -> io::Result<()>
 where
     T: ToWriter,
@@ -273,3 +407,93 @@ where
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum TestKind {
+        A,
+        B,
+    }
+
+    impl TryFrom<u16> for TestKind {
+        type Error = ();
+
+        fn try_from(value: u16) -> Result<Self, Self::Error> {
+            match value {
+                1 => Ok(TestKind::A),
+                2 => Ok(TestKind::B),
+                _ => Err(()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_read_enum() {
+        let mut reader = Cursor::new([0u8, 2, 0, 7]);
+        let kind: TestKind = read_enum::<_, u16, _>(&mut reader, Endian::Big).unwrap();
+        assert_eq!(kind, TestKind::B);
+        let err = read_enum::<TestKind, u16, _>(&mut reader, Endian::Big).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains("0x7"), "{err}");
+    }
+
+    #[test]
+    fn test_read_array_into() {
+        let data = (0..=255u8).collect::<Vec<u8>>();
+        for e in [Endian::Big, Endian::Little] {
+            let expected: Vec<u32> = read_vec(&mut Cursor::new(&data), 64, e).unwrap();
+            let mut out = [0u32; 64];
+            read_array_into(&mut Cursor::new(&data), &mut out, e).unwrap();
+            assert_eq!(out.as_slice(), expected.as_slice());
+
+            let expected: Vec<i16> = read_vec(&mut Cursor::new(&data), 128, e).unwrap();
+            let mut out = vec![0i16; 128];
+            read_array_into(&mut Cursor::new(&data), &mut out, e).unwrap();
+            assert_eq!(out, expected);
+        }
+        let mut out = [0u64; 33];
+        assert!(read_array_into(&mut Cursor::new(&data), &mut out, Endian::Big).is_err());
+    }
+
+    #[test]
+    fn test_from_reader_array() {
+        let data = (0..=255u8).collect::<Vec<u8>>();
+        for e in [Endian::Big, Endian::Little] {
+            let expected: Vec<u16> = read_vec(&mut Cursor::new(&data), 128, e).unwrap();
+            let out = <[u16; 128]>::from_reader(&mut Cursor::new(&data), e).unwrap();
+            assert_eq!(out.as_slice(), expected.as_slice());
+
+            let expected: Vec<i16> = read_vec(&mut Cursor::new(&data), 128, e).unwrap();
+            let out = <[i16; 128]>::from_reader(&mut Cursor::new(&data), e).unwrap();
+            assert_eq!(out.as_slice(), expected.as_slice());
+
+            let expected: Vec<i32> = read_vec(&mut Cursor::new(&data), 64, e).unwrap();
+            let out = <[i32; 64]>::from_reader(&mut Cursor::new(&data), e).unwrap();
+            assert_eq!(out.as_slice(), expected.as_slice());
+        }
+    }
+
+    #[test]
+    fn test_patch_writer() {
+        let mut writer = PatchWriter::new();
+        0xDEADBEEFu32.to_writer(&mut writer, Endian::Big).unwrap();
+        let table_offset = writer.reserve_patch::<u32>();
+        let table_count = writer.reserve_patch::<u16>();
+        let table = [1u32, 2, 3];
+        let offset = writer.position() as u32;
+        write_vec(&mut writer, &table, Endian::Big).unwrap();
+        writer.fill_patch(table_offset, &offset, Endian::Big).unwrap();
+        writer.fill_patch(table_count, &(table.len() as u16), Endian::Big).unwrap();
+        assert_eq!(writer.into_inner(), vec![
+            0xDE, 0xAD, 0xBE, 0xEF, // magic
+            0x00, 0x00, 0x00, 0x0A, // table offset
+            0x00, 0x03, // table count
+            0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x03,
+        ]);
+    }
+}
@@ -10,14 +10,14 @@ use object::{elf, Object, ObjectSection, ObjectSymbol};
 use tracing::warn;
 
 use crate::{
-    array_ref_mut,
+    array_ref,
     obj::{
         ObjArchitecture, ObjInfo, ObjKind, ObjRelocKind, ObjSection, ObjSectionKind, ObjSymbol,
         ObjSymbolFlagSet, ObjSymbolFlags, ObjSymbolKind, SectionIndex,
     },
     util::{
         align_up,
-        reader::{struct_size, Endian, FromReader, ToWriter, DYNAMIC_SIZE},
+        reader::{struct_size, Endian, FromBytes, FromReader, ToWriter, DYNAMIC_SIZE},
         split::default_section_align,
         IntoCow,
     },
@@ -306,15 +306,20 @@ impl RelSectionHeader {
     pub fn exec(&self) -> bool { self.offset_and_flags & 1 != 0 }
 }
 
-#[derive(Copy, Clone, Debug)]
-struct RelRelocRaw {
-    offset: u16,
-    kind: u8,
-    section: u8,
-    addend: u32,
+/// Raw REL relocation table entry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RelRelocEntry {
+    /// Offset from the previous relocation in the same section.
+    pub offset: u16,
+    /// ELF relocation type, or one of the `R_DOLPHIN_*` types.
+    pub kind: u8,
+    /// Target section index, or the section to patch for `R_DOLPHIN_SECTION`.
+    pub section: u8,
+    /// Target offset within the section, or absolute address for module 0.
+    pub addend: u32,
 }
 
-impl FromReader for RelRelocRaw {
+impl FromReader for RelRelocEntry {
     type Args = ();
 
     const STATIC_SIZE: usize = struct_size([
@@ -335,7 +340,7 @@ impl FromReader for RelRelocRaw {
     }
 }
 
-impl ToWriter for RelRelocRaw {
+impl ToWriter for RelRelocEntry {
     fn to_writer<W>(&self, writer: &mut W, e: Endian) -> io::Result<()>
     where W: Write + ?Sized {
         self.offset.to_writer(writer, e)?;
@@ -370,8 +375,18 @@ where
     Ok(sections)
 }
 
-pub fn process_rel<R>(reader: &mut R, name: &str) -> Result<(RelHeader, ObjInfo)>
-where R: Read + Seek + ?Sized {
+/// Reads a REL module. With `load_address` set, the module is loaded as the runtime linker
+/// would: sections are placed at their file offset from the base (followed by BSS), symbols
+/// are rebased and internal relocations are applied to the section data. Otherwise, the
+/// module stays relocatable with section addresses at 0.
+pub fn process_rel<R>(
+    reader: &mut R,
+    name: &str,
+    load_address: Option<u32>,
+) -> Result<(RelHeader, ObjInfo)>
+where
+    R: Read + Seek + ?Sized,
+{
     let header = process_rel_header(reader)?;
     let mut sections = Vec::with_capacity(header.num_sections as usize);
     let mut text_section = None;
@@ -421,9 +436,12 @@ where R: Read + Seek + ?Sized {
             elf_index: idx as SectionIndex,
             relocations: Default::default(),
             virtual_address: None, // TODO option to set?
-            file_offset: offset as u64,
+            file_offset: (offset != 0).then_some(offset as u64),
             section_known,
+            alloc: true,
+            load: true,
             splits: Default::default(),
+            segment: None,
         });
     }
     ensure!(
@@ -495,12 +513,12 @@ where R: Read + Seek + ?Sized {
         let mut address = 0u32;
         let mut section = u8::MAX;
         loop {
-            let reloc = RelRelocRaw::from_reader(reader, Endian::Big)?;
+            let reloc = RelRelocEntry::from_reader(reader, Endian::Big)?;
             let kind = match reloc.kind as u32 {
                 elf::R_PPC_NONE => continue,
                 elf::R_PPC_ADDR32 | elf::R_PPC_UADDR32 => ObjRelocKind::Absolute,
-                // elf::R_PPC_ADDR24 => ObjRelocKind::PpcAddr24,
-                // elf::R_PPC_ADDR16 => ObjRelocKind::PpcAddr16,
+                elf::R_PPC_ADDR24 => ObjRelocKind::PpcAddr24,
+                elf::R_PPC_ADDR16 => ObjRelocKind::PpcAddr16,
                 elf::R_PPC_ADDR16_LO => ObjRelocKind::PpcAddr16Lo,
                 elf::R_PPC_ADDR16_HI => ObjRelocKind::PpcAddr16Hi,
                 elf::R_PPC_ADDR16_HA => ObjRelocKind::PpcAddr16Ha,
@@ -540,19 +558,124 @@ where R: Read + Seek + ?Sized {
         reader.seek(SeekFrom::Start(position))?;
     }
 
+    let kind = if let Some(load_address) = load_address {
+        load_module_sections(
+            &mut sections,
+            &mut symbols,
+            load_address,
+            header.bss_align.unwrap_or(8),
+        );
+        let mut external_relocations = Vec::with_capacity(unresolved_relocations.len());
+        for reloc in unresolved_relocations {
+            if reloc.module_id != header.module_id {
+                external_relocations.push(reloc);
+                continue;
+            }
+            let source = module_section_index(&sections, reloc.section as SectionIndex)?;
+            let target = module_section_index(&sections, reloc.target_section as SectionIndex)?;
+            let value = sections[target].address as u32 + reloc.addend;
+            let section = &mut sections[source];
+            let address = section.address as u32 + reloc.address;
+            relocate_module_data(section, address, reloc.kind, value, Endian::Big)?;
+        }
+        unresolved_relocations = external_relocations;
+        ObjKind::Executable
+    } else {
+        ObjKind::Relocatable
+    };
+
     log::debug!("Read REL ID {}", header.module_id);
-    let mut obj = ObjInfo::new(
-        ObjKind::Relocatable,
-        ObjArchitecture::PowerPc,
-        name.to_string(),
-        symbols,
-        sections,
-    );
+    let mut obj = ObjInfo::new(kind, ObjArchitecture::PowerPc, name.to_string(), symbols, sections);
     obj.module_id = header.module_id;
     obj.unresolved_relocations = unresolved_relocations;
     Ok((header, obj))
 }
 
+/// Places module sections at `load_address` as the runtime linker would: sections with file
+/// data at their file offset, followed by BSS sections. Symbol addresses are rebased to
+/// their section.
+pub fn load_module_sections(
+    sections: &mut [ObjSection],
+    symbols: &mut [ObjSymbol],
+    load_address: u32,
+    bss_align: u32,
+) {
+    let file_end = sections
+        .iter()
+        .filter(|s| s.kind != ObjSectionKind::Bss)
+        .map(|s| (s.file_offset.unwrap_or_default() + s.size) as u32)
+        .max()
+        .unwrap_or(0);
+    let mut bss_address = align_up(load_address + file_end, bss_align.max(1));
+    for section in sections.iter_mut() {
+        if section.kind == ObjSectionKind::Bss {
+            section.address = bss_address as u64;
+            bss_address += section.size as u32;
+        } else {
+            section.address =
+                (load_address + section.file_offset.unwrap_or_default() as u32) as u64;
+        }
+    }
+    for symbol in symbols {
+        if let Some(section) = symbol.section.and_then(|idx| sections.get(idx as usize)) {
+            symbol.address += section.address;
+        }
+    }
+}
+
+/// Finds the section loaded from the given module section index.
+pub fn module_section_index(sections: &[ObjSection], index: SectionIndex) -> Result<usize> {
+    sections
+        .iter()
+        .position(|section| section.elf_index == index)
+        .ok_or_else(|| anyhow!("Failed to locate module section {}", index))
+}
+
+/// Patches the word at `address` in a loaded module section with the relocated `value`.
+pub fn relocate_module_data(
+    section: &mut ObjSection,
+    address: u32,
+    kind: ObjRelocKind,
+    value: u32,
+    e: Endian,
+) -> Result<()> {
+    let offset = (address as u64 - section.address) as usize;
+    ensure!(
+        offset + 4 <= section.data.len(),
+        "Relocation @ {:#010X} outside of section {} data",
+        address,
+        section.name
+    );
+    let ins = u32::from_bytes(*array_ref!(section.data, offset, 4), e);
+    let ins = match kind {
+        ObjRelocKind::Absolute => value,
+        ObjRelocKind::PpcAddr16 | ObjRelocKind::PpcAddr16Lo => (ins & !0xFFFF) | (value & 0xFFFF),
+        ObjRelocKind::PpcAddr16Hi => (ins & !0xFFFF) | (value >> 16),
+        ObjRelocKind::PpcAddr16Ha => (ins & !0xFFFF) | (value.wrapping_add(0x8000) >> 16),
+        ObjRelocKind::PpcRel24 => (ins & !0x3FFFFFC) | (value.wrapping_sub(address) & 0x3FFFFFC),
+        ObjRelocKind::PpcAddr24 => (ins & !0x3FFFFFC) | (value & 0x3FFFFFC),
+        ObjRelocKind::PpcRel14 => (ins & !0xFFFC) | (value.wrapping_sub(address) & 0xFFFC),
+        ObjRelocKind::PpcRel32 => value.wrapping_sub(address),
+        ObjRelocKind::PpcEmbSda21
+        | ObjRelocKind::PpcTprel16
+        | ObjRelocKind::PpcTprel16Lo
+        | ObjRelocKind::PpcTprel16Hi
+        | ObjRelocKind::PpcTprel16Ha
+        | ObjRelocKind::PpcDtprel16
+        | ObjRelocKind::PpcDtprel16Lo
+        | ObjRelocKind::PpcDtprel16Hi
+        | ObjRelocKind::PpcDtprel16Ha
+        | ObjRelocKind::PpcEmbNaddr32
+        | ObjRelocKind::PpcEmbNaddr16
+        | ObjRelocKind::PpcEmbSdai16
+        | ObjRelocKind::PpcEmbSda2i16 => {
+            bail!("Unsupported module relocation type {:?}", kind)
+        }
+    };
+    ins.to_writer_at(&mut section.data, offset, e)?;
+    Ok(())
+}
+
 pub fn print_relocations<R>(reader: &mut R, header: &RelHeader) -> Result<()>
 where R: Read + Seek + ?Sized {
     let imp_end = (header.imp_offset + header.imp_size) as u64;
@@ -566,14 +689,16 @@ where R: Read + Seek + ?Sized {
         let mut address = 0u32;
         let mut section = u8::MAX;
         loop {
-            let reloc = RelRelocRaw::from_reader(reader, Endian::Big)?;
+            let reloc = RelRelocEntry::from_reader(reader, Endian::Big)?;
             let kind = match reloc.kind as u32 {
                 elf::R_PPC_NONE => continue,
                 elf::R_PPC_ADDR32 | elf::R_PPC_UADDR32 => ObjRelocKind::Absolute,
+                elf::R_PPC_ADDR16 => ObjRelocKind::PpcAddr16,
                 elf::R_PPC_ADDR16_LO => ObjRelocKind::PpcAddr16Lo,
                 elf::R_PPC_ADDR16_HI => ObjRelocKind::PpcAddr16Hi,
                 elf::R_PPC_ADDR16_HA => ObjRelocKind::PpcAddr16Ha,
                 elf::R_PPC_REL24 => ObjRelocKind::PpcRel24,
+                elf::R_PPC_ADDR24 => ObjRelocKind::PpcAddr24,
                 elf::R_PPC_REL14 => ObjRelocKind::PpcRel14,
                 R_DOLPHIN_NOP => {
                     address += reloc.offset as u32;
@@ -639,6 +764,7 @@ fn apply_relocation(
     module_id: u32,
     rel_reloc: &RelReloc,
     header: &RelHeader,
+    e: Endian,
 ) -> Result<()> {
     let diff = if rel_reloc.module_id == module_id && rel_reloc.section == rel_reloc.target_section
     {
@@ -648,8 +774,8 @@ fn apply_relocation(
     } else {
         return Ok(());
     };
-    let ins_ref = array_ref_mut!(data, rel_reloc.address as usize, 4);
-    let mut ins = u32::from_be_bytes(*ins_ref);
+    let offset = rel_reloc.address as usize;
+    let mut ins = u32::from_bytes(*array_ref!(data, offset, 4), e);
     match rel_reloc.kind {
         ObjRelocKind::PpcRel24 => {
             ensure!((-0x2000000..0x2000000).contains(&diff), "R_PPC_REL24 relocation out of range");
@@ -661,7 +787,7 @@ fn apply_relocation(
         }
         kind => bail!("Unsupported relocation kind {:?}", kind),
     }
-    *ins_ref = ins.to_be_bytes();
+    ins.to_writer_at(data, offset, e)?;
     Ok(())
 }
 
@@ -862,79 +988,20 @@ where
         relocations: &[RelReloc],
         header: &mut RelHeader,
         imp_entries: &mut Vec<RelImport>,
-        raw_relocations: &mut Vec<RelRelocRaw>,
+        raw_relocations: &mut Vec<RelRelocEntry>,
         offset: &mut u32,
     ) -> Result<()> {
-        let mut address = 0u32;
-        let mut section = u8::MAX;
-        let mut last_module_id = u32::MAX;
-        for reloc in relocations {
-            if reloc.module_id != last_module_id {
-                if last_module_id != u32::MAX {
-                    raw_relocations.push(RelRelocRaw {
-                        offset: 0,
-                        kind: R_DOLPHIN_END as u8,
-                        section: 0,
-                        addend: 0,
-                    });
-                    *offset += 8;
-                }
-                imp_entries.push(RelImport { module_id: reloc.module_id, offset: *offset });
-                section = u8::MAX;
-                last_module_id = reloc.module_id;
-            }
+        for (module_id, entries) in encode_relocations(relocations)? {
+            imp_entries.push(RelImport { module_id, offset: *offset });
             if header.version >= 3
                 && header.fix_size.is_none()
-                && (reloc.module_id == 0 || reloc.module_id == header.module_id)
+                && (module_id == 0 || module_id == header.module_id)
             {
                 header.fix_size = Some(*offset);
             }
-            if reloc.section != section {
-                raw_relocations.push(RelRelocRaw {
-                    offset: 0,
-                    kind: R_DOLPHIN_SECTION as u8,
-                    section: reloc.section,
-                    addend: 0,
-                });
-                *offset += 8;
-                address = 0;
-                section = reloc.section;
-            }
-            let mut reloc_offset = reloc.address - address;
-            while reloc_offset > 0xffff {
-                raw_relocations.push(RelRelocRaw {
-                    offset: 0xffff,
-                    kind: R_DOLPHIN_NOP as u8,
-                    section: 0,
-                    addend: 0,
-                });
-                *offset += 8;
-                reloc_offset -= 0xffff;
-            }
-            raw_relocations.push(RelRelocRaw {
-                offset: reloc_offset as u16,
-                kind: match reloc.kind {
-                    ObjRelocKind::Absolute => elf::R_PPC_ADDR32,
-                    ObjRelocKind::PpcAddr16Lo => elf::R_PPC_ADDR16_LO,
-                    ObjRelocKind::PpcAddr16Hi => elf::R_PPC_ADDR16_HI,
-                    ObjRelocKind::PpcAddr16Ha => elf::R_PPC_ADDR16_HA,
-                    ObjRelocKind::PpcRel24 => elf::R_PPC_REL24,
-                    ObjRelocKind::PpcRel14 => elf::R_PPC_REL14,
-                    _ => bail!("Unsupported relocation kind {:?}", reloc.kind),
-                } as u8,
-                section: reloc.target_section,
-                addend: reloc.addend,
-            });
-            address = reloc.address;
-            *offset += 8;
+            *offset += entries.len() as u32 * RelRelocEntry::STATIC_SIZE as u32;
+            raw_relocations.extend(entries);
         }
-        raw_relocations.push(RelRelocRaw {
-            offset: 0,
-            kind: R_DOLPHIN_END as u8,
-            section: 0,
-            addend: 0,
-        });
-        *offset += 8;
         Ok(())
     }
 
@@ -1043,7 +1110,13 @@ where
         if apply_relocations.iter().any(|r| r.original_section == section_index) {
             let mut data = section_data.into_owned();
             for reloc in apply_relocations.iter().filter(|r| r.original_section == section_index) {
-                apply_relocation(&mut data, info.module_id, reloc, &header)?;
+                apply_relocation(
+                    &mut data,
+                    info.module_id,
+                    reloc,
+                    &header,
+                    file.endianness().into(),
+                )?;
             }
             section_data = data.into_cow();
         }
@@ -1078,12 +1151,112 @@ where
     Ok(())
 }
 
+/// Encodes relocations, sorted by module ID, section and address, into REL relocation
+/// entries. Returns the entries for each target module, each terminated by
+/// `R_DOLPHIN_END`.
+fn encode_relocations(relocations: &[RelReloc]) -> Result<Vec<(u32, Vec<RelRelocEntry>)>> {
+    let mut result = Vec::<(u32, Vec<RelRelocEntry>)>::new();
+    let mut address = 0u32;
+    let mut section = u8::MAX;
+    for reloc in relocations {
+        if !matches!(result.last(), Some((module_id, _)) if *module_id == reloc.module_id) {
+            result.push((reloc.module_id, vec![]));
+            section = u8::MAX;
+        }
+        let entries = &mut result.last_mut().unwrap().1;
+        if reloc.section != section {
+            entries.push(RelRelocEntry {
+                offset: 0,
+                kind: R_DOLPHIN_SECTION as u8,
+                section: reloc.section,
+                addend: 0,
+            });
+            address = 0;
+            section = reloc.section;
+        }
+        let mut reloc_offset = reloc.address - address;
+        while reloc_offset > 0xffff {
+            entries.push(RelRelocEntry {
+                offset: 0xffff,
+                kind: R_DOLPHIN_NOP as u8,
+                section: 0,
+                addend: 0,
+            });
+            reloc_offset -= 0xffff;
+        }
+        entries.push(RelRelocEntry {
+            offset: reloc_offset as u16,
+            kind: match reloc.kind {
+                ObjRelocKind::Absolute => elf::R_PPC_ADDR32,
+                ObjRelocKind::PpcAddr16 => elf::R_PPC_ADDR16,
+                ObjRelocKind::PpcAddr16Lo => elf::R_PPC_ADDR16_LO,
+                ObjRelocKind::PpcAddr16Hi => elf::R_PPC_ADDR16_HI,
+                ObjRelocKind::PpcAddr16Ha => elf::R_PPC_ADDR16_HA,
+                ObjRelocKind::PpcRel24 => elf::R_PPC_REL24,
+                ObjRelocKind::PpcAddr24 => elf::R_PPC_ADDR24,
+                ObjRelocKind::PpcRel14 => elf::R_PPC_REL14,
+                _ => bail!("Unsupported relocation kind {:?}", reloc.kind),
+            } as u8,
+            section: reloc.target_section,
+            addend: reloc.addend,
+        });
+        address = reloc.address;
+    }
+    for (_, entries) in &mut result {
+        entries.push(RelRelocEntry { offset: 0, kind: R_DOLPHIN_END as u8, section: 0, addend: 0 });
+    }
+    Ok(result)
+}
+
+/// Builds the REL relocation table for a module object, in version 1 and 2 order (by module
+/// ID, then section and address). Relocations against symbols defined in the module are
+/// relative to the target section; relocations against undefined symbols are resolved by
+/// name in `dol` and use absolute addresses. Sections are identified by their ELF index.
+pub fn build_relocation_table(obj: &ObjInfo, dol: &ObjInfo) -> Result<Vec<RelRelocEntry>> {
+    let mut relocations = vec![];
+    for (_, section) in obj.sections.iter() {
+        for (address, reloc) in section.relocations.iter() {
+            let symbol = &obj.symbols[reloc.target_symbol];
+            let (module_id, target_section, addend) = if let Some(index) = symbol.section {
+                let target_section = &obj.sections[index];
+                let offset = symbol.address as i64 - target_section.address as i64;
+                (obj.module_id, target_section.elf_index, offset + reloc.addend)
+            } else {
+                let (_, dol_symbol) = dol
+                    .symbols
+                    .by_name(&symbol.name)?
+                    .filter(|(_, s)| s.section.is_some())
+                    .ok_or_else(|| anyhow!("Symbol {} not found in DOL", symbol.name))?;
+                (0, 0, dol_symbol.address as i64 + reloc.addend)
+            };
+            relocations.push(RelReloc {
+                kind: reloc.kind,
+                section: section.elf_index.try_into()?,
+                address: address - section.address as u32,
+                module_id,
+                target_section: target_section.try_into()?,
+                addend: addend as u32,
+                original_section: section.elf_index as u8,
+                original_target_section: target_section as u8,
+            });
+        }
+    }
+    relocations.sort_by(|a, b| {
+        a.module_id
+            .cmp(&b.module_id)
+            .then(a.section.cmp(&b.section))
+            .then(a.address.cmp(&b.address))
+    });
+    Ok(encode_relocations(&relocations)?.into_iter().flat_map(|(_, entries)| entries).collect())
+}
+
 /// Determines REL section alignment based on its file offset.
 pub fn update_rel_section_alignment(obj: &mut ObjInfo, header: &RelHeader) -> Result<()> {
     let mut last_offset = header.section_info_offset + header.num_sections * 8;
     for (_, section) in obj.sections.iter_mut() {
         let prev_offset = last_offset;
-        last_offset = (section.file_offset + section.size) as u32;
+        let file_offset = section.file_offset.unwrap_or_default() as u32;
+        last_offset = file_offset + section.size as u32;
 
         if section.align > 0 {
             // Already set
@@ -1093,7 +1266,7 @@ pub fn update_rel_section_alignment(obj: &mut ObjInfo, header: &RelHeader) -> Re
         if section.section_known {
             // Try the default section alignment for known sections
             let default_align = default_section_align(section);
-            if align_up(prev_offset, default_align as u32) == section.file_offset as u32 {
+            if align_up(prev_offset, default_align as u32) == file_offset {
                 section.align = default_align;
                 continue;
             }
@@ -1102,7 +1275,7 @@ pub fn update_rel_section_alignment(obj: &mut ObjInfo, header: &RelHeader) -> Re
         // Work our way down from the REL header alignment
         let mut align = header.align.unwrap_or(32);
         while align >= 4 {
-            if align_up(prev_offset, align) == section.file_offset as u32 {
+            if align_up(prev_offset, align) == file_offset {
                 section.align = align as u64;
                 break;
             }
@@ -1114,9 +1287,131 @@ pub fn update_rel_section_alignment(obj: &mut ObjInfo, header: &RelHeader) -> Re
                 "Failed to determine alignment for REL section {}: {:#X} -> {:#X}",
                 section.name,
                 prev_offset,
-                section.file_offset
+                file_offset
             );
         }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::obj::{
+        testing::{test_section, test_symbol},
+        ObjReloc,
+    };
+
+    #[test]
+    fn test_build_relocation_table() {
+        let symbol = |name, section, address| ObjSymbol {
+            kind: ObjSymbolKind::Object,
+            section,
+            ..test_symbol(name, 0, address, 4)
+        };
+        let section = |name, address, size, elf_index| ObjSection {
+            elf_index,
+            ..test_section(name, ObjSectionKind::Data, address, size)
+        };
+        let dol = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "main.dol".to_string(),
+            vec![symbol("dol_var", Some(0), 0x80400000)],
+            vec![section(".data", 0x80400000, 4, 1)],
+        );
+        let mut obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "module.o".to_string(),
+            vec![symbol("dol_var", None, 0)],
+            vec![section(".data", 0, 0x20000, 2)],
+        );
+        obj.module_id = 1;
+        for address in [0x10, 0x18010] {
+            obj.sections[0]
+                .relocations
+                .insert(address, ObjReloc {
+                    kind: ObjRelocKind::Absolute,
+                    target_symbol: 0,
+                    addend: 4,
+                    module: None,
+                })
+                .unwrap();
+        }
+
+        let entries = build_relocation_table(&obj, &dol).unwrap();
+        let addr32 = elf::R_PPC_ADDR32 as u8;
+        assert_eq!(entries, vec![
+            RelRelocEntry { offset: 0, kind: R_DOLPHIN_SECTION as u8, section: 2, addend: 0 },
+            RelRelocEntry { offset: 0x10, kind: addr32, section: 0, addend: 0x80400004 },
+            RelRelocEntry { offset: 0xFFFF, kind: R_DOLPHIN_NOP as u8, section: 0, addend: 0 },
+            RelRelocEntry { offset: 0x8001, kind: addr32, section: 0, addend: 0x80400004 },
+            RelRelocEntry { offset: 0, kind: R_DOLPHIN_END as u8, section: 0, addend: 0 },
+        ]);
+
+        obj.symbols.replace(0, symbol("missing", None, 0)).unwrap();
+        assert!(build_relocation_table(&obj, &dol).is_err());
+    }
+
+    #[test]
+    fn test_process_rel_load_address() {
+        let header = RelHeader {
+            module_id: 1,
+            num_sections: 3,
+            section_info_offset: 0x40,
+            name_offset: 0,
+            name_size: 0,
+            version: 1,
+            bss_size: 0,
+            rel_offset: 0x78,
+            imp_offset: 0x70,
+            imp_size: 8,
+            prolog_section: 0,
+            epilog_section: 0,
+            unresolved_section: 0,
+            prolog_offset: 0,
+            epilog_offset: 0,
+            unresolved_offset: 0,
+            align: None,
+            bss_align: None,
+            fix_size: None,
+        };
+        let mut data = vec![];
+        header.to_writer(&mut data, Endian::Big).unwrap();
+        for section in [
+            RelSectionHeader::new(0, 0, false),
+            RelSectionHeader::new(0x60, 8, true),
+            RelSectionHeader::new(0x68, 4, false),
+        ] {
+            section.to_writer(&mut data, Endian::Big).unwrap();
+        }
+        data.resize(0x60, 0);
+        // .text: blr; nop
+        data.extend_from_slice(&[0x4E, 0x80, 0x00, 0x20, 0x60, 0x00, 0x00, 0x00]);
+        // .data: pointer to .text+4
+        data.extend_from_slice(&[0; 4]);
+        RelImport { module_id: 1, offset: 0x78 }.to_writer(&mut data, Endian::Big).unwrap();
+        for reloc in [
+            RelRelocEntry { offset: 0, kind: R_DOLPHIN_SECTION as u8, section: 2, addend: 0 },
+            RelRelocEntry { offset: 0, kind: elf::R_PPC_ADDR32 as u8, section: 1, addend: 4 },
+            RelRelocEntry { offset: 0, kind: R_DOLPHIN_END as u8, section: 0, addend: 0 },
+        ] {
+            reloc.to_writer(&mut data, Endian::Big).unwrap();
+        }
+
+        let (_, obj) = process_rel(&mut Cursor::new(&data), "test", None).unwrap();
+        assert_eq!(obj.kind, ObjKind::Relocatable);
+        assert_eq!(obj.sections[1].address, 0);
+        assert_eq!(obj.unresolved_relocations.len(), 1);
+
+        let (_, obj) = process_rel(&mut Cursor::new(&data), "test", Some(0x80800000)).unwrap();
+        assert_eq!(obj.kind, ObjKind::Executable);
+        assert_eq!(obj.sections[0].address, 0x80800060);
+        assert_eq!(obj.sections[1].address, 0x80800068);
+        assert_eq!(obj.sections[1].data, 0x80800064u32.to_be_bytes());
+        assert!(obj.unresolved_relocations.is_empty());
+    }
+}
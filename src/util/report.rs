@@ -0,0 +1,337 @@
+//! Progress reports in the objdiff report JSON format, for decomp dashboards.
+use anyhow::{anyhow, Result};
+use indexmap::IndexMap;
+use objdiff_core::{
+    diff::{code::diff_code, DiffObjConfig},
+    obj::SymbolRef,
+};
+use serde::Serialize;
+
+use crate::{
+    obj::{ObjInfo, ObjRelocKind, ObjSection, ObjSectionKind, ObjSymbol, ObjSymbolKind},
+    util::diff::process_code,
+};
+
+/// Version of the objdiff report format written by [`generate_report`].
+pub const REPORT_VERSION: u32 = 1;
+
+/// Progress measures, matching objdiff's `Measures`.
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct ReportMeasures {
+    pub fuzzy_match_percent: f32,
+    pub total_code: u64,
+    pub matched_code: u64,
+    pub matched_code_percent: f32,
+    pub total_data: u64,
+    pub matched_data: u64,
+    pub matched_data_percent: f32,
+    pub total_functions: u32,
+    pub matched_functions: u32,
+    pub matched_functions_percent: f32,
+    pub complete_code: u64,
+    pub complete_code_percent: f32,
+    pub complete_data: u64,
+    pub complete_data_percent: f32,
+    pub total_units: u32,
+    pub complete_units: u32,
+}
+
+/// A function or data symbol, matching objdiff's `ReportItem`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReportItem {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub demangled_name: Option<String>,
+    pub address: u64,
+    pub size: u64,
+    pub fuzzy_match_percent: f32,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReportUnit {
+    pub name: String,
+    pub measures: ReportMeasures,
+    pub functions: Vec<ReportItem>,
+}
+
+/// The top-level objdiff report.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReportJson {
+    pub version: u32,
+    pub measures: ReportMeasures,
+    pub units: Vec<ReportUnit>,
+}
+
+#[derive(Default)]
+struct UnitProgress {
+    measures: ReportMeasures,
+    functions: Vec<ReportItem>,
+    /// Sum of function sizes weighted by their match percent.
+    fuzzy_code: f64,
+}
+
+/// Generates a progress report for each unit split in `target`, comparing its symbols
+/// against the symbols of the same name in `obj`. Functions are diffed with objdiff-core,
+/// and are matched at 100%. Data symbols are matched when their bytes are identical
+/// outside of relocated fields, and their relocations have the same kind, target symbol
+/// name and addend. A unit is complete when all of its code and data is matched.
+pub fn generate_report(obj: &ObjInfo, target: &ObjInfo) -> Result<ReportJson> {
+    let config = DiffObjConfig::default();
+    let mut units = IndexMap::<String, UnitProgress>::new();
+    for (section_index, section, address, split) in target.sections.all_splits() {
+        let unit = units.entry(split.unit.clone()).or_default();
+        let end = if split.end == 0 { (section.address + section.size) as u32 } else { split.end };
+        for (_, symbol) in target.symbols.for_section_range(section_index, address..end) {
+            if symbol.size == 0
+                || !matches!(symbol.kind, ObjSymbolKind::Function | ObjSymbolKind::Object)
+            {
+                continue;
+            }
+            let measures = &mut unit.measures;
+            let obj_symbols = obj
+                .symbols
+                .for_name(&symbol.name)
+                .filter(|(_, s)| s.section.is_some())
+                .map(|(_, s)| s)
+                .collect::<Vec<_>>();
+            if section.kind == ObjSectionKind::Code {
+                let mut percent = 0.0f32;
+                for obj_symbol in obj_symbols {
+                    percent = percent
+                        .max(function_match_percent(obj, obj_symbol, target, symbol, &config)?);
+                }
+                let matched = percent == 100.0;
+                measures.total_code += symbol.size;
+                measures.matched_code += if matched { symbol.size } else { 0 };
+                measures.total_functions += 1;
+                measures.matched_functions += matched as u32;
+                unit.fuzzy_code += symbol.size as f64 * percent as f64;
+                unit.functions.push(ReportItem {
+                    name: symbol.name.clone(),
+                    demangled_name: symbol.demangled_name.clone(),
+                    address: symbol.address - section.address,
+                    size: symbol.size,
+                    fuzzy_match_percent: percent,
+                });
+            } else {
+                let mut matched = false;
+                for obj_symbol in obj_symbols {
+                    if symbols_match(obj, obj_symbol, target, symbol)? {
+                        matched = true;
+                        break;
+                    }
+                }
+                measures.total_data += symbol.size;
+                measures.matched_data += if matched { symbol.size } else { 0 };
+            }
+        }
+    }
+
+    let mut total = ReportMeasures::default();
+    let mut total_fuzzy_code = 0.0f64;
+    let units = units
+        .into_iter()
+        .map(|(name, unit)| {
+            let mut measures = unit.measures;
+            measures.total_units = 1;
+            measures.complete_units = (measures.matched_code == measures.total_code
+                && measures.matched_data == measures.total_data)
+                as u32;
+            if measures.complete_units != 0 {
+                measures.complete_code = measures.total_code;
+                measures.complete_data = measures.total_data;
+            }
+            total.total_code += measures.total_code;
+            total.matched_code += measures.matched_code;
+            total.total_data += measures.total_data;
+            total.matched_data += measures.matched_data;
+            total.total_functions += measures.total_functions;
+            total.matched_functions += measures.matched_functions;
+            total.complete_code += measures.complete_code;
+            total.complete_data += measures.complete_data;
+            total.total_units += 1;
+            total.complete_units += measures.complete_units;
+            total_fuzzy_code += unit.fuzzy_code;
+            update_percents(&mut measures, unit.fuzzy_code);
+            ReportUnit { name, measures, functions: unit.functions }
+        })
+        .collect();
+    update_percents(&mut total, total_fuzzy_code);
+    Ok(ReportJson { version: REPORT_VERSION, measures: total, units })
+}
+
+fn update_percents(measures: &mut ReportMeasures, fuzzy_code: f64) {
+    let percent = |matched: u64, total: u64| {
+        if total == 0 {
+            100.0
+        } else {
+            matched as f32 / total as f32 * 100.0
+        }
+    };
+    measures.matched_code_percent = percent(measures.matched_code, measures.total_code);
+    measures.matched_data_percent = percent(measures.matched_data, measures.total_data);
+    measures.matched_functions_percent =
+        percent(measures.matched_functions as u64, measures.total_functions as u64);
+    measures.complete_code_percent = percent(measures.complete_code, measures.total_code);
+    measures.complete_data_percent = percent(measures.complete_data, measures.total_data);
+    measures.fuzzy_match_percent = if measures.total_code == 0 {
+        100.0
+    } else {
+        (fuzzy_code / measures.total_code as f64) as f32
+    };
+}
+
+/// Diffs a function against its counterpart using objdiff-core.
+fn function_match_percent(
+    left: &ObjInfo,
+    left_symbol: &ObjSymbol,
+    right: &ObjInfo,
+    right_symbol: &ObjSymbol,
+    config: &DiffObjConfig,
+) -> Result<f32> {
+    let left_code = process_code(left, left_symbol, code_section(left, left_symbol)?, config)?;
+    let right_code = process_code(right, right_symbol, code_section(right, right_symbol)?, config)?;
+    let (left_diff, _) =
+        diff_code(&left_code, &right_code, SymbolRef::default(), SymbolRef::default(), config)?;
+    Ok(left_diff.match_percent.unwrap_or(0.0))
+}
+
+fn code_section<'a>(obj: &'a ObjInfo, symbol: &ObjSymbol) -> Result<&'a ObjSection> {
+    symbol
+        .section
+        .map(|index| &obj.sections[index])
+        .filter(|section| section.kind == ObjSectionKind::Code)
+        .ok_or_else(|| anyhow!("Function {} is not in a code section", symbol.name))
+}
+
+fn symbols_match(
+    left: &ObjInfo,
+    left_symbol: &ObjSymbol,
+    right: &ObjInfo,
+    right_symbol: &ObjSymbol,
+) -> Result<bool> {
+    if left_symbol.size != right_symbol.size {
+        return Ok(false);
+    }
+    Ok(symbol_contents(left, left_symbol)? == symbol_contents(right, right_symbol)?)
+}
+
+type SymbolRelocation<'a> = (u32, ObjRelocKind, &'a str, i64);
+
+/// Symbol data with relocated fields zeroed, and relocations relative to the symbol start.
+fn symbol_contents<'a>(
+    obj: &'a ObjInfo,
+    symbol: &ObjSymbol,
+) -> Result<(Vec<u8>, Vec<SymbolRelocation<'a>>)> {
+    let section_index =
+        symbol.section.ok_or_else(|| anyhow!("Symbol {} has no section", symbol.name))?;
+    let section = &obj.sections[section_index];
+    let mut data =
+        section.data_or_zero(symbol.address - section.address, symbol.size)?.into_owned();
+    let start = symbol.address as u32;
+    let mut relocations = vec![];
+    for (address, reloc) in section.relocations.range(start..start + symbol.size as u32) {
+        let offset = address - start;
+        let field = offset as usize & !3;
+        let field_end = (field + 4).min(data.len());
+        data[field..field_end].fill(0);
+        let target = obj.symbols[reloc.target_symbol].name.as_str();
+        relocations.push((offset, reloc.kind, target, reloc.addend));
+    }
+    Ok((data, relocations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{
+        testing::{test_section_words, test_symbol},
+        ObjArchitecture, ObjKind, ObjSplit,
+    };
+
+    fn split(unit: &str, end: u32) -> ObjSplit {
+        ObjSplit {
+            unit: unit.to_string(),
+            end,
+            align: None,
+            common: false,
+            autogenerated: false,
+            skip: false,
+            rename: None,
+        }
+    }
+
+    #[test]
+    fn test_generate_report() {
+        let mut text = test_section_words(".text", ObjSectionKind::Code, 0x80003100, &[
+            0x38600000, 0x4E800020, 0x38600001, 0x4E800020,
+        ]);
+        text.splits.push(0x80003100, split("a.c", 0x80003110));
+        let mut data =
+            test_section_words(".data", ObjSectionKind::Data, 0x80004000, &[0x01020304, 0]);
+        data.splits.push(0x80004000, split("a.c", 0x80004004));
+        data.splits.push(0x80004004, split("b.c", 0x80004008));
+        let target = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "main.dol".to_string(),
+            vec![
+                test_symbol("foo", 0, 0x80003100, 8),
+                test_symbol("bar", 0, 0x80003108, 8),
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("baz", 1, 0x80004000, 4) },
+                ObjSymbol { kind: ObjSymbolKind::Object, ..test_symbol("qux", 1, 0x80004004, 4) },
+            ],
+            vec![text, data],
+        );
+        let obj = ObjInfo::new(
+            ObjKind::Relocatable,
+            ObjArchitecture::PowerPc,
+            "a.o".to_string(),
+            vec![test_symbol("foo", 0, 0, 8), test_symbol("bar", 0, 8, 8), ObjSymbol {
+                kind: ObjSymbolKind::Object,
+                ..test_symbol("baz", 1, 0, 4)
+            }],
+            vec![
+                test_section_words(".text", ObjSectionKind::Code, 0, &[
+                    0x38600000, 0x4E800020, 0x38600002, 0x4E800020,
+                ]),
+                test_section_words(".data", ObjSectionKind::Data, 0, &[0x01020304]),
+            ],
+        );
+
+        let report = generate_report(&obj, &target).unwrap();
+        assert_eq!(report.units.len(), 2);
+        let a = &report.units[0];
+        assert_eq!(a.name, "a.c");
+        assert_eq!((a.measures.matched_code, a.measures.total_code), (8, 16));
+        assert_eq!(a.measures.matched_code_percent, 50.0);
+        assert_eq!(a.measures.matched_functions_percent, 50.0);
+        assert_eq!(a.measures.matched_data_percent, 100.0);
+        let b = &report.units[1];
+        assert_eq!(b.name, "b.c");
+        assert_eq!(b.measures.matched_code_percent, 100.0);
+        assert_eq!(b.measures.matched_data_percent, 0.0);
+        assert_eq!(b.measures.complete_code, 0);
+
+        // bar differs by one instruction
+        let functions = a
+            .functions
+            .iter()
+            .map(|f| (f.name.as_str(), f.address, f.fuzzy_match_percent))
+            .collect::<Vec<_>>();
+        assert_eq!(functions[0], ("foo", 0, 100.0));
+        assert_eq!((functions[1].0, functions[1].1), ("bar", 8));
+        assert!(functions[1].2 > 0.0 && functions[1].2 < 100.0);
+        let fuzzy = a.measures.fuzzy_match_percent;
+        assert!(fuzzy > a.measures.matched_code_percent && fuzzy < 100.0);
+
+        assert_eq!(report.measures.matched_code_percent, 50.0);
+        assert_eq!(report.measures.matched_data_percent, 50.0);
+        assert_eq!(report.measures.fuzzy_match_percent, fuzzy);
+        assert_eq!((report.measures.complete_units, report.measures.total_units), (0, 2));
+        assert_eq!(report.measures.complete_code_percent, 0.0);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["version"], REPORT_VERSION);
+        assert_eq!(json["units"][0]["functions"][0]["fuzzy_match_percent"], 100.0);
+    }
+}
@@ -3,17 +3,19 @@ use std::{
     io::{Read, Seek, SeekFrom, Write},
 };
 
-use anyhow::{anyhow, ensure, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use cwdemangle::{demangle, DemangleOptions};
+use object::elf;
 
 use crate::{
     obj::{
-        ObjArchitecture, ObjInfo, ObjKind, ObjSection, ObjSectionKind, ObjSymbol, ObjSymbolFlagSet,
-        ObjSymbolFlags, ObjSymbolKind, SectionIndex,
+        ObjArchitecture, ObjInfo, ObjKind, ObjRelocKind, ObjSection, ObjSectionKind, ObjSymbol,
+        ObjSymbolFlagSet, ObjSymbolFlags, ObjSymbolKind, SectionIndex,
     },
     util::{
         file::{read_c_string, read_string},
         reader::{struct_size, Endian, FromReader, ToWriter, DYNAMIC_SIZE},
+        rel::{load_module_sections, module_section_index, relocate_module_data},
     },
 };
 
@@ -412,7 +414,11 @@ impl ToWriter for RsoSymbol {
     fn write_size(&self) -> usize { Self::STATIC_SIZE }
 }
 
-pub fn process_rso<R>(reader: &mut R) -> Result<ObjInfo>
+/// Reads an RSO module. With `load_address` set, the module is loaded as the runtime linker
+/// would: sections are placed at their file offset from the base (followed by BSS), symbols
+/// are rebased and internal relocations are applied to the section data. Otherwise, the
+/// module stays relocatable with section addresses at 0.
+pub fn process_rso<R>(reader: &mut R, load_address: Option<u32>) -> Result<ObjInfo>
 where R: Read + Seek + ?Sized {
     let header = RsoHeader::from_reader(reader, Endian::Big)?;
     let mut sections = Vec::with_capacity(header.num_sections as usize);
@@ -455,9 +461,12 @@ where R: Read + Seek + ?Sized {
             elf_index: idx as SectionIndex,
             relocations: Default::default(),
             virtual_address: None, // TODO option to set?
-            file_offset: offset as u64,
+            file_offset: (offset != 0).then_some(offset as u64),
             section_known: false,
+            alloc: true,
+            load: true,
             splits: Default::default(),
+            segment: None,
         });
         if offset == 0 {
             total_bss_size += size;
@@ -564,7 +573,47 @@ where R: Read + Seek + ?Sized {
         _ => read_string(reader, header.name_offset as u64, header.name_size as usize)?,
     };
 
-    let obj = ObjInfo::new(ObjKind::Relocatable, ObjArchitecture::PowerPc, name, symbols, sections);
+    let kind = if let Some(load_address) = load_address {
+        load_module_sections(&mut sections, &mut symbols, load_address, 8);
+        reader.seek(SeekFrom::Start(header.internal_rel_offset as u64))?;
+        while reader.stream_position()?
+            < (header.internal_rel_offset + header.internal_rel_size) as u64
+        {
+            let reloc = RsoRelocation::from_reader(reader, Endian::Big)?;
+            let kind = match reloc.rel_type() as u32 {
+                elf::R_PPC_NONE => continue,
+                elf::R_PPC_ADDR32 | elf::R_PPC_UADDR32 => ObjRelocKind::Absolute,
+                elf::R_PPC_ADDR16 => ObjRelocKind::PpcAddr16,
+                elf::R_PPC_ADDR16_LO => ObjRelocKind::PpcAddr16Lo,
+                elf::R_PPC_ADDR16_HI => ObjRelocKind::PpcAddr16Hi,
+                elf::R_PPC_ADDR16_HA => ObjRelocKind::PpcAddr16Ha,
+                elf::R_PPC_REL24 => ObjRelocKind::PpcRel24,
+                elf::R_PPC_ADDR24 => ObjRelocKind::PpcAddr24,
+                elf::R_PPC_REL14 => ObjRelocKind::PpcRel14,
+                reloc_type => bail!("Unhandled RSO relocation type {reloc_type}"),
+            };
+            // Internal relocation offsets are relative to the start of the file
+            let source = sections
+                .iter()
+                .position(|s| {
+                    s.file_offset.is_some_and(|offset| {
+                        (offset..offset + s.size).contains(&(reloc.offset() as u64))
+                    })
+                })
+                .ok_or_else(|| {
+                    anyhow!("Failed to locate section for relocation @ {:#X}", reloc.offset())
+                })?;
+            let target = module_section_index(&sections, reloc.id() as SectionIndex)?;
+            let value = sections[target].address as u32 + reloc.sym_offset();
+            let address = (load_address + reloc.offset()) & !3;
+            relocate_module_data(&mut sections[source], address, kind, value, Endian::Big)?;
+        }
+        ObjKind::Executable
+    } else {
+        ObjKind::Relocatable
+    };
+
+    let obj = ObjInfo::new(kind, ObjArchitecture::PowerPc, name, symbols, sections);
     Ok(obj)
 }
 
@@ -15,8 +15,8 @@ use crate::{
     },
     array_ref,
     obj::{
-        ObjInfo, ObjKind, ObjReloc, ObjRelocKind, ObjSection, ObjSymbol, ObjSymbolFlagSet,
-        ObjSymbolKind, SectionIndex, SymbolIndex,
+        sda21_field_mask, ObjInfo, ObjKind, ObjReloc, ObjRelocKind, ObjSection, ObjSymbol,
+        ObjSymbolFlagSet, ObjSymbolKind, SectionIndex, SymbolIndex,
     },
     util::elf::process_elf,
 };
@@ -333,17 +333,31 @@ pub fn generate_signature(
                     btree_map::Entry::Occupied(e) => *e.get(),
                 };
                 match reloc.kind {
-                    ObjRelocKind::Absolute => {
+                    ObjRelocKind::Absolute
+                    | ObjRelocKind::PpcRel32
+                    | ObjRelocKind::PpcEmbNaddr32 => {
                         *ins = 0;
                         *pat = 0;
                     }
-                    ObjRelocKind::PpcAddr16Hi
+                    ObjRelocKind::PpcAddr16
+                    | ObjRelocKind::PpcAddr16Hi
                     | ObjRelocKind::PpcAddr16Ha
-                    | ObjRelocKind::PpcAddr16Lo => {
+                    | ObjRelocKind::PpcAddr16Lo
+                    | ObjRelocKind::PpcTprel16
+                    | ObjRelocKind::PpcTprel16Lo
+                    | ObjRelocKind::PpcTprel16Hi
+                    | ObjRelocKind::PpcTprel16Ha
+                    | ObjRelocKind::PpcDtprel16
+                    | ObjRelocKind::PpcDtprel16Lo
+                    | ObjRelocKind::PpcDtprel16Hi
+                    | ObjRelocKind::PpcDtprel16Ha
+                    | ObjRelocKind::PpcEmbNaddr16
+                    | ObjRelocKind::PpcEmbSdai16
+                    | ObjRelocKind::PpcEmbSda2i16 => {
                         *ins &= !0xFFFF;
                         *pat = !0xFFFF;
                     }
-                    ObjRelocKind::PpcRel24 => {
+                    ObjRelocKind::PpcRel24 | ObjRelocKind::PpcAddr24 => {
                         *ins &= !0x3FFFFFC;
                         *pat = !0x3FFFFFC;
                     }
@@ -352,8 +366,9 @@ pub fn generate_signature(
                         *pat = !0xFFFC;
                     }
                     ObjRelocKind::PpcEmbSda21 => {
-                        *ins &= !0x1FFFFF;
-                        *pat = !0x1FFFFF;
+                        let mask = sda21_field_mask(*ins);
+                        *ins &= !mask;
+                        *pat = !mask;
                     }
                 }
                 out_relocs.push(OutReloc {
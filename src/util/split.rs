@@ -139,12 +139,13 @@ fn split_extabindex(obj: &mut ObjInfo, start: SectionAddress) -> Result<()> {
     let section_end = eti_init_info.address as u32;
     while current_address.address < section_end {
         let function_addr = read_address(obj, section, current_address.address)?;
-        let function_size = read_u32(section, current_address.address + 4).with_context(|| {
-            format!(
-                "Failed to read extabindex entry function size @ {:#010X}",
-                current_address.address + 4
-            )
-        })?;
+        let function_size =
+            read_u32(obj, section, current_address.address + 4).with_context(|| {
+                format!(
+                    "Failed to read extabindex entry function size @ {:#010X}",
+                    current_address.address + 4
+                )
+            })?;
         let extab_addr = read_address(obj, section, current_address.address + 8)?;
         ensure!(
             extab_addr.section == extab_section_index,
@@ -929,38 +930,21 @@ fn resolve_link_order(obj: &ObjInfo) -> Result<Vec<ObjUnit>> {
     }
 }
 
-/// Split an object into multiple relocatable objects.
-#[instrument(level = "debug", skip(obj))]
-pub fn split_obj(obj: &ObjInfo, module_name: Option<&str>) -> Result<Vec<ObjInfo>> {
-    let mut objects: Vec<ObjInfo> = vec![];
-    let mut object_symbols: Vec<Vec<Option<SymbolIndex>>> = vec![];
-    let mut name_to_obj: HashMap<String, usize> = HashMap::new();
-    for unit in &obj.link_order {
-        name_to_obj.insert(unit.name.clone(), objects.len());
-        object_symbols.push(vec![None; obj.symbols.count() as usize]);
-        let mut split_obj = ObjInfo::new(
-            ObjKind::Relocatable,
-            ObjArchitecture::PowerPc,
-            unit.name.clone(),
-            vec![],
-            vec![],
-        );
-        if let Some(comment_version) = unit.comment_version {
-            if comment_version > 0 {
-                split_obj.mw_comment = Some(MWComment::new(comment_version)?);
-            }
-        } else {
-            split_obj.mw_comment.clone_from(&obj.mw_comment);
-        }
-        split_obj.split_meta = Some(SplitMeta {
-            generator: Some(format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))),
-            module_name: module_name.map(str::to_string),
-            module_id: Some(obj.module_id),
-            virtual_addresses: None,
-        });
-        objects.push(split_obj);
-    }
+/// A range of a section assigned to a unit by its split.
+struct SplitRange<'a> {
+    section_index: SectionIndex,
+    section: &'a ObjSection,
+    start: SectionAddress,
+    end: SectionAddress,
+    split: &'a ObjSplit,
+}
 
+/// Validates that the splits cover each section, and collects the ranges of each unit in
+/// link order. Skipped splits are omitted.
+fn collect_split_ranges(obj: &ObjInfo) -> Result<Vec<Vec<SplitRange>>> {
+    let name_to_obj: HashMap<&str, usize> =
+        obj.link_order.iter().enumerate().map(|(idx, unit)| (unit.name.as_str(), idx)).collect();
+    let mut ranges = obj.link_order.iter().map(|_| vec![]).collect_vec();
     for (section_index, section) in obj.sections.iter() {
         let mut current_address = SectionAddress::new(section_index, section.address as u32);
         let section_end = end_for_section(obj, section_index)?;
@@ -1006,267 +990,361 @@ pub fn split_obj(obj: &ObjInfo, module_name: Option<&str>) -> Result<Vec<ObjInfo
                 continue;
             }
 
-            let split_obj = name_to_obj
-                .get(&split.unit)
-                .and_then(|&idx| objects.get_mut(idx))
-                .ok_or_else(|| anyhow!("Unit '{}' not in link order", split.unit))?;
-            let symbol_idxs = name_to_obj
-                .get(&split.unit)
-                .and_then(|&idx| object_symbols.get_mut(idx))
-                .ok_or_else(|| anyhow!("Unit '{}' not in link order", split.unit))?;
-
-            // Calculate & verify section alignment
-            let mut align = split.align.unwrap_or_else(|| {
-                let default_align = default_section_align(section) as u32;
-                max(
-                    // Maximum alignment of any symbol in this split
-                    obj.symbols
-                        .for_section_range(
-                            section_index,
-                            current_address.address..split_end.address,
-                        )
-                        .filter(|&(_, s)| s.size_known && s.size > 0)
-                        .filter_map(|(_, s)| s.align)
-                        .max()
-                        .unwrap_or(default_align),
-                    default_align,
-                )
-            }) as u64;
+            name_to_obj
+                .get(split.unit.as_str())
+                .and_then(|&idx| ranges.get_mut(idx))
+                .ok_or_else(|| anyhow!("Unit '{}' not in link order", split.unit))?
+                .push(SplitRange {
+                    section_index,
+                    section,
+                    start: current_address,
+                    end: split_end,
+                    split,
+                });
+            current_address = next_addr;
+        }
+    }
+    Ok(ranges)
+}
 
-            if current_address & (align as u32 - 1) != 0 {
-                log::warn!(
-                    "Alignment for {} {} expected {}, but starts at {:#010X}",
-                    split.unit,
-                    section.name,
-                    align,
-                    current_address
-                );
-                while align > 4 {
-                    align /= 2;
-                    if current_address & (align as u32 - 1) == 0 {
-                        break;
-                    }
+/// Symbols defined within a split range, which are copied into the unit's object.
+fn split_range_symbols<'a>(
+    obj: &'a ObjInfo,
+    range: &SplitRange,
+) -> impl Iterator<Item = (SymbolIndex, &'a ObjSymbol)> {
+    let (section_index, start, end) = (range.section_index, range.start, range.end);
+    obj.symbols
+        .for_section_range(section_index, start.address..=end.address)
+        .filter(move |&(_, s)| {
+            s.section == Some(section_index) && !is_linker_generated_label(&s.name)
+        })
+        // TODO hack for gTRKInterruptVectorTableEnd
+        .filter(move |&(_, s)| {
+            !((s.address == end.address as u64 && s.name != "gTRKInterruptVectorTableEnd")
+                || (s.address == start.address as u64 && s.name == "gTRKInterruptVectorTableEnd"))
+        })
+}
+
+/// Local symbols referenced from a unit other than the one defining them. These are upgraded
+/// to global scope and renamed to avoid conflicts in every split object.
+fn globalized_symbols(
+    obj: &ObjInfo,
+    ranges: &[Vec<SplitRange>],
+) -> Result<HashMap<SymbolIndex, String>> {
+    let mut globalize_symbols = HashMap::new();
+    for unit_ranges in ranges {
+        let defined = unit_ranges
+            .iter()
+            .flat_map(|range| split_range_symbols(obj, range))
+            .map(|(symbol_idx, _)| symbol_idx)
+            .collect::<HashSet<_>>();
+        // Relocations in common splits are dropped along with the data
+        for range in unit_ranges.iter().filter(|range| !range.split.common) {
+            for (_, reloc) in
+                range.section.relocations.range(range.start.address..range.end.address)
+            {
+                let target_sym = &obj.symbols[reloc.target_symbol];
+                if defined.contains(&reloc.target_symbol)
+                    || !target_sym.flags.is_local()
+                    || globalize_symbols.contains_key(&reloc.target_symbol)
+                {
+                    continue;
                 }
+                let address_str = if obj.module_id == 0 {
+                    format!("{:08X}", target_sym.address)
+                } else if let Some(section_index) = target_sym.section {
+                    let target_section = &obj.sections[section_index];
+                    format!(
+                        "{}_{}_{:X}",
+                        obj.module_id,
+                        target_section.name.trim_start_matches('.'),
+                        target_sym.address
+                    )
+                } else {
+                    bail!("Local symbol {} has no section", target_sym.name);
+                };
+                let new_name = if target_sym.name.ends_with(&address_str) {
+                    target_sym.name.clone()
+                } else {
+                    format!("{}_{}", target_sym.name, address_str)
+                };
+                globalize_symbols.insert(reloc.target_symbol, new_name);
             }
-            ensure!(
-                current_address & (align as u32 - 1) == 0,
-                "Invalid alignment for split: {} {} {:#010X}",
+        }
+    }
+    Ok(globalize_symbols)
+}
+
+/// Builds the relocatable object for a single unit from its split ranges.
+fn split_unit(
+    obj: &ObjInfo,
+    unit: &ObjUnit,
+    ranges: &[SplitRange],
+    module_name: Option<&str>,
+    globalize_symbols: &HashMap<SymbolIndex, String>,
+) -> Result<ObjInfo> {
+    let mut symbol_idxs: Vec<Option<SymbolIndex>> = vec![None; obj.symbols.count() as usize];
+    let mut split_obj = ObjInfo::new(
+        ObjKind::Relocatable,
+        ObjArchitecture::PowerPc,
+        unit.name.clone(),
+        vec![],
+        vec![],
+    );
+    if let Some(comment_version) = unit.comment_version {
+        if comment_version > 0 {
+            split_obj.mw_comment = Some(MWComment::new(comment_version)?);
+        }
+    } else {
+        split_obj.mw_comment.clone_from(&obj.mw_comment);
+    }
+    split_obj.split_meta = Some(SplitMeta {
+        generator: Some(format!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"))),
+        module_name: module_name.map(str::to_string),
+        module_id: Some(obj.module_id),
+        virtual_addresses: None,
+    });
+
+    for range in ranges {
+        let SplitRange { section_index, section, start: current_address, end: split_end, split } =
+            *range;
+
+        // Calculate & verify section alignment
+        let mut align = split.align.unwrap_or_else(|| {
+            let default_align = default_section_align(section) as u32;
+            max(
+                // Maximum alignment of any symbol in this split
+                obj.symbols
+                    .for_section_range(section_index, current_address.address..split_end.address)
+                    .filter(|&(_, s)| s.size_known && s.size > 0)
+                    .filter_map(|(_, s)| s.align)
+                    .max()
+                    .unwrap_or(default_align),
+                default_align,
+            )
+        }) as u64;
+
+        if current_address & (align as u32 - 1) != 0 {
+            log::warn!(
+                "Alignment for {} {} expected {}, but starts at {:#010X}",
                 split.unit,
                 section.name,
+                align,
                 current_address
             );
-
-            // Collect relocations; target_symbol will be updated later
-            let out_relocations = section
-                .relocations
-                .range(current_address.address..split_end.address)
-                .map(|(addr, o)| {
-                    (addr - current_address.address, ObjReloc {
-                        kind: o.kind,
-                        target_symbol: o.target_symbol,
-                        addend: o.addend,
-                        module: o.module,
-                    })
-                })
-                .collect_vec();
-
-            // Add section symbols
-            let out_section_idx = split_obj.sections.next_section_index();
-            for (symbol_idx, symbol) in obj
-                .symbols
-                .for_section_range(section_index, current_address.address..=split_end.address)
-                .filter(|&(_, s)| {
-                    s.section == Some(section_index) && !is_linker_generated_label(&s.name)
-                })
-            {
-                if symbol_idxs[symbol_idx as usize].is_some() {
-                    continue; // should never happen?
+            while align > 4 {
+                align /= 2;
+                if current_address & (align as u32 - 1) == 0 {
+                    break;
                 }
+            }
+        }
+        ensure!(
+            current_address & (align as u32 - 1) == 0,
+            "Invalid alignment for split: {} {} {:#010X}",
+            split.unit,
+            section.name,
+            current_address
+        );
 
-                // TODO hack for gTRKInterruptVectorTableEnd
-                if (symbol.address == split_end.address as u64
-                    && symbol.name != "gTRKInterruptVectorTableEnd")
-                    || (symbol.address == current_address.address as u64
-                        && symbol.name == "gTRKInterruptVectorTableEnd")
-                {
-                    continue;
-                }
+        // Collect relocations; target_symbol will be updated later
+        let out_relocations = section
+            .relocations
+            .range(current_address.address..split_end.address)
+            .map(|(addr, o)| {
+                (addr - current_address.address, ObjReloc {
+                    kind: o.kind,
+                    target_symbol: o.target_symbol,
+                    addend: o.addend,
+                    module: o.module,
+                })
+            })
+            .collect_vec();
 
-                let new_index = split_obj.symbols.add_direct(ObjSymbol {
-                    name: symbol.name.clone(),
-                    demangled_name: symbol.demangled_name.clone(),
-                    address: if split.common {
-                        symbol.align.unwrap_or(4) as u64
-                    } else {
-                        symbol.address - current_address.address as u64
-                    },
-                    section: if split.common { None } else { Some(out_section_idx) },
-                    size: symbol.size,
-                    size_known: symbol.size_known,
-                    flags: if split.common {
-                        ObjSymbolFlagSet(symbol.flags.keep_flags() | ObjSymbolFlags::Common)
-                    } else {
-                        symbol.flags
-                    },
-                    kind: symbol.kind,
-                    align: symbol.align,
-                    data_kind: symbol.data_kind,
-                    name_hash: symbol.name_hash,
-                    demangled_name_hash: symbol.demangled_name_hash,
-                })?;
-                symbol_idxs[symbol_idx as usize] = Some(new_index);
+        // Add section symbols
+        let out_section_idx = split_obj.sections.next_section_index();
+        for (symbol_idx, symbol) in split_range_symbols(obj, range) {
+            if symbol_idxs[symbol_idx as usize].is_some() {
+                continue; // should never happen?
             }
 
-            // For mwldeppc 2.7 and above, a .comment section is required to link without error
-            // when common symbols are present. Automatically add one if needed.
-            if split.common && split_obj.mw_comment.is_none() {
-                split_obj.mw_comment = Some(MWComment::new(8)?);
-            }
+            let new_index = split_obj.symbols.add_direct(ObjSymbol {
+                name: symbol.name.clone(),
+                demangled_name: symbol.demangled_name.clone(),
+                address: if split.common {
+                    symbol.align.unwrap_or(4) as u64
+                } else {
+                    symbol.address - current_address.address as u64
+                },
+                section: if split.common { None } else { Some(out_section_idx) },
+                size: symbol.size,
+                size_known: symbol.size_known,
+                flags: if split.common {
+                    ObjSymbolFlagSet(symbol.flags.keep_flags() | ObjSymbolFlags::Common)
+                } else {
+                    symbol.flags
+                },
+                kind: symbol.kind,
+                align: symbol.align,
+                data_kind: symbol.data_kind,
+                st_other: symbol.st_other,
+                name_hash: symbol.name_hash,
+                demangled_name_hash: symbol.demangled_name_hash,
+            })?;
+            symbol_idxs[symbol_idx as usize] = Some(new_index);
+        }
 
-            if !split.common {
-                let data = match section.kind {
-                    ObjSectionKind::Bss => vec![],
-                    _ => section.data[(current_address.address as u64 - section.address) as usize
-                        ..(split_end.address as u64 - section.address) as usize]
-                        .to_vec(),
-                };
-                split_obj.sections.push(ObjSection {
-                    name: split.rename.as_ref().unwrap_or(&section.name).clone(),
-                    kind: section.kind,
-                    address: 0,
-                    size: split_end.address as u64 - current_address.address as u64,
-                    data,
-                    align,
-                    elf_index: out_section_idx + 1,
-                    relocations: ObjRelocations::new(out_relocations)?,
-                    virtual_address: Some(current_address.address as u64),
-                    file_offset: section.file_offset
-                        + (current_address.address as u64 - section.address),
-                    section_known: true,
-                    splits: Default::default(),
-                });
-            }
+        // For mwldeppc 2.7 and above, a .comment section is required to link without error
+        // when common symbols are present. Automatically add one if needed.
+        if split.common && split_obj.mw_comment.is_none() {
+            split_obj.mw_comment = Some(MWComment::new(8)?);
+        }
 
-            current_address = next_addr;
+        if !split.common {
+            let data = match section.kind {
+                ObjSectionKind::Bss => vec![],
+                _ => section.data[(current_address.address as u64 - section.address) as usize
+                    ..(split_end.address as u64 - section.address) as usize]
+                    .to_vec(),
+            };
+            split_obj.sections.push(ObjSection {
+                name: split.rename.as_ref().unwrap_or(&section.name).clone(),
+                kind: section.kind,
+                address: 0,
+                size: split_end.address as u64 - current_address.address as u64,
+                data,
+                align,
+                elf_index: out_section_idx + 1,
+                relocations: ObjRelocations::new(out_relocations)?,
+                virtual_address: Some(current_address.address as u64),
+                file_offset: section
+                    .file_offset
+                    .map(|offset| offset + (current_address.address as u64 - section.address)),
+                section_known: true,
+                alloc: section.alloc,
+                load: section.load,
+                splits: Default::default(),
+                segment: None,
+            });
         }
     }
 
-    // Update relocations
-    let mut globalize_symbols = vec![];
-    for (obj_idx, out_obj) in objects.iter_mut().enumerate() {
-        let symbol_idxs = &mut object_symbols[obj_idx];
-        for (_section_index, section) in out_obj.sections.iter_mut() {
-            for (reloc_address, reloc) in section.relocations.iter_mut() {
-                match symbol_idxs[reloc.target_symbol as usize] {
-                    Some(out_sym_idx) => {
-                        reloc.target_symbol = out_sym_idx;
-                    }
-                    None => {
-                        // Extern
-                        let out_sym_idx = out_obj.symbols.count();
-                        let target_sym = &obj.symbols[reloc.target_symbol];
-
-                        // If the symbol is local, we'll upgrade the scope to global
-                        // and rename it to avoid conflicts
-                        if target_sym.flags.is_local() {
-                            let address_str = if obj.module_id == 0 {
-                                format!("{:08X}", target_sym.address)
-                            } else if let Some(section_index) = target_sym.section {
-                                let target_section = &obj.sections[section_index];
-                                format!(
-                                    "{}_{}_{:X}",
-                                    obj.module_id,
-                                    target_section.name.trim_start_matches('.'),
-                                    target_sym.address
-                                )
-                            } else {
-                                bail!("Local symbol {} has no section", target_sym.name);
-                            };
-                            let new_name = if target_sym.name.ends_with(&address_str) {
-                                target_sym.name.clone()
-                            } else {
-                                format!("{}_{}", target_sym.name, address_str)
-                            };
-                            globalize_symbols.push((reloc.target_symbol, new_name));
-                        }
+    // Add externs for relocation targets outside of the split
+    for (_section_index, section) in split_obj.sections.iter() {
+        for (reloc_address, reloc) in section.relocations.iter() {
+            if symbol_idxs[reloc.target_symbol as usize].is_some() {
+                continue;
+            }
+            let out_sym_idx = split_obj.symbols.count();
+            let target_sym = &obj.symbols[reloc.target_symbol];
+            symbol_idxs[reloc.target_symbol as usize] = Some(out_sym_idx);
+            split_obj.symbols.add_direct(ObjSymbol {
+                name: target_sym.name.clone(),
+                demangled_name: target_sym.demangled_name.clone(),
+                ..Default::default()
+            })?;
 
-                        symbol_idxs[reloc.target_symbol as usize] = Some(out_sym_idx);
-                        out_obj.symbols.add_direct(ObjSymbol {
-                            name: target_sym.name.clone(),
-                            demangled_name: target_sym.demangled_name.clone(),
-                            ..Default::default()
-                        })?;
-                        reloc.target_symbol = out_sym_idx;
-
-                        if section.name.as_str() == "extabindex" {
-                            let Some((target_addr, target_split)) =
-                                section.splits.for_address(target_sym.address as u32)
-                            else {
-                                bail!(
-                                    "Bad extabindex relocation @ {:#010X}",
-                                    reloc_address as u64 + section.virtual_address.unwrap_or(0)
-                                );
-                            };
-                            let target_section = &obj.sections.at_address(target_addr)?.1.name;
-                            log::error!(
-                                "Bad extabindex relocation @ {:#010X}\n\
-                                \tSource object: {}:{:#010X} ({})\n\
-                                \tTarget object: {}:{:#010X} ({})\n\
-                                \tTarget symbol: {:#010X} ({})\n\
-                                This will cause the linker to crash.\n",
-                                reloc_address as u64 + section.virtual_address.unwrap_or(0),
-                                section.name,
-                                section.virtual_address.unwrap_or(0),
-                                out_obj.name,
-                                target_section,
-                                target_addr,
-                                target_split.unit,
-                                target_sym.address,
-                                target_sym.demangled_name.as_deref().unwrap_or(&target_sym.name),
-                            );
-                        }
-                    }
-                }
+            if section.name.as_str() == "extabindex" {
+                let Some((target_addr, target_split)) =
+                    section.splits.for_address(target_sym.address as u32)
+                else {
+                    bail!(
+                        "Bad extabindex relocation @ {:#010X}",
+                        reloc_address as u64 + section.virtual_address.unwrap_or(0)
+                    );
+                };
+                let target_section = &obj.sections.at_address(target_addr)?.1.name;
+                log::error!(
+                    "Bad extabindex relocation @ {:#010X}\n\
+                    \tSource object: {}:{:#010X} ({})\n\
+                    \tTarget object: {}:{:#010X} ({})\n\
+                    \tTarget symbol: {:#010X} ({})\n\
+                    This will cause the linker to crash.\n",
+                    reloc_address as u64 + section.virtual_address.unwrap_or(0),
+                    section.name,
+                    section.virtual_address.unwrap_or(0),
+                    split_obj.name,
+                    target_section,
+                    target_addr,
+                    target_split.unit,
+                    target_sym.address,
+                    target_sym.demangled_name.as_deref().unwrap_or(&target_sym.name),
+                );
             }
         }
     }
 
+    // Update relocations; every target was mapped above
+    split_obj.map_relocations(|_, _, reloc| {
+        reloc.target_symbol = symbol_idxs[reloc.target_symbol as usize].unwrap();
+    });
+
     // Upgrade local symbols to global if necessary
-    for (obj, symbol_map) in objects.iter_mut().zip(&object_symbols) {
-        for (globalize_idx, new_name) in &globalize_symbols {
-            if let Some(symbol_idx) = symbol_map[*globalize_idx as usize] {
-                let mut symbol = obj.symbols[symbol_idx].clone();
-                symbol.name.clone_from(new_name);
-                if symbol.flags.is_local() {
-                    log::debug!("Globalizing {} in {}", symbol.name, obj.name);
-                    symbol.flags.set_scope(ObjSymbolScope::Global);
-                }
-                obj.symbols.replace(symbol_idx, symbol)?;
+    for (globalize_idx, new_name) in globalize_symbols {
+        if let Some(symbol_idx) = symbol_idxs[*globalize_idx as usize] {
+            let mut symbol = split_obj.symbols[symbol_idx].clone();
+            symbol.name.clone_from(new_name);
+            if symbol.flags.is_local() {
+                log::debug!("Globalizing {} in {}", symbol.name, split_obj.name);
+                symbol.flags.set_scope(ObjSymbolScope::Global);
             }
+            split_obj.symbols.replace(symbol_idx, symbol)?;
         }
     }
 
     // Extern linker generated symbols
-    for obj in &mut objects {
-        let mut replace_symbols = vec![];
-        for (symbol_idx, symbol) in obj.symbols.iter() {
-            if is_linker_generated_label(&symbol.name) && symbol.section.is_some() {
-                log::debug!("Externing {:?} in {}", symbol, obj.name);
-                replace_symbols.push((symbol_idx, ObjSymbol {
-                    name: symbol.name.clone(),
-                    demangled_name: symbol.demangled_name.clone(),
-                    ..Default::default()
-                }));
-            }
-        }
-        for (symbol_idx, symbol) in replace_symbols {
-            obj.symbols.replace(symbol_idx, symbol)?;
+    let mut replace_symbols = vec![];
+    for (symbol_idx, symbol) in split_obj.symbols.iter() {
+        if is_linker_generated_label(&symbol.name) && symbol.section.is_some() {
+            log::debug!("Externing {:?} in {}", symbol, split_obj.name);
+            replace_symbols.push((symbol_idx, ObjSymbol {
+                name: symbol.name.clone(),
+                demangled_name: symbol.demangled_name.clone(),
+                ..Default::default()
+            }));
         }
     }
+    for (symbol_idx, symbol) in replace_symbols {
+        split_obj.symbols.replace(symbol_idx, symbol)?;
+    }
 
+    Ok(split_obj)
+}
+
+/// Split an object into multiple relocatable objects.
+#[instrument(level = "debug", skip(obj))]
+pub fn split_obj(obj: &ObjInfo, module_name: Option<&str>) -> Result<Vec<ObjInfo>> {
+    let mut objects = Vec::with_capacity(obj.link_order.len());
+    for_each_split_obj(obj, module_name, |_, split_obj| {
+        objects.push(split_obj);
+        Ok(())
+    })?;
     Ok(objects)
 }
 
+/// Split an object into multiple relocatable objects, passing each unit's object to `f` in
+/// link order as soon as it's built. Only one split object is held in memory at a time.
+pub fn for_each_split_obj<F>(obj: &ObjInfo, module_name: Option<&str>, mut f: F) -> Result<()>
+where F: FnMut(&ObjUnit, ObjInfo) -> Result<()> {
+    let ranges = collect_split_ranges(obj)?;
+    let globalize_symbols = globalized_symbols(obj, &ranges)?;
+    for (unit, unit_ranges) in obj.link_order.iter().zip(&ranges) {
+        f(unit, split_unit(obj, unit, unit_ranges, module_name, &globalize_symbols)?)?;
+    }
+    Ok(())
+}
+
+/// Splits an object into one relocatable object per unit, keyed by unit name in link
+/// order. Units with no splits other than skipped ones are omitted.
+pub fn split_all(obj: &ObjInfo) -> Result<Vec<(String, ObjInfo)>> {
+    let mut split_objs = vec![];
+    obj.for_each_split(|name, split_obj| {
+        split_objs.push((name.to_string(), split_obj));
+        Ok(())
+    })?;
+    Ok(split_objs)
+}
+
 /// mwld doesn't preserve the original section alignment values
 pub fn default_section_align(section: &ObjSection) -> u64 {
     match section.kind {
@@ -1445,3 +1523,111 @@ fn unit_exists(
             .any(|(_, split)| split.unit.eq_ignore_ascii_case(unit_name))
         || new_splits.values().any(|split| split.unit.eq_ignore_ascii_case(unit_name))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::testing::{test_section_words, test_symbol};
+
+    #[test]
+    fn test_split_all() {
+        let mut text = test_section_words(".text", ObjSectionKind::Code, 0x80003100, &[
+            0x38600000, 0x4E800020, 0x38600001, 0x4E800020,
+        ]);
+        let units = ["a.c", "b.c", "c.c", "d.c"];
+        for (i, unit) in units.iter().enumerate() {
+            let address = 0x80003100 + i as u32 * 4;
+            text.splits.push(address, ObjSplit {
+                unit: unit.to_string(),
+                end: address + 4,
+                align: None,
+                common: false,
+                autogenerated: false,
+                skip: *unit == "d.c",
+                rename: None,
+            });
+        }
+        let symbols = ["foo", "bar", "baz", "qux"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| test_symbol(name, 0, 0x80003100 + i as u64 * 4, 4))
+            .collect();
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "main.dol".to_string(),
+            symbols,
+            vec![text],
+        );
+        obj.link_order = units
+            .iter()
+            .map(|unit| ObjUnit {
+                name: unit.to_string(),
+                autogenerated: false,
+                comment_version: None,
+                order: None,
+            })
+            .collect();
+
+        let split_objs = split_all(&obj).unwrap();
+        let names = split_objs.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["a.c", "b.c", "c.c"]);
+        for ((_, split_obj), symbol) in split_objs.iter().zip(["foo", "bar", "baz"]) {
+            assert_eq!(split_obj.sections.len(), 1);
+            assert_eq!(split_obj.sections[0].size, 4);
+            let (_, symbol) = split_obj.symbols.by_name(symbol).unwrap().unwrap();
+            assert_eq!((symbol.section, symbol.address), (Some(0), 0));
+            assert!(crate::util::elf::write_elf(split_obj, false).is_ok());
+        }
+
+        let mut count = 0;
+        obj.for_each_split(|name, split_obj| {
+            assert_eq!(name, units[count]);
+            assert_eq!(split_obj.name, name);
+            count += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_split_overlay_rename() {
+        let mut text = test_section_words(".text.a", ObjSectionKind::Code, 0x80003100, &[
+            0x4E800020, 0x4E800020,
+        ]);
+        for (i, (unit, rename)) in [("a.c", None), ("b.c", Some(".text"))].into_iter().enumerate() {
+            let address = 0x80003100 + i as u32 * 4;
+            text.splits.push(address, ObjSplit {
+                unit: unit.to_string(),
+                end: address + 4,
+                align: None,
+                common: false,
+                autogenerated: false,
+                skip: false,
+                rename: rename.map(str::to_string),
+            });
+        }
+        let mut obj = ObjInfo::new(
+            ObjKind::Executable,
+            ObjArchitecture::PowerPc,
+            "main.dol".to_string(),
+            vec![test_symbol("foo", 0, 0x80003100, 4), test_symbol("bar", 0, 0x80003104, 4)],
+            vec![text],
+        );
+        obj.link_order = ["a.c", "b.c"]
+            .iter()
+            .map(|unit| ObjUnit {
+                name: unit.to_string(),
+                autogenerated: false,
+                comment_version: None,
+                order: None,
+            })
+            .collect();
+
+        // The overlay suffix is kept unless the split is explicitly renamed
+        let split_objs = split_obj(&obj, None).unwrap();
+        let names = split_objs.iter().map(|o| o.sections[0].name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec![".text.a", ".text"]);
+    }
+}